@@ -1,6 +1,5 @@
 import { NextRequest, NextResponse } from 'next/server';
 import { PublicKey } from '@solana/web3.js';
-import nacl from 'tweetnacl';
 import { createHash } from 'crypto';
 import {
   DEFAULT_INITIAL_PRICE,
@@ -15,6 +14,10 @@ import {
   type PayloadRaw,
 } from '@/services/shield-oracle/src/shield_score';
 import { oracleKeypair, oraclePubkeyBase58 } from '@/services/shield-oracle/src/oracle';
+import {
+  DEFAULT_SIGNING_PURPOSES,
+  signShieldPayloadForPurposes,
+} from '@/services/shield-oracle/src/signing_domain';
 
 function toHex(bytes: Uint8Array): string {
   return Buffer.from(bytes).toString('hex');
@@ -74,7 +77,11 @@ export async function POST(req: NextRequest) {
 
     const payloadBytes = serializeShieldPayloadV0(payload);
     const oracle = oracleKeypair();
-    const signature = nacl.sign.detached(payloadBytes, oracle.secretKey);
+    const signed = signShieldPayloadForPurposes(
+      payloadBytes,
+      oracle.secretKey,
+      DEFAULT_SIGNING_PURPOSES,
+    );
 
     const scoringModelHashHex = toHex(Uint8Array.from(SCORING_MODEL_HASH));
     return NextResponse.json({
@@ -95,7 +102,10 @@ export async function POST(req: NextRequest) {
         nonce: payload.nonce.toString(),
       },
       payload_hex: toHex(payloadBytes),
-      oracle_signature_hex: toHex(signature),
+      oracle_signature_hex: signed.oracle_signatures.preview,
+      signing_purpose: 'preview',
+      signing_domain: signed.signing_domain,
+      oracle_signatures: signed.oracle_signatures,
       oracle_pubkey: oraclePubkeyBase58(),
       scoring_model_hash_hex: scoringModelHashHex,
     });
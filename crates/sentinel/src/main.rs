@@ -27,11 +27,13 @@ use tokio::sync::{mpsc, Mutex};
 use tokio::time::sleep;
 use tracing::{debug, error, info, warn};
 use tracing_subscriber::EnvFilter;
+// Shared with the program so account offsets cannot drift.
+use unforgiven_v2_layout::{account_layout, quote_return};
 
 const PAYLOAD_V0_LEN: usize = 141;
-const PREVIEW_EVENT_NAME: &str = "PreviewPriceEvent";
 const SET_MODEL_HASH_IX_NAME: &str = "set_scoring_model_hash";
-const LOG_PREFIX_PROGRAM_DATA: &str = "Program data: ";
+const LOG_PREFIX_PROGRAM_RETURN: &str = "Program return: ";
+const SIGNING_PURPOSE_PREVIEW: u8 = 1;
 const USER_MODE_VERIFIED: u8 = 2;
 
 #[derive(Debug, Clone, Deserialize)]
@@ -463,12 +465,9 @@ struct GovernanceExecutor {
 
 impl GovernanceExecutor {
     fn parse_admin_active_hash(data: &[u8]) -> Option<[u8; 32]> {
-        // Anchor layout: 8 disc + authority(32) + oracle_pubkey(32) + active_hash(32) + bump(1)
-        if data.len() < 105 {
-            return None;
-        }
+        use account_layout::admin_config::{ACTIVE_SCORING_MODEL_HASH, BUMP};
         let mut out = [0u8; 32];
-        out.copy_from_slice(&data[72..104]);
+        out.copy_from_slice(data.get(ACTIVE_SCORING_MODEL_HASH..BUMP)?);
         Some(out)
     }
 
@@ -1086,7 +1085,7 @@ async fn subscribe_preview_events(
             if logs.err.is_some() {
                 continue;
             }
-            let Some(event) = decode_preview_event(logs) else {
+            let Some(event) = decode_preview_event(logs, &cfg.solana.program_id) else {
                 continue;
             };
             debug!(
@@ -1117,53 +1116,43 @@ async fn subscribe_preview_events(
     }
 }
 
-fn decode_preview_event(logs: &RpcLogsResponse) -> Option<PreviewPriceEventWire> {
-    let discriminator = anchor_discriminator("event", PREVIEW_EVENT_NAME);
+// `preview_price` hands its quote back as `QuoteReturnV0` return data, which the runtime
+// logs as "Program return: <program_id> <base64>".
+fn decode_preview_event(logs: &RpcLogsResponse, program_id: &str) -> Option<PreviewPriceEventWire> {
     for line in &logs.logs {
-        let encoded = match line.strip_prefix(LOG_PREFIX_PROGRAM_DATA) {
+        let (returning_program, encoded) = match line
+            .strip_prefix(LOG_PREFIX_PROGRAM_RETURN)
+            .and_then(|v| v.split_once(' '))
+        {
             Some(v) => v,
             None => continue,
         };
+        if returning_program != program_id {
+            continue;
+        }
 
         let raw = match BASE64_STANDARD.decode(encoded) {
             Ok(v) => v,
             Err(_) => continue,
         };
-        if raw.len() < 8 || raw[..8] != discriminator {
-            continue;
-        }
-        if let Some(event) = decode_preview_event_body(&raw[8..]) {
-            return Some(event);
-        }
+        let quote = match quote_return::decode_v0(&raw) {
+            Some(v) if v.purpose == SIGNING_PURPOSE_PREVIEW => v,
+            _ => continue,
+        };
+        return Some(PreviewPriceEventWire {
+            final_price: quote.final_price,
+            is_infinite: quote.is_infinite,
+            blocked: quote.blocked,
+            effective_velocity_bps: quote.effective_velocity_bps,
+            dignity_score: quote.dignity_score,
+            adapter_mask: quote.adapter_mask,
+            dignity_bucket: quote.dignity_bucket,
+            user_mode: quote.user_mode,
+        });
     }
     None
 }
 
-fn decode_preview_event_body(bytes: &[u8]) -> Option<PreviewPriceEventWire> {
-    if bytes.len() < 22 {
-        return None;
-    }
-    let final_price = u64::from_le_bytes(bytes[0..8].try_into().ok()?);
-    let is_infinite = bytes[8] != 0;
-    let blocked = bytes[9] != 0;
-    let effective_velocity_bps = i64::from_le_bytes(bytes[10..18].try_into().ok()?);
-    let dignity_score = bytes[18];
-    let adapter_mask = bytes[19];
-    let dignity_bucket = bytes[20];
-    let user_mode = bytes[21];
-
-    Some(PreviewPriceEventWire {
-        final_price,
-        is_infinite,
-        blocked,
-        effective_velocity_bps,
-        dignity_score,
-        adapter_mask,
-        dignity_bucket,
-        user_mode,
-    })
-}
-
 fn compute_velocity_growth_pct(samples: &VecDeque<EventSample>) -> f64 {
     if samples.len() < 2 {
         return 0.0;
@@ -1231,12 +1220,14 @@ fn build_set_scoring_model_hash_ix(
     let mut data = Vec::with_capacity(8 + 32);
     data.extend_from_slice(&anchor_discriminator("global", SET_MODEL_HASH_IX_NAME));
     data.extend_from_slice(&model_hash);
+    let (audit_log_pubkey, _) = Pubkey::find_program_address(&[b"audit_log_v2"], &program_id);
 
     Instruction {
         program_id,
         accounts: vec![
             AccountMeta::new_readonly(authority_pubkey, true),
             AccountMeta::new(admin_config_pubkey, false),
+            AccountMeta::new(audit_log_pubkey, false),
         ],
         data,
     }
@@ -0,0 +1,89 @@
+// Byte layouts of the accounts that are written or read without Anchor deserialization
+// (the execute-path writers and off-chain readers such as sentinel). Every offset is
+// derived from the field before it, so a new field is one line before `LAYOUT_VERSION`
+// plus a bump of `VERSION`. Fields are only ever appended; `migrate_*` reallocs older
+// accounts up to `LEN`.
+
+pub const DISCRIMINATOR_LEN: usize = 8;
+pub const ZK_PROVIDER_ALLOWLIST_LEN: usize = 32;
+
+const PUBKEY_LEN: usize = 32;
+const HASH_LEN: usize = 32;
+const U64_LEN: usize = 8;
+const U16_LEN: usize = 2;
+const U8_LEN: usize = 1;
+
+pub mod global_config_v2 {
+    use super::*;
+
+    pub const VERSION: u8 = 1;
+
+    pub const AUTHORITY: usize = DISCRIMINATOR_LEN;
+    pub const BUMP: usize = AUTHORITY + PUBKEY_LEN;
+    pub const LAYOUT_VERSION: usize = BUMP + U8_LEN;
+    pub const LEN: usize = LAYOUT_VERSION + U8_LEN;
+}
+
+pub mod admin_config {
+    use super::*;
+
+    pub const VERSION: u8 = 1;
+
+    pub const AUTHORITY: usize = DISCRIMINATOR_LEN;
+    pub const ORACLE_PUBKEY: usize = AUTHORITY + PUBKEY_LEN;
+    pub const ACTIVE_SCORING_MODEL_HASH: usize = ORACLE_PUBKEY + PUBKEY_LEN;
+    pub const BUMP: usize = ACTIVE_SCORING_MODEL_HASH + HASH_LEN;
+    pub const ZK_PROVIDER_ALLOWLIST: usize = BUMP + U8_LEN;
+    pub const CLUSTER_ID: usize = ZK_PROVIDER_ALLOWLIST + ZK_PROVIDER_ALLOWLIST_LEN;
+    pub const LAYOUT_VERSION: usize = CLUSTER_ID + HASH_LEN;
+    pub const LEN: usize = LAYOUT_VERSION + U8_LEN;
+}
+
+pub mod ticket_receipt {
+    use super::*;
+
+    pub const VERSION: u8 = 1;
+
+    pub const MINT: usize = DISCRIMINATOR_LEN;
+    pub const EVENT_KEY: usize = MINT + PUBKEY_LEN;
+    pub const ORIGINAL_BUYER: usize = EVENT_KEY + PUBKEY_LEN;
+    pub const CURRENT_HOLDER: usize = ORIGINAL_BUYER + PUBKEY_LEN;
+    pub const PURCHASE_PRICE: usize = CURRENT_HOLDER + PUBKEY_LEN;
+    pub const LAST_SALE_PRICE: usize = PURCHASE_PRICE + U64_LEN;
+    pub const ISSUED_AT: usize = LAST_SALE_PRICE + U64_LEN;
+    pub const LAST_TRANSFER_AT: usize = ISSUED_AT + U64_LEN;
+    pub const NONCE: usize = LAST_TRANSFER_AT + U64_LEN;
+    pub const ZK_PROOF_HASH: usize = NONCE + U64_LEN;
+    pub const LISTED: usize = ZK_PROOF_HASH + HASH_LEN;
+    pub const RESALE_COUNT: usize = LISTED + U8_LEN;
+    pub const BUMP: usize = RESALE_COUNT + U64_LEN;
+    pub const HOLDER_USER_MODE: usize = BUMP + U8_LEN;
+    pub const CLASS_ID: usize = HOLDER_USER_MODE + U8_LEN;
+    pub const TRANSFER_COUNT: usize = CLASS_ID + U8_LEN;
+    pub const ENTRY_SECRET_COMMITMENT: usize = TRANSFER_COUNT + U16_LEN;
+    pub const ENTRY_SECRET_ROTATED_AT: usize = ENTRY_SECRET_COMMITMENT + HASH_LEN;
+    pub const REDEEMED_AT: usize = ENTRY_SECRET_ROTATED_AT + U64_LEN;
+    pub const LAYOUT_VERSION: usize = REDEEMED_AT + U64_LEN;
+    pub const LEN: usize = LAYOUT_VERSION + U8_LEN;
+}
+
+pub mod proof_use {
+    use super::*;
+
+    pub const USER_PUBKEY: usize = DISCRIMINATOR_LEN;
+    pub const ZK_PROOF_HASH: usize = USER_PUBKEY + PUBKEY_LEN;
+    pub const NONCE: usize = ZK_PROOF_HASH + HASH_LEN;
+    pub const USED_AT: usize = NONCE + U64_LEN;
+    pub const BUMP: usize = USED_AT + U64_LEN;
+    pub const LEN: usize = BUMP + U8_LEN;
+}
+
+pub mod proof_quota {
+    use super::*;
+
+    pub const EVENT_KEY: usize = DISCRIMINATOR_LEN;
+    pub const ZK_PROOF_HASH: usize = EVENT_KEY + PUBKEY_LEN;
+    pub const USES: usize = ZK_PROOF_HASH + HASH_LEN;
+    pub const BUMP: usize = USES + U16_LEN;
+    pub const LEN: usize = BUMP + U8_LEN;
+}
@@ -0,0 +1,6 @@
+// Fixed byte layouts written by the program and read back off-chain (sentinel, simulation
+// clients). The crate has no dependencies, so a reader can decode program data without
+// pulling in Anchor or matching the program's Solana version.
+
+pub mod account_layout;
+pub mod quote_return;
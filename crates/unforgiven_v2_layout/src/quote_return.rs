@@ -0,0 +1,66 @@
+// `QuoteReturnV0`, handed back through `set_return_data`; see PAYLOAD_V0_SPEC.md.
+
+pub const VERSION_V0: u8 = 0;
+pub const V0_LEN: usize = 64;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct QuoteReturnV0 {
+    pub purpose: u8,
+    pub final_price: u64,
+    pub is_infinite: bool,
+    pub blocked: bool,
+    pub effective_velocity_bps: i64,
+    pub dignity_score: u8,
+    pub adapter_mask: u8,
+    pub dignity_bucket: u8,
+    pub user_mode: u8,
+    pub nonce: u64,
+    pub zk_proof_hash: [u8; 32],
+}
+
+pub fn encode_v0(quote: &QuoteReturnV0) -> [u8; V0_LEN] {
+    let mut out = [0u8; V0_LEN];
+
+    out[0] = VERSION_V0;
+    out[1] = quote.purpose;
+    out[2..10].copy_from_slice(&quote.final_price.to_le_bytes());
+    out[10] = u8::from(quote.is_infinite);
+    out[11] = u8::from(quote.blocked);
+    out[12..20].copy_from_slice(&quote.effective_velocity_bps.to_le_bytes());
+    out[20] = quote.dignity_score;
+    out[21] = quote.adapter_mask;
+    out[22] = quote.dignity_bucket;
+    out[23] = quote.user_mode;
+    out[24..32].copy_from_slice(&quote.nonce.to_le_bytes());
+    out[32..64].copy_from_slice(&quote.zk_proof_hash);
+
+    out
+}
+
+// Rejects any other length or version.
+pub fn decode_v0(data: &[u8]) -> Option<QuoteReturnV0> {
+    if data.len() != V0_LEN || data[0] != VERSION_V0 {
+        return None;
+    }
+    let u64_at = |offset: usize| {
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&data[offset..offset + 8]);
+        bytes
+    };
+    let mut zk_proof_hash = [0u8; 32];
+    zk_proof_hash.copy_from_slice(&data[32..64]);
+
+    Some(QuoteReturnV0 {
+        purpose: data[1],
+        final_price: u64::from_le_bytes(u64_at(2)),
+        is_infinite: data[10] != 0,
+        blocked: data[11] != 0,
+        effective_velocity_bps: i64::from_le_bytes(u64_at(12)),
+        dignity_score: data[20],
+        adapter_mask: data[21],
+        dignity_bucket: data[22],
+        user_mode: data[23],
+        nonce: u64::from_le_bytes(u64_at(24)),
+        zk_proof_hash,
+    })
+}
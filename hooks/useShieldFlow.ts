@@ -2,10 +2,20 @@
 
 import { useCallback, useState } from 'react';
 import { useConnection, useWallet } from '@solana/wallet-adapter-react';
-import { ComputeBudgetProgram, PublicKey, Transaction } from '@solana/web3.js';
+import {
+  ComputeBudgetProgram,
+  PublicKey,
+  Transaction,
+  type TransactionInstruction,
+} from '@solana/web3.js';
 import {
   buildExecuteInstructions,
+  buildInitializeProofQuotaInstruction,
+  buildInitializeReputationInstruction,
   fetchProtocolState,
+  findMembershipPda,
+  findProofQuotaPdaWithBump,
+  findReputationPda,
   hexToBytes,
   normalizeSignedProofPayload,
   parseExecutionEventFromLogs,
@@ -219,13 +229,38 @@ export function useShieldFlow(programId: PublicKey | null) {
       }
 
       const payloadBytes = hexToBytes(activeQuote.payloadHex);
-      const oracleSignatureBytes = hexToBytes(activeQuote.oracleSignatureHex);
+      const oracleSignatureBytes = hexToBytes(activeQuote.executeOracleSignatureHex);
       if (payloadBytes.length !== 141 || oracleSignatureBytes.length !== 64) {
         throw new Error(
           `Quote payload/signature length mismatch: payload=${payloadBytes.length} (expected 141), signature=${oracleSignatureBytes.length} (expected 64). This can cause on-chain Access violation.`,
         );
       }
       const oraclePubkeyBytes = new PublicKey(activeQuote.oraclePubkey).toBytes();
+      // execute_shield needs the proof's quota and the buyer's reputation to exist already.
+      const zkProofHash = payloadBytes.slice(61, 93);
+      const [quotaInfo, reputationInfo, membershipInfo] = await connection.getMultipleAccountsInfo(
+        [
+          findProofQuotaPdaWithBump(programId, zkProofHash)[0],
+          findReputationPda(programId, wallet.publicKey),
+          findMembershipPda(programId, wallet.publicKey),
+        ],
+        'confirmed',
+      );
+      const setupIxs: TransactionInstruction[] = [];
+      if (!quotaInfo) {
+        setupIxs.push(
+          buildInitializeProofQuotaInstruction({ programId, payerPubkey: wallet.publicKey, zkProofHash }),
+        );
+      }
+      if (!reputationInfo) {
+        setupIxs.push(
+          buildInitializeReputationInstruction({
+            programId,
+            payerPubkey: wallet.publicKey,
+            walletPubkey: wallet.publicKey,
+          }),
+        );
+      }
       const { ed25519Ix, executeIx, ticketMintPda } = buildExecuteInstructions({
         programId,
         userPubkey: wallet.publicKey,
@@ -233,10 +268,16 @@ export function useShieldFlow(programId: PublicKey | null) {
         payloadBytes,
         oracleSignatureBytes,
         oraclePubkeyBytes,
+        clusterId: new PublicKey(activeQuote.clusterId).toBytes(),
+        ticketClass: activeQuote.ticketClass,
+        // Compute budget, then setup, then the Ed25519 instruction.
+        sigInstructionIndex: 1 + setupIxs.length,
+        withMembership: !!membershipInfo,
       });
 
       const tx = new Transaction().add(
         ComputeBudgetProgram.setComputeUnitLimit({ units: 900_000 }),
+        ...setupIxs,
         ed25519Ix,
         executeIx,
       );
@@ -33,6 +33,12 @@ export type ShieldScoreApiResponse = {
   };
   payload_hex: string;
   oracle_signature_hex: string;
+  signing_domain?: {
+    program_id: string;
+    cluster_id: string;
+    ticket_class: number;
+  };
+  oracle_signatures?: Partial<Record<string, string>>;
   oracle_pubkey: string;
   scoring_model_hash_hex: string;
 };
@@ -53,6 +59,9 @@ export type ShieldQuote = {
   oraclePubkey: string;
   payloadHex: string;
   oracleSignatureHex: string;
+  executeOracleSignatureHex: string;
+  clusterId: string;
+  ticketClass: number;
   scoringModelHashHex: string;
   nonce: bigint;
 };
@@ -73,6 +82,14 @@ export type AdminConfigSnapshot = {
   oraclePubkey: Uint8Array;
   activeScoringModelHash: Uint8Array;
   bump: number;
+  clusterId: Uint8Array | null;
+};
+
+export type SigningDomain = {
+  programId: PublicKey;
+  clusterId: Uint8Array;
+  purpose: number;
+  ticketClass?: number;
 };
 
 export type ShieldExecutionEvent = {
@@ -133,6 +150,22 @@ const TICKET_TOKEN_SEED = utf8ToBytes('ticket_token_v2');
 const TICKET_RECEIPT_SEED = utf8ToBytes('ticket_receipt_v2');
 const TICKET_LISTING_SEED = utf8ToBytes('ticket_listing_v2');
 const TICKET_ESCROW_SEED = utf8ToBytes('ticket_escrow_v2');
+const SALE_CONFIG_SEED = utf8ToBytes('sale_config_v2');
+const PROOF_QUOTA_SEED = utf8ToBytes('proof_quota_v2');
+const TICKET_CLASS_SEED = utf8ToBytes('ticket_class_v2');
+const MEMBERSHIP_SEED = utf8ToBytes('membership_v2');
+const REPUTATION_SEED = utf8ToBytes('reputation_v2');
+const SHIELD_DOMAIN_TAG = utf8ToBytes('UNFORGIVEN_SHIELD_V0');
+const SHIELD_DOMAIN_PROGRAM_ID_OFFSET = SHIELD_DOMAIN_TAG.length;
+const SHIELD_DOMAIN_CLUSTER_ID_OFFSET = SHIELD_DOMAIN_PROGRAM_ID_OFFSET + 32;
+const SHIELD_DOMAIN_PURPOSE_OFFSET = SHIELD_DOMAIN_CLUSTER_ID_OFFSET + 32;
+const SHIELD_DOMAIN_TICKET_CLASS_OFFSET = SHIELD_DOMAIN_PURPOSE_OFFSET + 1;
+export const SHIELD_DOMAIN_LEN = SHIELD_DOMAIN_TICKET_CLASS_OFFSET + 1;
+export const SIGNING_PURPOSE_PREVIEW = 1;
+export const SIGNING_PURPOSE_EXECUTE = 2;
+export const TICKET_CLASS_NONE = 0;
+// AdminConfig: authority, oracle_pubkey, model hash, bump, zk provider allowlist, cluster_id.
+const ADMIN_CONFIG_CLUSTER_ID_OFFSET = 8 + 32 + 32 + 32 + 1 + 32;
 
 function boolFromByte(bytes: Uint8Array, offset: number): boolean {
   return bytes[offset] !== 0;
@@ -250,40 +283,56 @@ export function findAdminConfigPda(programId: PublicKey): PublicKey {
   return PublicKey.findProgramAddressSync([utf8ToBytes('admin_config_v2')], programId)[0];
 }
 
-export function findProofUsePda(programId: PublicKey, payloadBytes: Uint8Array): PublicKey {
+// ProofUse and ticket mint seeds: user_pubkey, zk_proof_hash and nonce from the payload.
+function payloadSeeds(payloadBytes: Uint8Array): Uint8Array[] {
   if (payloadBytes.length !== 141) {
     throw new Error(`payload length mismatch: expected 141, got ${payloadBytes.length}`);
   }
+  return [payloadBytes.slice(1, 33), payloadBytes.slice(61, 93), payloadBytes.slice(133, 141)];
+}
 
+export function findProofUsePdaWithBump(
+  programId: PublicKey,
+  payloadBytes: Uint8Array,
+): [PublicKey, number] {
   return PublicKey.findProgramAddressSync(
-    [
-      utf8ToBytes('proof_use'),
-      payloadBytes.slice(1, 33),
-      payloadBytes.slice(61, 93),
-      payloadBytes.slice(133, 141),
-    ],
+    [utf8ToBytes('proof_use'), ...payloadSeeds(payloadBytes)],
     programId,
-  )[0];
+  );
+}
+
+export function findProofUsePda(programId: PublicKey, payloadBytes: Uint8Array): PublicKey {
+  return findProofUsePdaWithBump(programId, payloadBytes)[0];
+}
+
+export function findTicketMintAuthorityPdaWithBump(programId: PublicKey): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync([TICKET_MINT_AUTHORITY_SEED], programId);
 }
 
 export function findTicketMintAuthorityPda(programId: PublicKey): PublicKey {
-  return PublicKey.findProgramAddressSync([TICKET_MINT_AUTHORITY_SEED], programId)[0];
+  return findTicketMintAuthorityPdaWithBump(programId)[0];
+}
+
+export function findTicketMintPdaWithBump(
+  programId: PublicKey,
+  payloadBytes: Uint8Array,
+): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync([TICKET_MINT_SEED, ...payloadSeeds(payloadBytes)], programId);
 }
 
 export function findTicketMintPda(programId: PublicKey, payloadBytes: Uint8Array): PublicKey {
-  if (payloadBytes.length !== 141) {
-    throw new Error(`payload length mismatch: expected 141, got ${payloadBytes.length}`);
-  }
+  return findTicketMintPdaWithBump(programId, payloadBytes)[0];
+}
 
+export function findTicketTokenPdaWithBump(
+  programId: PublicKey,
+  ticketMint: PublicKey,
+  owner: PublicKey,
+): [PublicKey, number] {
   return PublicKey.findProgramAddressSync(
-    [
-      TICKET_MINT_SEED,
-      payloadBytes.slice(1, 33),
-      payloadBytes.slice(61, 93),
-      payloadBytes.slice(133, 141),
-    ],
+    [TICKET_TOKEN_SEED, ticketMint.toBuffer(), owner.toBuffer()],
     programId,
-  )[0];
+  );
 }
 
 export function findTicketTokenPda(
@@ -291,14 +340,57 @@ export function findTicketTokenPda(
   ticketMint: PublicKey,
   owner: PublicKey,
 ): PublicKey {
+  return findTicketTokenPdaWithBump(programId, ticketMint, owner)[0];
+}
+
+export function findTicketReceiptPdaWithBump(
+  programId: PublicKey,
+  ticketMint: PublicKey,
+): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync([TICKET_RECEIPT_SEED, ticketMint.toBuffer()], programId);
+}
+
+export function findTicketReceiptPda(programId: PublicKey, ticketMint: PublicKey): PublicKey {
+  return findTicketReceiptPdaWithBump(programId, ticketMint)[0];
+}
+
+export function findSaleConfigPda(programId: PublicKey): PublicKey {
   return PublicKey.findProgramAddressSync(
-    [TICKET_TOKEN_SEED, ticketMint.toBuffer(), owner.toBuffer()],
+    [SALE_CONFIG_SEED, findGlobalConfigV2Pda(programId).toBuffer()],
     programId,
   )[0];
 }
 
-export function findTicketReceiptPda(programId: PublicKey, ticketMint: PublicKey): PublicKey {
-  return PublicKey.findProgramAddressSync([TICKET_RECEIPT_SEED, ticketMint.toBuffer()], programId)[0];
+export function findProofQuotaPdaWithBump(
+  programId: PublicKey,
+  zkProofHash: Uint8Array,
+): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [PROOF_QUOTA_SEED, findGlobalConfigV2Pda(programId).toBuffer(), zkProofHash],
+    programId,
+  );
+}
+
+export function findTicketClassPda(programId: PublicKey, classId: number): PublicKey {
+  return PublicKey.findProgramAddressSync(
+    [TICKET_CLASS_SEED, findGlobalConfigV2Pda(programId).toBuffer(), Uint8Array.of(classId)],
+    programId,
+  )[0];
+}
+
+export function findMembershipPda(programId: PublicKey, wallet: PublicKey): PublicKey {
+  return PublicKey.findProgramAddressSync([MEMBERSHIP_SEED, wallet.toBuffer()], programId)[0];
+}
+
+export function findReputationPdaWithBump(
+  programId: PublicKey,
+  wallet: PublicKey,
+): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync([REPUTATION_SEED, wallet.toBuffer()], programId);
+}
+
+export function findReputationPda(programId: PublicKey, wallet: PublicKey): PublicKey {
+  return findReputationPdaWithBump(programId, wallet)[0];
 }
 
 export function findTicketListingPda(programId: PublicKey, ticketMint: PublicKey): PublicKey {
@@ -309,6 +401,29 @@ export function findTicketEscrowPda(programId: PublicKey, ticketMint: PublicKey)
   return PublicKey.findProgramAddressSync([TICKET_ESCROW_SEED, ticketMint.toBuffer()], programId)[0];
 }
 
+// Message the oracle signs and the Ed25519 instruction must carry: the 86-byte signing
+// domain followed by the 141-byte payload (docs/v2/PAYLOAD_V0_SPEC.md).
+export function serializeShieldMessageV0(
+  payloadBytes: Uint8Array,
+  domain: SigningDomain,
+): Uint8Array {
+  if (payloadBytes.length !== 141) {
+    throw new Error(`payload length mismatch: expected 141, got ${payloadBytes.length}`);
+  }
+  if (domain.clusterId.length !== 32) {
+    throw new Error(`cluster id length mismatch: expected 32, got ${domain.clusterId.length}`);
+  }
+
+  const out = new Uint8Array(SHIELD_DOMAIN_LEN + payloadBytes.length);
+  out.set(SHIELD_DOMAIN_TAG, 0);
+  out.set(domain.programId.toBytes(), SHIELD_DOMAIN_PROGRAM_ID_OFFSET);
+  out.set(domain.clusterId, SHIELD_DOMAIN_CLUSTER_ID_OFFSET);
+  out[SHIELD_DOMAIN_PURPOSE_OFFSET] = domain.purpose;
+  out[SHIELD_DOMAIN_TICKET_CLASS_OFFSET] = domain.ticketClass ?? TICKET_CLASS_NONE;
+  out.set(payloadBytes, SHIELD_DOMAIN_LEN);
+  return out;
+}
+
 export function calculateShieldQuote(input: {
   initialPriceLamports: bigint;
   salesVelocityBps: bigint;
@@ -372,6 +487,10 @@ export function shieldQuoteFromApiResponse(
     timeElapsedSecs,
     dignityScore,
   });
+  const executeSignatureHex = response.oracle_signatures?.execute;
+  if (!response.signing_domain || !executeSignatureHex) {
+    throw new Error('shield-score response has no signing domain or execute signature');
+  }
 
   return {
     mode,
@@ -389,6 +508,9 @@ export function shieldQuoteFromApiResponse(
     oraclePubkey: response.oracle_pubkey,
     payloadHex: response.payload_hex,
     oracleSignatureHex: response.oracle_signature_hex,
+    executeOracleSignatureHex: executeSignatureHex,
+    clusterId: response.signing_domain.cluster_id,
+    ticketClass: response.signing_domain.ticket_class,
     scoringModelHashHex: response.scoring_model_hash_hex,
     nonce: BigInt(response.payload.nonce),
   };
@@ -409,6 +531,10 @@ export function parseAdminConfigAccount(data: Uint8Array): AdminConfigSnapshot |
     oraclePubkey: data.slice(40, 72),
     activeScoringModelHash: data.slice(72, 104),
     bump: data[104] ?? 0,
+    clusterId:
+      data.length >= ADMIN_CONFIG_CLUSTER_ID_OFFSET + 32
+        ? data.slice(ADMIN_CONFIG_CLUSTER_ID_OFFSET, ADMIN_CONFIG_CLUSTER_ID_OFFSET + 32)
+        : null,
   };
 }
 
@@ -488,9 +614,26 @@ export function buildPreviewInstructionData(
 const EXECUTE_SHIELD_PAYLOAD_LEN = 141;
 const ORACLE_SIGNATURE_LEN = 64;
 
+// Canonical bumps for the PDAs execute_shield checks, in the program's field order.
+export type ShieldAccountBumps = {
+  proofUse: number;
+  ticketMint: number;
+  ticketMintAuthority: number;
+  userTicketToken: number;
+  ticketReceipt: number;
+  proofQuota: number;
+  reputation: number;
+};
+
+function encodeU16LE(value: number): Uint8Array {
+  return Uint8Array.of(value & 0xff, (value >> 8) & 0xff);
+}
+
 export function buildExecuteInstructionData(
   payloadBytes: Uint8Array,
   oracleSignatureBytes: Uint8Array,
+  sigInstructionIndex: number,
+  bumps: ShieldAccountBumps,
 ): Buffer {
   if (payloadBytes.length !== EXECUTE_SHIELD_PAYLOAD_LEN) {
     throw new Error(
@@ -507,6 +650,16 @@ export function buildExecuteInstructionData(
       anchorDiscriminator(GLOBAL_NAMESPACE, 'execute_shield'),
       payloadBytes,
       oracleSignatureBytes,
+      encodeU16LE(sigInstructionIndex),
+      Uint8Array.of(
+        bumps.proofUse,
+        bumps.ticketMint,
+        bumps.ticketMintAuthority,
+        bumps.userTicketToken,
+        bumps.ticketReceipt,
+        bumps.proofQuota,
+        bumps.reputation,
+      ),
     ),
   );
 }
@@ -528,24 +681,64 @@ export function buildExecuteInstructions(input: {
   payloadBytes: Uint8Array;
   oracleSignatureBytes: Uint8Array;
   oraclePubkeyBytes: Uint8Array;
+  clusterId: Uint8Array;
+  ticketClass?: number;
+  // Index of the Ed25519 instruction within the transaction.
+  sigInstructionIndex: number;
+  // Pass the buyer's Membership when the ticket class requires a tier.
+  withMembership?: boolean;
 }): {
   ed25519Ix: TransactionInstruction;
   executeIx: TransactionInstruction;
   ticketMintPda: PublicKey;
   ticketReceiptPda: PublicKey;
+  proofQuotaPda: PublicKey;
+  reputationPda: PublicKey;
 } {
   const adminConfigPda = findAdminConfigPda(input.programId);
   const globalConfigPda = findGlobalConfigV2Pda(input.programId);
-  const proofUsePda = findProofUsePda(input.programId, input.payloadBytes);
-  const ticketMintPda = findTicketMintPda(input.programId, input.payloadBytes);
-  const ticketMintAuthorityPda = findTicketMintAuthorityPda(input.programId);
-  const userTicketTokenPda = findTicketTokenPda(input.programId, ticketMintPda, input.userPubkey);
-  const ticketReceiptPda = findTicketReceiptPda(input.programId, ticketMintPda);
+  const [proofUsePda, proofUseBump] = findProofUsePdaWithBump(input.programId, input.payloadBytes);
+  const [ticketMintPda, ticketMintBump] = findTicketMintPdaWithBump(
+    input.programId,
+    input.payloadBytes,
+  );
+  const [ticketMintAuthorityPda, ticketMintAuthorityBump] = findTicketMintAuthorityPdaWithBump(
+    input.programId,
+  );
+  const [userTicketTokenPda, userTicketTokenBump] = findTicketTokenPdaWithBump(
+    input.programId,
+    ticketMintPda,
+    input.userPubkey,
+  );
+  const [ticketReceiptPda, ticketReceiptBump] = findTicketReceiptPdaWithBump(
+    input.programId,
+    ticketMintPda,
+  );
+  const [proofQuotaPda, proofQuotaBump] = findProofQuotaPdaWithBump(
+    input.programId,
+    input.payloadBytes.slice(61, 93),
+  );
+  const [reputationPda, reputationBump] = findReputationPdaWithBump(
+    input.programId,
+    input.userPubkey,
+  );
+  const ticketClass = input.ticketClass ?? TICKET_CLASS_NONE;
+  // Optional accounts: the program id stands in for None.
+  const ticketClassKey =
+    ticketClass === TICKET_CLASS_NONE ? input.programId : findTicketClassPda(input.programId, ticketClass);
+  const membershipKey = input.withMembership
+    ? findMembershipPda(input.programId, input.userPubkey)
+    : input.programId;
 
   return {
     ed25519Ix: Ed25519Program.createInstructionWithPublicKey({
       publicKey: input.oraclePubkeyBytes,
-      message: input.payloadBytes,
+      message: serializeShieldMessageV0(input.payloadBytes, {
+        programId: input.programId,
+        clusterId: input.clusterId,
+        purpose: SIGNING_PURPOSE_EXECUTE,
+        ticketClass: input.ticketClass,
+      }),
       signature: input.oracleSignatureBytes,
     }),
     executeIx: new TransactionInstruction({
@@ -564,14 +757,75 @@ export function buildExecuteInstructions(input: {
         { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
         { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
         { pubkey: SYSVAR_RENT_PUBKEY, isSigner: false, isWritable: false },
+        { pubkey: findSaleConfigPda(input.programId), isSigner: false, isWritable: false },
+        { pubkey: proofQuotaPda, isSigner: false, isWritable: true },
+        { pubkey: ticketClassKey, isSigner: false, isWritable: ticketClass !== TICKET_CLASS_NONE },
+        { pubkey: membershipKey, isSigner: false, isWritable: false },
+        { pubkey: reputationPda, isSigner: false, isWritable: true },
       ],
-      data: buildExecuteInstructionData(input.payloadBytes, input.oracleSignatureBytes),
+      data: buildExecuteInstructionData(
+        input.payloadBytes,
+        input.oracleSignatureBytes,
+        input.sigInstructionIndex,
+        {
+          proofUse: proofUseBump,
+          ticketMint: ticketMintBump,
+          ticketMintAuthority: ticketMintAuthorityBump,
+          userTicketToken: userTicketTokenBump,
+          ticketReceipt: ticketReceiptBump,
+          proofQuota: proofQuotaBump,
+          reputation: reputationBump,
+        },
+      ),
     }),
     ticketMintPda,
     ticketReceiptPda,
+    proofQuotaPda,
+    reputationPda,
   };
 }
 
+// execute_shield expects the proof's ProofQuota to exist; anyone may create it.
+export function buildInitializeProofQuotaInstruction(input: {
+  programId: PublicKey;
+  payerPubkey: PublicKey;
+  zkProofHash: Uint8Array;
+}): TransactionInstruction {
+  return new TransactionInstruction({
+    programId: input.programId,
+    keys: [
+      { pubkey: input.payerPubkey, isSigner: true, isWritable: true },
+      { pubkey: findSaleConfigPda(input.programId), isSigner: false, isWritable: false },
+      {
+        pubkey: findProofQuotaPdaWithBump(input.programId, input.zkProofHash)[0],
+        isSigner: false,
+        isWritable: true,
+      },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    ],
+    data: Buffer.from(
+      concatBytes(anchorDiscriminator(GLOBAL_NAMESPACE, 'initialize_proof_quota'), input.zkProofHash),
+    ),
+  });
+}
+
+export function buildInitializeReputationInstruction(input: {
+  programId: PublicKey;
+  payerPubkey: PublicKey;
+  walletPubkey: PublicKey;
+}): TransactionInstruction {
+  return new TransactionInstruction({
+    programId: input.programId,
+    keys: [
+      { pubkey: input.payerPubkey, isSigner: true, isWritable: true },
+      { pubkey: input.walletPubkey, isSigner: false, isWritable: false },
+      { pubkey: findReputationPda(input.programId, input.walletPubkey), isSigner: false, isWritable: true },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    ],
+    data: Buffer.from(anchorDiscriminator(GLOBAL_NAMESPACE, 'initialize_reputation')),
+  });
+}
+
 export function buildListTicketInstruction(input: {
   programId: PublicKey;
   sellerPubkey: PublicKey;
@@ -597,6 +851,12 @@ export function buildListTicketInstruction(input: {
         { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
         { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
         { pubkey: SYSVAR_RENT_PUBKEY, isSigner: false, isWritable: false },
+        { pubkey: findSaleConfigPda(input.programId), isSigner: false, isWritable: false },
+        {
+          pubkey: findReputationPda(input.programId, input.sellerPubkey),
+          isSigner: false,
+          isWritable: true,
+        },
       ],
       data: Buffer.from(
         concatBytes(
@@ -12,6 +12,26 @@ const DEFAULT_POLICY_VERSION = 0;
 const DEFAULT_ZK_PROVIDER = 1;
 const DEFAULT_SCORING_MODEL_HASH_HEX = "11".repeat(32);
 const SHIELD_PAYLOAD_V0_LEN = 141;
+const SHIELD_DOMAIN_TAG = new TextEncoder().encode("UNFORGIVEN_SHIELD_V0");
+const SHIELD_DOMAIN_PROGRAM_ID_OFFSET = SHIELD_DOMAIN_TAG.length;
+const SHIELD_DOMAIN_CLUSTER_ID_OFFSET = SHIELD_DOMAIN_PROGRAM_ID_OFFSET + 32;
+const SHIELD_DOMAIN_PURPOSE_OFFSET = SHIELD_DOMAIN_CLUSTER_ID_OFFSET + 32;
+const SHIELD_DOMAIN_TICKET_CLASS_OFFSET = SHIELD_DOMAIN_PURPOSE_OFFSET + 1;
+const SHIELD_DOMAIN_LEN = SHIELD_DOMAIN_TICKET_CLASS_OFFSET + 1;
+
+// Signing purposes of `unforgiven_v2`; a signature only verifies for its own purpose.
+export const SIGNING_PURPOSE = {
+  preview: 1,
+  execute: 2,
+  buyResale: 3,
+  waitlist: 4,
+  sealedBid: 5,
+  transfer: 6,
+  startSession: 7,
+  endingBadge: 8,
+  membership: 9,
+  shieldGate: 10,
+} as const;
 
 export type UserMode = "bot_suspected" | "guest" | "verified";
 
@@ -87,8 +107,16 @@ export type ShieldQuoteOutput = {
   antiResalePolicy: AntiResalePolicy;
 };
 
+// `clusterId` is the base58 genesis hash stored in `AdminConfig.cluster_id`.
+export type ShieldSigningDomain = {
+  programId: string;
+  clusterId: string;
+  ticketClass?: number;
+};
+
 export type SignedShieldRequest = {
   quote: ShieldQuoteOutput;
+  signedMessage?: Uint8Array;
   oracleSignature?: Uint8Array;
   oracleSignatureHex?: string;
 };
@@ -96,11 +124,13 @@ export type SignedShieldRequest = {
 export type ExecuteShieldRequest = {
   quote: ShieldQuoteOutput;
   payload: ShieldPayloadV0;
+  signedMessage: Uint8Array;
   oracleSignature: Uint8Array;
   oracleSignatureHex: string;
 };
 
-export type OracleSigner = (payloadBytes: Uint8Array) => Promise<Uint8Array> | Uint8Array;
+// Receives the full signed message (signing domain + payload), not the bare payload.
+export type OracleSigner = (message: Uint8Array) => Promise<Uint8Array> | Uint8Array;
 
 function assertSafeInteger(value: number, name: string): void {
   if (!Number.isSafeInteger(value)) {
@@ -540,6 +570,28 @@ export function serializeShieldPayloadV0(payload: ShieldPayloadV0): Uint8Array {
   return out;
 }
 
+export function serializeShieldMessageV0(
+  payload: ShieldPayloadV0,
+  domain: ShieldSigningDomain,
+  purpose: number,
+): Uint8Array {
+  const ticketClass = domain.ticketClass ?? 0;
+  assertSafeInteger(purpose, "purpose");
+  assertSafeInteger(ticketClass, "ticketClass");
+  if (purpose < 0 || purpose > 255 || ticketClass < 0 || ticketClass > 255) {
+    throw new Error("purpose and ticketClass must be in range 0..255");
+  }
+
+  const out = new Uint8Array(SHIELD_DOMAIN_LEN + SHIELD_PAYLOAD_V0_LEN);
+  out.set(SHIELD_DOMAIN_TAG, 0);
+  out.set(new PublicKey(domain.programId).toBytes(), SHIELD_DOMAIN_PROGRAM_ID_OFFSET);
+  out.set(new PublicKey(domain.clusterId).toBytes(), SHIELD_DOMAIN_CLUSTER_ID_OFFSET);
+  out[SHIELD_DOMAIN_PURPOSE_OFFSET] = purpose;
+  out[SHIELD_DOMAIN_TICKET_CLASS_OFFSET] = ticketClass;
+  out.set(serializeShieldPayloadV0(payload), SHIELD_DOMAIN_LEN);
+  return out;
+}
+
 export function calculateShieldQuote(input: ShieldQuoteInput): ShieldQuoteOutput {
   const { payload, proof, dignityBreakdown, antiResalePolicy } = buildShieldPayloadV0(input);
   return calculateFromPayload(payload, proof, dignityBreakdown, antiResalePolicy);
@@ -552,19 +604,26 @@ export function activateUniversalShield(input: ShieldQuoteInput): ShieldQuoteOut
 export async function buildSignedShieldRequest(
   input: ShieldQuoteInput,
   oracleSigner?: OracleSigner,
+  signingDomain?: ShieldSigningDomain,
+  purpose: number = SIGNING_PURPOSE.preview,
 ): Promise<SignedShieldRequest> {
   const quote = calculateShieldQuote(input);
   if (!oracleSigner) {
     return { quote };
   }
+  if (!signingDomain) {
+    throw new Error("signingDomain is required to sign a shield payload");
+  }
 
-  const signature = await oracleSigner(quote.payloadBytes);
+  const signedMessage = serializeShieldMessageV0(quote.payload, signingDomain, purpose);
+  const signature = await oracleSigner(signedMessage);
   if (signature.length !== 64) {
     throw new Error("oracle signature must be 64 bytes");
   }
 
   return {
     quote,
+    signedMessage,
     oracleSignature: Uint8Array.from(signature),
     oracleSignatureHex: `0x${toHex(signature)}`,
   };
@@ -573,9 +632,15 @@ export async function buildSignedShieldRequest(
 export async function buildExecuteShieldRequest(
   input: ShieldQuoteInput,
   oracleSigner: OracleSigner,
+  signingDomain: ShieldSigningDomain,
 ): Promise<ExecuteShieldRequest> {
-  const signed = await buildSignedShieldRequest(input, oracleSigner);
-  if (!signed.oracleSignature || !signed.oracleSignatureHex) {
+  const signed = await buildSignedShieldRequest(
+    input,
+    oracleSigner,
+    signingDomain,
+    SIGNING_PURPOSE.execute,
+  );
+  if (!signed.oracleSignature || !signed.oracleSignatureHex || !signed.signedMessage) {
     throw new Error("oracleSigner is required for execute request");
   }
   if (signed.quote.blocked || signed.quote.isInfinite) {
@@ -585,6 +650,7 @@ export async function buildExecuteShieldRequest(
   return {
     quote: signed.quote,
     payload: signed.quote.payload,
+    signedMessage: signed.signedMessage,
     oracleSignature: signed.oracleSignature,
     oracleSignatureHex: signed.oracleSignatureHex,
   };
@@ -1,3 +1,4 @@
+#![allow(unexpected_cfgs)]
 use anchor_lang::solana_program::sysvar::SysvarId;
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::{
@@ -38,6 +39,9 @@ const ORACLE_MESSAGE_LEN: usize = 81;
 /// Sentinel value: offsets point to current instruction's data
 const ED25519_CURRENT_IX: usize = u16::MAX as usize;
 
+/// Furthest `update_auction_params` may move `start_time` from the current clock (1 year)
+const MAX_START_TIME_OFFSET_SECS: i64 = 365 * 24 * 60 * 60;
+
 // =============================================================================
 // STATE
 // =============================================================================
@@ -77,6 +81,37 @@ pub struct Vault {
     pub _reserved: u8,
 }
 
+// =============================================================================
+// EVENTS
+// =============================================================================
+
+#[event]
+pub struct VaultWithdrawnEvent {
+    pub authority: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    /// Vault balance left after the withdrawal
+    pub remaining: u64,
+}
+
+#[event]
+pub struct AuctionParamsUpdatedEvent {
+    pub authority: Pubkey,
+    pub old_target_rate_bps: u64,
+    pub new_target_rate_bps: u64,
+    pub old_start_time: i64,
+    pub new_start_time: i64,
+    pub old_base_price: u64,
+    pub new_base_price: u64,
+}
+
+#[event]
+pub struct OracleRotatedEvent {
+    pub authority: Pubkey,
+    pub old_oracle_pubkey: [u8; 32],
+    pub new_oracle_pubkey: [u8; 32],
+}
+
 // =============================================================================
 // ERRORS
 // =============================================================================
@@ -97,6 +132,12 @@ pub enum UnforgivenError {
     MathOverflow,
     #[msg("Event ID mismatch")]
     EventMismatch,
+    #[msg("Signer is not the global state authority")]
+    Unauthorized,
+    #[msg("Withdrawal would leave the vault below rent exemption")]
+    InsufficientVaultBalance,
+    #[msg("Invalid auction parameters")]
+    InvalidAuctionParams,
 }
 
 /// Tier -> Alpha BPS for VRGDA (Spec 3.4): 1=Platinum(10000), 2=Gold(5000), 3=Silver(2500)
@@ -167,11 +208,13 @@ pub mod unforgiven {
         let ix_sysvar_info = ctx.accounts.instruction_sysvar.to_account_info();
         verify_ed25519_ix(
             &ix_sysvar_info,
-            ctx.accounts.buyer.key(),
-            event_id,
-            tier_level,
-            expiry,
-            nonce,
+            &AuthorizationPayload {
+                user_wallet: ctx.accounts.buyer.key(),
+                event_id,
+                tier_level,
+                expiry,
+                nonce,
+            },
             sig_instruction_index as usize,
             &ctx.accounts.global_state.oracle_pubkey,
         )?;
@@ -258,21 +301,135 @@ pub mod unforgiven {
 
         Ok(())
     }
+
+    /// Withdraw ticket proceeds from the vault PDA.
+    ///
+    /// * `amount` - Lamports to send to `recipient`; the vault keeps its rent-exempt minimum
+    pub fn withdraw_vault(ctx: Context<WithdrawVault>, amount: u64) -> Result<()> {
+        let vault_info = ctx.accounts.vault.to_account_info();
+        let rent_minimum = Rent::get()?.minimum_balance(vault_info.data_len());
+        let remaining =
+            vault_balance_after_withdrawal(vault_info.lamports(), amount, rent_minimum)?;
+
+        // Vault is owned by this program, so lamports move without a system transfer.
+        **vault_info.try_borrow_mut_lamports()? = remaining;
+        let recipient_info = ctx.accounts.recipient.to_account_info();
+        let recipient_balance = recipient_info
+            .lamports()
+            .checked_add(amount)
+            .ok_or(UnforgivenError::MathOverflow)?;
+        **recipient_info.try_borrow_mut_lamports()? = recipient_balance;
+
+        emit!(VaultWithdrawnEvent {
+            authority: ctx.accounts.authority.key(),
+            recipient: recipient_info.key(),
+            amount,
+            remaining,
+        });
+        Ok(())
+    }
+
+    /// Update the VRGDA schedule. `items_sold` is left untouched.
+    ///
+    /// * `target_rate_bps` - Target sales per second in basis points (10000 = 1 item/sec), non-zero
+    /// * `start_time` - Unix timestamp when auction starts, within a year of now
+    /// * `base_price` - Base ticket price in lamports, non-zero
+    pub fn update_auction_params(
+        ctx: Context<AuthorityOnly>,
+        target_rate_bps: u64,
+        start_time: i64,
+        base_price: u64,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        validate_auction_params(target_rate_bps, start_time, base_price, now)?;
+
+        let state = &mut ctx.accounts.global_state;
+        emit!(AuctionParamsUpdatedEvent {
+            authority: ctx.accounts.authority.key(),
+            old_target_rate_bps: state.target_rate_bps,
+            new_target_rate_bps: target_rate_bps,
+            old_start_time: state.start_time,
+            new_start_time: start_time,
+            old_base_price: state.base_price,
+            new_base_price: base_price,
+        });
+        state.target_rate_bps = target_rate_bps;
+        state.start_time = start_time;
+        state.base_price = base_price;
+
+        Ok(())
+    }
+
+    /// Replace the Oracle key that signs AuthorizationPayloads.
+    /// Payloads signed by the old key stop verifying immediately.
+    pub fn rotate_oracle(ctx: Context<RotateOracle>) -> Result<()> {
+        let state = &mut ctx.accounts.global_state;
+        let new_oracle_pubkey = ctx.accounts.new_oracle.key().to_bytes();
+        emit!(OracleRotatedEvent {
+            authority: ctx.accounts.authority.key(),
+            old_oracle_pubkey: state.oracle_pubkey,
+            new_oracle_pubkey,
+        });
+        state.oracle_pubkey = new_oracle_pubkey;
+
+        Ok(())
+    }
+}
+
+// =============================================================================
+// HELPERS: Admin
+// =============================================================================
+
+/// Vault balance left after sending `amount`; it may not drop below `rent_minimum`.
+fn vault_balance_after_withdrawal(balance: u64, amount: u64, rent_minimum: u64) -> Result<u64> {
+    let remaining = balance
+        .checked_sub(amount)
+        .ok_or(UnforgivenError::InsufficientVaultBalance)?;
+    require!(
+        remaining >= rent_minimum,
+        UnforgivenError::InsufficientVaultBalance
+    );
+    Ok(remaining)
+}
+
+/// A zero rate expects no sales, so the price only climbs; a zero price makes tickets free.
+/// A `start_time` far from `now` pushes `buy_ticket`'s elapsed-time math toward overflow.
+fn validate_auction_params(
+    target_rate_bps: u64,
+    start_time: i64,
+    base_price: u64,
+    now: i64,
+) -> Result<()> {
+    require!(target_rate_bps > 0, UnforgivenError::InvalidAuctionParams);
+    require!(base_price > 0, UnforgivenError::InvalidAuctionParams);
+    let offset = start_time
+        .checked_sub(now)
+        .ok_or(UnforgivenError::InvalidAuctionParams)?;
+    require!(
+        offset.unsigned_abs() <= MAX_START_TIME_OFFSET_SECS as u64,
+        UnforgivenError::InvalidAuctionParams
+    );
+    Ok(())
 }
 
 // =============================================================================
 // HELPER: Ed25519 Instruction Introspection
 // =============================================================================
 
+/// AuthorizationPayload (Spec 3.3) fields the Oracle signature must cover
+struct AuthorizationPayload {
+    user_wallet: Pubkey,
+    event_id: Pubkey,
+    tier_level: u8,
+    expiry: i64,
+    nonce: u64,
+}
+
 /// Verifies that the Ed25519 instruction at the given index contains a valid
 /// Oracle signature for AuthorizationPayload: [user_wallet(32) + event_id(32) + tier_level(1) + expiry(8) + nonce(8)] = 81 bytes
 fn verify_ed25519_ix(
     instruction_sysvar: &AccountInfo<'_>,
-    expected_buyer: Pubkey,
-    expected_event_id: Pubkey,
-    expected_tier_level: u8,
-    expected_expiry: i64,
-    expected_nonce: u64,
+    expected: &AuthorizationPayload,
     sig_instruction_index: usize,
     oracle_pubkey: &[u8; 32],
 ) -> Result<()> {
@@ -330,6 +487,11 @@ fn verify_ed25519_ix(
         UnforgivenError::InvalidEd25519Instruction
     );
 
+    require!(
+        signature_offset >= ED25519_HEADER_LEN && data.len() >= signature_offset + ED25519_SIG_LEN,
+        UnforgivenError::InvalidEd25519Instruction
+    );
+
     require!(
         public_key_offset >= ED25519_HEADER_LEN
             && data.len() >= public_key_offset + ED25519_PUBKEY_LEN,
@@ -363,7 +525,7 @@ fn verify_ed25519_ix(
     wallet_arr.copy_from_slice(msg_wallet);
     let signed_buyer = Pubkey::new_from_array(wallet_arr);
     require!(
-        signed_buyer == expected_buyer,
+        signed_buyer == expected.user_wallet,
         UnforgivenError::BuyerMismatch
     );
 
@@ -372,13 +534,13 @@ fn verify_ed25519_ix(
     event_arr.copy_from_slice(msg_event_id);
     let signed_event_id = Pubkey::new_from_array(event_arr);
     require!(
-        signed_event_id == expected_event_id,
+        signed_event_id == expected.event_id,
         UnforgivenError::MessageMismatch
     );
 
     let signed_tier_level = msg[64];
     require!(
-        signed_tier_level == expected_tier_level,
+        signed_tier_level == expected.tier_level,
         UnforgivenError::MessageMismatch
     );
 
@@ -386,13 +548,19 @@ fn verify_ed25519_ix(
     let mut expiry_arr = [0u8; 8];
     expiry_arr.copy_from_slice(msg_expiry);
     let signed_expiry = i64::from_le_bytes(expiry_arr);
-    require!(signed_expiry == expected_expiry, UnforgivenError::MessageMismatch);
+    require!(
+        signed_expiry == expected.expiry,
+        UnforgivenError::MessageMismatch
+    );
 
     let msg_nonce = &msg[73..81];
     let mut nonce_arr = [0u8; 8];
     nonce_arr.copy_from_slice(msg_nonce);
     let signed_nonce = u64::from_le_bytes(nonce_arr);
-    require!(signed_nonce == expected_nonce, UnforgivenError::MessageMismatch);
+    require!(
+        signed_nonce == expected.nonce,
+        UnforgivenError::MessageMismatch
+    );
 
     Ok(())
 }
@@ -472,3 +640,55 @@ pub struct BuyTicket<'info> {
 
     pub system_program: Program<'info, System>,
 }
+
+#[derive(Accounts)]
+pub struct WithdrawVault<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"global"],
+        bump = global_state.bump,
+        has_one = authority @ UnforgivenError::Unauthorized,
+    )]
+    pub global_state: Account<'info, GlobalState>,
+
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(mut)]
+    pub recipient: SystemAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AuthorityOnly<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"global"],
+        bump = global_state.bump,
+        has_one = authority @ UnforgivenError::Unauthorized,
+    )]
+    pub global_state: Account<'info, GlobalState>,
+}
+
+#[derive(Accounts)]
+pub struct RotateOracle<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"global"],
+        bump = global_state.bump,
+        has_one = authority @ UnforgivenError::Unauthorized,
+    )]
+    pub global_state: Account<'info, GlobalState>,
+
+    /// New Oracle Ed25519 public key
+    /// CHECK: Only the key is stored
+    pub new_oracle: UncheckedAccount<'info>,
+}
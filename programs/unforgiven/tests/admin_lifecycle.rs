@@ -0,0 +1,285 @@
+use anchor_lang::{
+    prelude::{AccountInfo, Pubkey, Rent},
+    solana_program::entrypoint::ProgramResult,
+    AccountSerialize, InstructionData, Space, ToAccountMetas,
+};
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    clock::Clock,
+    instruction::{Instruction, InstructionError},
+    signature::{Keypair, Signer},
+    system_program,
+    transaction::{Transaction, TransactionError},
+};
+use unforgiven::{GlobalState, UnforgivenError, Vault, ID};
+
+const ONE_SOL_LAMPORTS: u64 = 1_000_000_000;
+const TARGET_RATE_BPS: u64 = 10_000;
+const BASE_PRICE: u64 = 100_000_000;
+
+// Anchor's entrypoint wants the account slice to outlive the call.
+fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    unforgiven::entry(program_id, accounts, data)
+}
+
+fn program_account<T: AccountSerialize>(state: &T, space: usize, lamports: u64) -> Account {
+    let mut data = Vec::with_capacity(space);
+    state.try_serialize(&mut data).unwrap();
+    data.resize(space, 0);
+    Account {
+        lamports,
+        data,
+        owner: ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+struct Setup {
+    context: ProgramTestContext,
+    authority: Keypair,
+    global_state: Pubkey,
+    vault: Pubkey,
+    vault_rent: u64,
+    now: i64,
+}
+
+async fn setup() -> Setup {
+    let mut program_test = ProgramTest::new("unforgiven", ID, processor!(process_instruction));
+    program_test.prefer_bpf(false);
+    let mut context = program_test.start_with_context().await;
+
+    let authority = Keypair::new();
+    let (global_state, global_bump) = Pubkey::find_program_address(&[b"global"], &ID);
+    let (vault, _) = Pubkey::find_program_address(&[b"vault"], &ID);
+    let now = context
+        .banks_client
+        .get_sysvar::<Clock>()
+        .await
+        .unwrap()
+        .unix_timestamp;
+    let vault_space = 8 + Vault::INIT_SPACE;
+    let vault_rent = Rent::default().minimum_balance(vault_space);
+
+    context.set_account(
+        &authority.pubkey(),
+        &Account::new(10 * ONE_SOL_LAMPORTS, 0, &system_program::id()).into(),
+    );
+    context.set_account(
+        &global_state,
+        &program_account(
+            &GlobalState {
+                authority: authority.pubkey(),
+                oracle_pubkey: [1u8; 32],
+                target_rate_bps: TARGET_RATE_BPS,
+                start_time: now,
+                base_price: BASE_PRICE,
+                items_sold: 3,
+                bump: global_bump,
+            },
+            8 + GlobalState::INIT_SPACE,
+            ONE_SOL_LAMPORTS,
+        )
+        .into(),
+    );
+    context.set_account(
+        &vault,
+        &program_account(
+            &Vault { _reserved: 0 },
+            vault_space,
+            vault_rent + ONE_SOL_LAMPORTS,
+        )
+        .into(),
+    );
+
+    Setup {
+        context,
+        authority,
+        global_state,
+        vault,
+        vault_rent,
+        now,
+    }
+}
+
+async fn send(
+    context: &mut ProgramTestContext,
+    ix: Instruction,
+    signer: &Keypair,
+) -> Result<(), BanksClientError> {
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, signer],
+        blockhash,
+    );
+    context.banks_client.process_transaction(tx).await
+}
+
+fn assert_program_error(result: Result<(), BanksClientError>, expected: UnforgivenError) {
+    let err = result.unwrap_err().unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(0, InstructionError::Custom(expected.into()))
+    );
+}
+
+fn withdraw_ix(setup: &Setup, authority: Pubkey, recipient: Pubkey, amount: u64) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: unforgiven::accounts::WithdrawVault {
+            authority,
+            global_state: setup.global_state,
+            vault: setup.vault,
+            recipient,
+        }
+        .to_account_metas(None),
+        data: unforgiven::instruction::WithdrawVault { amount }.data(),
+    }
+}
+
+fn update_params_ix(
+    setup: &Setup,
+    authority: Pubkey,
+    target_rate_bps: u64,
+    start_time: i64,
+    base_price: u64,
+) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: unforgiven::accounts::AuthorityOnly {
+            authority,
+            global_state: setup.global_state,
+        }
+        .to_account_metas(None),
+        data: unforgiven::instruction::UpdateAuctionParams {
+            target_rate_bps,
+            start_time,
+            base_price,
+        }
+        .data(),
+    }
+}
+
+fn rotate_oracle_ix(setup: &Setup, authority: Pubkey, new_oracle: Pubkey) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: unforgiven::accounts::RotateOracle {
+            authority,
+            global_state: setup.global_state,
+            new_oracle,
+        }
+        .to_account_metas(None),
+        data: unforgiven::instruction::RotateOracle {}.data(),
+    }
+}
+
+async fn global_state(setup: &mut Setup) -> GlobalState {
+    let account = setup
+        .context
+        .banks_client
+        .get_account(setup.global_state)
+        .await
+        .unwrap()
+        .unwrap();
+    anchor_lang::AccountDeserialize::try_deserialize(&mut account.data.as_slice()).unwrap()
+}
+
+#[tokio::test]
+async fn admin_instructions_reject_other_signers() {
+    let mut setup = setup().await;
+    let intruder = Keypair::new();
+    let recipient = Pubkey::new_unique();
+
+    let ix = withdraw_ix(&setup, intruder.pubkey(), recipient, 1);
+    let result = send(&mut setup.context, ix, &intruder).await;
+    assert_program_error(result, UnforgivenError::Unauthorized);
+
+    let ix = update_params_ix(&setup, intruder.pubkey(), TARGET_RATE_BPS, setup.now, 1);
+    let result = send(&mut setup.context, ix, &intruder).await;
+    assert_program_error(result, UnforgivenError::Unauthorized);
+
+    let ix = rotate_oracle_ix(&setup, intruder.pubkey(), intruder.pubkey());
+    let result = send(&mut setup.context, ix, &intruder).await;
+    assert_program_error(result, UnforgivenError::Unauthorized);
+
+    let state = global_state(&mut setup).await;
+    assert_eq!(state.oracle_pubkey, [1u8; 32]);
+    assert_eq!(state.base_price, BASE_PRICE);
+}
+
+// The vault may be drained down to its rent-exempt minimum and no further.
+#[tokio::test]
+async fn withdraw_vault_keeps_the_rent_floor() {
+    let mut setup = setup().await;
+    let authority = setup.authority.insecure_clone();
+    let recipient = Pubkey::new_unique();
+
+    let ix = withdraw_ix(&setup, authority.pubkey(), recipient, ONE_SOL_LAMPORTS + 1);
+    let result = send(&mut setup.context, ix, &authority).await;
+    assert_program_error(result, UnforgivenError::InsufficientVaultBalance);
+
+    let ix = withdraw_ix(&setup, authority.pubkey(), recipient, ONE_SOL_LAMPORTS);
+    send(&mut setup.context, ix, &authority).await.unwrap();
+
+    let banks = &mut setup.context.banks_client;
+    assert_eq!(
+        banks.get_balance(setup.vault).await.unwrap(),
+        setup.vault_rent
+    );
+    assert_eq!(
+        banks.get_balance(recipient).await.unwrap(),
+        ONE_SOL_LAMPORTS
+    );
+}
+
+#[tokio::test]
+async fn update_auction_params_enforces_bounds() {
+    let mut setup = setup().await;
+    let authority = setup.authority.insecure_clone();
+    let year = 365 * 24 * 60 * 60;
+
+    for (target_rate_bps, start_time, base_price) in [
+        (0, setup.now, BASE_PRICE),
+        (TARGET_RATE_BPS, setup.now, 0),
+        (TARGET_RATE_BPS, setup.now + year + 1, BASE_PRICE),
+        (TARGET_RATE_BPS, setup.now - year - 1, BASE_PRICE),
+        (TARGET_RATE_BPS, i64::MIN, BASE_PRICE),
+    ] {
+        let ix = update_params_ix(
+            &setup,
+            authority.pubkey(),
+            target_rate_bps,
+            start_time,
+            base_price,
+        );
+        let result = send(&mut setup.context, ix, &authority).await;
+        assert_program_error(result, UnforgivenError::InvalidAuctionParams);
+    }
+
+    let ix = update_params_ix(
+        &setup,
+        authority.pubkey(),
+        2 * TARGET_RATE_BPS,
+        setup.now - 60,
+        2 * BASE_PRICE,
+    );
+    send(&mut setup.context, ix, &authority).await.unwrap();
+    let new_oracle = Pubkey::new_unique();
+    let ix = rotate_oracle_ix(&setup, authority.pubkey(), new_oracle);
+    send(&mut setup.context, ix, &authority).await.unwrap();
+
+    let state = global_state(&mut setup).await;
+    assert_eq!(state.target_rate_bps, 2 * TARGET_RATE_BPS);
+    assert_eq!(state.start_time, setup.now - 60);
+    assert_eq!(state.base_price, 2 * BASE_PRICE);
+    assert_eq!(state.items_sold, 3);
+    assert_eq!(state.oracle_pubkey, new_oracle.to_bytes());
+}
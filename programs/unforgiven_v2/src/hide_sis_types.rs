@@ -60,6 +60,172 @@ pub fn resolve_ending_code(
     BROKEN_OATH
 }
 
+pub const NODE_NONE: u8 = 0;
+pub const DECISION_NONE: u8 = 0;
+pub const ENDING_NONE: u8 = 0;
+pub const CHAPTER_COUNT: usize = 3;
+pub const CHAPTER_BUDGET_BPS: u16 = 10_000;
+pub const DECISION_NODE_BUDGET_COST_BPS: u16 = 200;
+pub const DECISION_NODE_SUSPICION_COST: u64 = 80_000;
+pub const RELATION_MIN: i8 = -3;
+pub const RELATION_MAX: i8 = 3;
+pub const POLLUTION_SCORE_MAX: u8 = 3;
+pub const POLLUTION_FLAG_THRESHOLD: u8 = 2;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChoiceEffect {
+    pub relation_delta: i8,
+    pub pollution_risk: bool,
+    pub dignity_delta: i8,
+    pub marks_reveal: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TurnQuote {
+    pub blocked: bool,
+    pub suspicion_cost: u64,
+    pub budget_left_bps: u16,
+}
+
+const fn effect(relation_delta: i8, pollution_risk: bool, dignity_delta: i8, marks_reveal: bool) -> ChoiceEffect {
+    ChoiceEffect { relation_delta, pollution_risk, dignity_delta, marks_reveal }
+}
+
+// Story order; every node has exactly one successor and C3_N3 ends the session.
+pub fn next_node(node_id: u8) -> Option<u8> {
+    match node_id {
+        C1_N1_OPENING_PROBE => Some(C1_N2_PRIVATE_PROOF),
+        C1_N2_PRIVATE_PROOF => Some(C1_N3_LAST_CALL_PRESSURE),
+        C1_N3_LAST_CALL_PRESSURE => Some(C2_N1_TERMS_EXCHANGE),
+        C2_N1_TERMS_EXCHANGE => Some(C2_N2_FOOTPRINT_SWAP),
+        C2_N2_FOOTPRINT_SWAP => Some(C2_N3_KILLER_REVEAL_GATE),
+        C2_N3_KILLER_REVEAL_GATE => Some(C2_N4_SECRET_PACT),
+        C2_N4_SECRET_PACT => Some(C3_N1_SYSTEM_BREAKDOWN),
+        C3_N1_SYSTEM_BREAKDOWN => Some(C3_N2_FINAL_CHOICE),
+        C3_N2_FINAL_CHOICE => Some(C3_N3_ENDING_RESOLVE),
+        _ => None,
+    }
+}
+
+pub fn chapter_of(node_id: u8) -> Option<u8> {
+    match node_id {
+        C1_N1_OPENING_PROBE | C1_N2_PRIVATE_PROOF | C1_N3_LAST_CALL_PRESSURE => Some(CH1_NIGHT_DINNER),
+        C2_N1_TERMS_EXCHANGE | C2_N2_FOOTPRINT_SWAP | C2_N3_KILLER_REVEAL_GATE | C2_N4_SECRET_PACT => Some(CH2_MEMORY_TRADE),
+        C3_N1_SYSTEM_BREAKDOWN | C3_N2_FINAL_CHOICE | C3_N3_ENDING_RESOLVE => Some(CH3_ROOFTOP_COLLAPSE),
+        _ => None,
+    }
+}
+
+pub fn is_final_decision(decision_code: u8) -> bool {
+    matches!(decision_code, END_BURY_TRUTH | END_DISCLOSE | END_DOUBLE_PLAY)
+}
+
+// Choices at interrogative nodes, mirroring the engine's story table.
+pub fn interrogation_choice(node_id: u8, choice_id: u8) -> Option<ChoiceEffect> {
+    let choice = match (node_id, choice_id) {
+        (C1_N1_OPENING_PROBE, 1) => effect(-1, true, 0, false),
+        (C1_N1_OPENING_PROBE, 2) => effect(1, false, 0, false),
+        (C1_N1_OPENING_PROBE, 3) => effect(0, false, 0, false),
+        (C1_N2_PRIVATE_PROOF, 1) => effect(1, false, 4, false),
+        (C1_N2_PRIVATE_PROOF, 2) => effect(-1, true, -3, false),
+        (C1_N3_LAST_CALL_PRESSURE, 1) => effect(0, false, 0, false),
+        (C1_N3_LAST_CALL_PRESSURE, 2) => effect(-1, true, 0, false),
+        (C1_N3_LAST_CALL_PRESSURE, 3) => effect(1, false, 0, false),
+        (C2_N1_TERMS_EXCHANGE, 1) => effect(1, false, 0, false),
+        (C2_N1_TERMS_EXCHANGE, 2) => effect(0, false, 0, false),
+        (C2_N1_TERMS_EXCHANGE, 3) => effect(-1, true, 0, false),
+        (C2_N2_FOOTPRINT_SWAP, 1) => effect(0, false, 3, false),
+        (C2_N2_FOOTPRINT_SWAP, 2) => effect(0, false, 4, false),
+        (C2_N2_FOOTPRINT_SWAP, 3) => effect(0, false, 3, false),
+        (C2_N3_KILLER_REVEAL_GATE, 1) => effect(1, false, 0, true),
+        (C2_N3_KILLER_REVEAL_GATE, 2) => effect(-1, true, 0, false),
+        (C2_N3_KILLER_REVEAL_GATE, 3) => effect(0, false, 0, true),
+        (C3_N1_SYSTEM_BREAKDOWN, 1) => effect(1, false, 0, false),
+        (C3_N1_SYSTEM_BREAKDOWN, 2) => effect(0, true, 0, false),
+        (C3_N1_SYSTEM_BREAKDOWN, 3) => effect(-1, true, 0, false),
+        _ => return None,
+    };
+    Some(choice)
+}
+
+// Decisions at the two decision nodes; any other (node, decision) pair is illegal.
+pub fn chapter_decision(node_id: u8, decision_code: u8) -> Option<ChoiceEffect> {
+    let decision = match (node_id, decision_code) {
+        (C2_N4_SECRET_PACT, PACT_COMMIT) => effect(1, false, 0, false),
+        (C2_N4_SECRET_PACT, PACT_DELAY) => effect(0, false, 0, false),
+        (C2_N4_SECRET_PACT, PACT_BACKDOOR) => effect(-1, true, 0, false),
+        (C3_N2_FINAL_CHOICE, END_BURY_TRUTH) => effect(1, false, 0, false),
+        (C3_N2_FINAL_CHOICE, END_DISCLOSE) => effect(-1, false, 0, false),
+        (C3_N2_FINAL_CHOICE, END_DOUBLE_PLAY) => effect(-1, true, 0, false),
+        _ => return None,
+    };
+    Some(decision)
+}
+
+fn round_div(numerator: u64, denominator: u64) -> u64 {
+    (numerator + denominator / 2) / denominator
+}
+
+// Integer port of the engine's `quoteNode` for a first attempt at an interrogative node.
+pub fn quote_interrogation(chapter: u8, dignity_score: u8, budget_left_bps: u16) -> TurnQuote {
+    let chapter_heat_pct: u64 = match chapter {
+        CH1_NIGHT_DINNER => 100,
+        CH2_MEMORY_TRADE => 120,
+        _ => 145,
+    };
+    let dignity_heat = 240 - u64::from(dignity_score.min(100));
+    let raw_cost = round_div(650 * chapter_heat_pct * dignity_heat, 100 * 140);
+    let budget_cost_bps = round_div(raw_cost * 135, 100).clamp(300, 7_500) as u16;
+    TurnQuote {
+        blocked: budget_left_bps <= budget_cost_bps,
+        suspicion_cost: raw_cost * 1_000,
+        budget_left_bps: budget_left_bps.saturating_sub(budget_cost_bps),
+    }
+}
+
+pub fn quote_decision(budget_left_bps: u16) -> TurnQuote {
+    TurnQuote {
+        blocked: false,
+        suspicion_cost: DECISION_NODE_SUSPICION_COST,
+        budget_left_bps: budget_left_bps.saturating_sub(DECISION_NODE_BUDGET_COST_BPS),
+    }
+}
+
+pub fn pollution_delta(blocked: bool, choice: ChoiceEffect, strong_cleanse: bool) -> i8 {
+    let mut delta = 0;
+    if blocked {
+        delta += 2;
+    }
+    if choice.pollution_risk {
+        delta += 1;
+    } else if choice.relation_delta >= 0 || choice.dignity_delta > 0 || strong_cleanse {
+        delta -= if strong_cleanse { 3 } else { 1 };
+    }
+    delta
+}
+
+pub fn truth_progress(truth_unlocked: bool, dignity_score: u8, relation_score: i8) -> u8 {
+    if truth_unlocked {
+        return 100;
+    }
+    let weighted = u64::from(dignity_score) + (i64::from(relation_score) - i64::from(RELATION_MIN)) as u64 * 8;
+    round_div(weighted * 10, 16).min(99) as u8
+}
+
+pub fn humanity_score(relation_score: i8, truth_unlocked: bool, pollution_flag: bool, framed_flag: bool) -> u8 {
+    let mut score = 50 + i16::from(relation_score) * 8;
+    if truth_unlocked {
+        score += 10;
+    }
+    if pollution_flag {
+        score -= 18;
+    }
+    if framed_flag {
+        score -= 8;
+    }
+    score.clamp(0, 100) as u8
+}
+
 #[event]
 pub struct InterrogationTurnCommittedEvent {
     pub chapter: u8,
@@ -120,6 +286,41 @@ mod tests {
         assert_eq!(FRAMED_AND_JAILED, 3);
     }
 
+    #[test]
+    fn node_transitions_follow_the_story_order() {
+        let mut node = C1_N1_OPENING_PROBE;
+        let mut path = vec![node];
+        while let Some(next) = next_node(node) {
+            node = next;
+            path.push(node);
+        }
+        assert_eq!(path, vec![11, 12, 13, 21, 22, 23, 24, 31, 32, 33]);
+        assert_eq!(next_node(NODE_NONE), None);
+        assert_eq!(chapter_of(C2_N4_SECRET_PACT), Some(CH2_MEMORY_TRADE));
+        assert!(interrogation_choice(C2_N4_SECRET_PACT, 1).is_none());
+        assert!(interrogation_choice(C1_N2_PRIVATE_PROOF, 3).is_none());
+        assert!(chapter_decision(C2_N4_SECRET_PACT, END_BURY_TRUTH).is_none());
+        assert!(chapter_decision(C3_N2_FINAL_CHOICE, END_BURY_TRUTH).is_some());
+    }
+
+    #[test]
+    fn quotes_and_scores_match_engine_samples() {
+        let quote = quote_interrogation(CH1_NIGHT_DINNER, 72, CHAPTER_BUDGET_BPS);
+        assert_eq!(quote.suspicion_cost, 780_000);
+        assert_eq!(quote.budget_left_bps, 10_000 - 1_053);
+        assert!(!quote.blocked);
+        assert!(quote_interrogation(CH3_ROOFTOP_COLLAPSE, 20, 500).blocked);
+        assert_eq!(quote_decision(100).budget_left_bps, 0);
+
+        assert_eq!(pollution_delta(false, effect(-1, true, 0, false), false), 1);
+        assert_eq!(pollution_delta(false, effect(1, false, 0, true), true), -3);
+        assert_eq!(pollution_delta(true, effect(0, false, 0, false), false), 1);
+        assert_eq!(truth_progress(false, 72, 0), 60);
+        assert_eq!(truth_progress(true, 0, -3), 100);
+        assert_eq!(humanity_score(3, true, false, false), 84);
+        assert_eq!(humanity_score(-3, false, true, true), 0);
+    }
+
     #[test]
     fn truth_gate_boundaries_match_spec() {
         assert!(!truth_unlocked(69, 1, false, true));
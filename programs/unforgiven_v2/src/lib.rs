@@ -3,14 +3,19 @@
 use anchor_lang::{prelude::*, Discriminator};
 use anchor_lang::solana_program::{
     ed25519_program,
-    program::{invoke, invoke_signed},
-    system_instruction,
-    sysvar::instructions::{self, load_current_index_checked, load_instruction_at_checked},
+    hash::hashv,
+    program::{invoke, invoke_signed, set_return_data},
+    slot_hashes, system_instruction,
+    sysvar::{
+        self,
+        instructions::{self, load_current_index_checked, load_instruction_at_checked},
+    },
 };
 use anchor_spl::token::{
     self,
     spl_token::{self, instruction::AuthorityType},
     CloseAccount,
+    FreezeAccount,
     Mint,
     MintTo,
     SetAuthority,
@@ -22,32 +27,158 @@ use anchor_spl::token::{
 pub mod hide_sis_types;
 pub mod unforgiven_math;
 
+pub use unforgiven_v2_layout::account_layout;
+pub use unforgiven_v2_layout::quote_return::{
+    QuoteReturnV0, V0_LEN as QUOTE_RETURN_V0_LEN, VERSION_V0 as QUOTE_RETURN_VERSION_V0,
+};
+
+use hide_sis_types::{
+    chapter_decision, chapter_of, humanity_score, interrogation_choice, is_final_decision,
+    next_node, pollution_delta, quote_decision, quote_interrogation, resolve_ending_code,
+    truth_progress, truth_unlocked, ChapterDecisionCommittedEvent, ChoiceEffect,
+    InterrogationTurnCommittedEvent, SessionFinalizedEvent, TurnQuote, C1_N1_OPENING_PROBE,
+    C2_N3_KILLER_REVEAL_GATE, C3_N3_ENDING_RESOLVE, CHAPTER_BUDGET_BPS, CHAPTER_COUNT,
+    DECISION_NONE, ENDING_NONE, END_BURY_TRUTH, FRAMED_AND_JAILED, PACT_COMMIT,
+    POLLUTION_FLAG_THRESHOLD, POLLUTION_SCORE_MAX, RELATION_MAX, RELATION_MIN,
+};
 use unforgiven_math::{
-    calculate_vrgda_quote, VrgdaInput, VrgdaMathError, VrgdaQuote, MAX_TIME_ELAPSED_SECS,
+    bot_price_cap, calculate_dutch_quote, calculate_vrgda_quote, DutchAuctionInput, VrgdaInput,
+    VrgdaMathError, VrgdaQuote, MAX_TIME_ELAPSED_SECS,
 };
 
 declare_id!("5VqDVHqeCJW1cWZgydjJLG68ShDGVZ45k6cE7hUY9uMW");
 
+// Legacy `programs/unforgiven` deployment (7cVF3X3PvNLTNHd9EqvWHsrtHkeJXwRzBcRuoHoTThVT)
+// whose `Ticket` PDAs can be migrated.
+pub const V1_PROGRAM_ID: Pubkey = Pubkey::new_from_array([
+    98, 61, 98, 35, 82, 68, 213, 213, 42, 89, 13, 155, 133, 74, 137, 84, 10, 100, 96, 5, 65, 48,
+    16, 29, 196, 239, 18, 108, 236, 157, 233, 130,
+]);
+
 pub const POLICY_VERSION_V0: u8 = 0;
 pub const SHIELD_PAYLOAD_V0_LEN: usize = 141;
+pub const SHIELD_DOMAIN_TAG: &[u8; SHIELD_DOMAIN_TAG_LEN] = b"UNFORGIVEN_SHIELD_V0";
+pub const SHIELD_DOMAIN_TAG_LEN: usize = 20;
+pub const SHIELD_DOMAIN_PROGRAM_ID_OFFSET: usize = SHIELD_DOMAIN_TAG_LEN;
+pub const SHIELD_DOMAIN_CLUSTER_ID_OFFSET: usize = SHIELD_DOMAIN_PROGRAM_ID_OFFSET + 32;
+pub const SHIELD_DOMAIN_PURPOSE_OFFSET: usize = SHIELD_DOMAIN_CLUSTER_ID_OFFSET + 32;
+pub const SHIELD_DOMAIN_TICKET_CLASS_OFFSET: usize = SHIELD_DOMAIN_PURPOSE_OFFSET + 1;
+pub const SHIELD_DOMAIN_LEN: usize = SHIELD_DOMAIN_TICKET_CLASS_OFFSET + 1;
+pub const SHIELD_SIGNED_MESSAGE_V0_LEN: usize = SHIELD_DOMAIN_LEN + SHIELD_PAYLOAD_V0_LEN;
+pub const SIGNING_PURPOSE_PREVIEW: u8 = 1;
+pub const SIGNING_PURPOSE_EXECUTE: u8 = 2;
+pub const SIGNING_PURPOSE_BUY_RESALE: u8 = 3;
+pub const SIGNING_PURPOSE_WAITLIST: u8 = 4;
+pub const SIGNING_PURPOSE_SEALED_BID: u8 = 5;
+pub const SIGNING_PURPOSE_TRANSFER: u8 = 6;
+pub const SIGNING_PURPOSE_HIDE_SIS: u8 = 7;
+pub const SIGNING_PURPOSE_ENDING_BADGE: u8 = 8;
+pub const SIGNING_PURPOSE_MEMBERSHIP: u8 = 9;
+pub const SIGNING_PURPOSE_SHIELD_GATE: u8 = 10;
 pub const USER_MODE_BOT_SUSPECTED: u8 = 0;
 pub const USER_MODE_GUEST: u8 = 1;
 pub const USER_MODE_VERIFIED: u8 = 2;
 pub const TICKET_AMOUNT: u64 = 1;
 pub const TICKET_DECIMALS: u8 = 0;
 pub const RESALE_FEE_BPS: u64 = 500;
+pub const MODE_ACTION_PREVIEW: u8 = 1 << 0;
+pub const MODE_ACTION_BUY: u8 = 1 << 1;
+pub const MODE_ACTION_LIST: u8 = 1 << 2;
+pub const MODE_ACTION_BUY_RESALE: u8 = 1 << 3;
+pub const MODE_ACTION_ALL: u8 =
+    MODE_ACTION_PREVIEW | MODE_ACTION_BUY | MODE_ACTION_LIST | MODE_ACTION_BUY_RESALE;
+pub const PRICE_MULTIPLIER_BASE_BPS: u16 = 10_000;
+pub const USER_MODE_COUNT: usize = 3;
+pub const ADAPTER_GITHUB: u8 = 1 << 0;
+pub const ADAPTER_SPOTIFY: u8 = 1 << 1;
+pub const ADAPTER_TWITTER: u8 = 1 << 2;
+pub const ALLOCATION_MODE_OPEN: u8 = 0;
+pub const ALLOCATION_MODE_LOTTERY: u8 = 1;
+pub const ALLOCATION_MODE_SEALED_BID: u8 = 2;
+pub const LOTTERY_DRAW_DELAY_SLOTS: u64 = 32;
+pub const MAX_LOTTERY_WINNERS: u32 = 32;
+pub const PRICING_STRATEGY_VRGDA: u8 = 0;
+pub const PRICING_STRATEGY_DUTCH: u8 = 1;
+pub const MAX_SEALED_BID_WINNERS: u8 = 16;
+// Array lengths in account and instruction types are literals: Anchor's IDL parser cannot
+// evaluate expressions or follow re-exports.
+pub const SEALED_BID_BOARD_LEN: usize = 17;
+pub const ZK_PROVIDER_ALLOWLIST_LEN: usize = 32;
+const _: () = assert!(SEALED_BID_BOARD_LEN == MAX_SEALED_BID_WINNERS as usize + 1);
+const _: () = assert!(ZK_PROVIDER_ALLOWLIST_LEN == account_layout::ZK_PROVIDER_ALLOWLIST_LEN);
+pub const TICKET_CLASS_NONE: u8 = 0;
+pub const MEMBERSHIP_TIER_NONE: u8 = 0;
+pub const MEMBERSHIP_TIER_CORE: u8 = 1;
+pub const MEMBERSHIP_TIER_INNER_CIRCLE: u8 = 2;
+pub const MEMBERSHIP_TIER_COUNT: usize = 2;
+pub const REPUTATION_PURCHASE_BONUS: u32 = 2;
+pub const REPUTATION_REDEEM_BONUS: u32 = 5;
+pub const REPUTATION_BONUS_CAP: u32 = 20;
+pub const REPUTATION_FLIP_PENALTY: u32 = 5;
+pub const REPUTATION_BLOCKED_PENALTY: u32 = 10;
+pub const GRAPH_ROOT_KIND_EXPORT_SHA256: u8 = 0;
+pub const GRAPH_ROOT_KIND_MERKLE: u8 = 1;
+pub const AUDIT_LOG_CAPACITY: usize = 16;
+pub const AUDIT_ACTION_ADMIN_CONFIG_INITIALIZED: u8 = 1;
+pub const AUDIT_ACTION_ORACLE_ROTATED: u8 = 2;
+pub const AUDIT_ACTION_SCORING_MODEL_HASH_SET: u8 = 3;
+pub const AUDIT_ACTION_GUARDRAILS_RESET: u8 = 4;
+pub const AUDIT_ACTION_ZK_PROVIDER_ALLOWED: u8 = 5;
+pub const AUDIT_ACTION_CLUSTER_ID_SET: u8 = 6;
+pub const AUDIT_ACTION_GRAPH_PUBLISHER_SET: u8 = 7;
+pub const AUDIT_ACTION_PROOF_QUOTA_SET: u8 = 8;
+pub const AUDIT_ACTION_USER_MODE_POLICY_SET: u8 = 9;
+pub const AUDIT_ACTION_ADAPTER_REQUIREMENTS_SET: u8 = 10;
+pub const AUDIT_ACTION_COMMIT_REVEAL_PARAMS_SET: u8 = 11;
+pub const AUDIT_ACTION_PRICING_STRATEGY_SET: u8 = 12;
+pub const AUDIT_ACTION_TRANSFER_POLICY_SET: u8 = 13;
+pub const AUDIT_ACTION_MEMBERSHIP_REQUIREMENT_SET: u8 = 14;
+pub const AUDIT_ACTION_SCANNER_SET: u8 = 15;
+pub const AUDIT_ACTION_GLOBAL_CONFIG_INITIALIZED: u8 = 16;
+pub const AUDIT_ACTION_SALE_CONFIG_INITIALIZED: u8 = 17;
+pub const AUDIT_ACTION_TICKET_CLASS_INITIALIZED: u8 = 18;
+pub const AUDIT_ACTION_TICKET_CLASS_UPDATED: u8 = 19;
+pub const AUDIT_ACTION_LOTTERY_INITIALIZED: u8 = 20;
+pub const AUDIT_ACTION_SEALED_BID_AUCTION_INITIALIZED: u8 = 21;
+pub const AUDIT_ACTION_MEMBERSHIP_CONFIG_INITIALIZED: u8 = 22;
+pub const AUDIT_ACTION_MEMBERSHIP_CONFIG_UPDATED: u8 = 23;
+pub const AUDIT_ACTION_GLOBAL_CONFIG_MIGRATED: u8 = 24;
+pub const AUDIT_ACTION_ADMIN_CONFIG_MIGRATED: u8 = 25;
 
 const ED25519_OFFSETS_START: usize = 2;
 const ED25519_OFFSETS_SIZE: usize = 14;
 const ED25519_SIGNATURE_LEN: usize = 64;
 const ED25519_PUBKEY_LEN: usize = 32;
-const EXECUTE_SHIELD_DISCRIMINATOR: [u8; 8] = [121, 30, 47, 225, 69, 64, 66, 80];
 const TICKET_MINT_AUTHORITY_SEED: &[u8] = b"ticket_mint_authority_v2";
 const TICKET_MINT_SEED: &[u8] = b"ticket_mint_v2";
 const TICKET_TOKEN_SEED: &[u8] = b"ticket_token_v2";
 const TICKET_RECEIPT_SEED: &[u8] = b"ticket_receipt_v2";
 const TICKET_LISTING_SEED: &[u8] = b"ticket_listing_v2";
 const TICKET_ESCROW_SEED: &[u8] = b"ticket_escrow_v2";
+const SALE_CONFIG_SEED: &[u8] = b"sale_config_v2";
+const PROOF_QUOTA_SEED: &[u8] = b"proof_quota_v2";
+const LOTTERY_SEED: &[u8] = b"lottery_v2";
+const WAITLIST_ENTRY_SEED: &[u8] = b"waitlist_entry_v2";
+const SLOT_HASH_ENTRY_LEN: usize = 40;
+const PURCHASE_COMMITMENT_SEED: &[u8] = b"purchase_commitment_v2";
+const SEALED_BID_AUCTION_SEED: &[u8] = b"sealed_bid_auction_v2";
+const SEALED_BID_SEED: &[u8] = b"sealed_bid_v2";
+const TICKET_CLASS_SEED: &[u8] = b"ticket_class_v2";
+const AUDIT_LOG_SEED: &[u8] = b"audit_log_v2";
+const HIDE_SIS_SESSION_SEED: &[u8] = b"hide_sis_session_v2";
+const ENDING_BADGE_SEED: &[u8] = b"ending_badge_v2";
+const GRAPH_REGISTRY_SEED: &[u8] = b"graph_registry_v2";
+const MEMBERSHIP_CONFIG_SEED: &[u8] = b"membership_config_v2";
+const MEMBERSHIP_SEED: &[u8] = b"membership_v2";
+const REPUTATION_SEED: &[u8] = b"reputation_v2";
+const V1_MIGRATION_SEED: &[u8] = b"v1_migration_v2";
+const V1_GLOBAL_SEED: &[u8] = b"global";
+const V1_TICKET_SEED: &[u8] = b"ticket";
+// buyer (32) + purchase_time (8) after the 8-byte discriminator.
+const V1_TICKET_LEN: usize = 8 + 32 + 8;
+const GRAPH_SNAPSHOT_SEED: &[u8] = b"graph_snapshot_v2";
+const ENDING_BADGE_MINT_SEED: &[u8] = b"ending_badge_mint_v2";
+const ENDING_BADGE_TOKEN_SEED: &[u8] = b"ending_badge_token_v2";
 const SPL_TOKEN_MINT_LEN: usize = 82;
 const SPL_TOKEN_ACCOUNT_LEN: usize = 165;
 
@@ -103,6 +234,146 @@ pub enum UnforgivenV2Error {
     SelfTradeForbidden,
     #[msg("Invalid execute_shield account")]
     InvalidExecuteShieldAccount,
+    #[msg("Max uses per proof must be positive")]
+    InvalidProofQuota,
+    #[msg("Proof quota exhausted for this sale")]
+    ProofQuotaExhausted,
+    #[msg("Invalid user mode policy")]
+    InvalidUserModePolicy,
+    #[msg("Action is not allowed for this user mode")]
+    UserModeActionForbidden,
+    #[msg("Dignity score is below the user mode floor")]
+    DignityBelowModeFloor,
+    #[msg("Required and forbidden adapter bits overlap")]
+    InvalidAdapterRequirements,
+    #[msg("Adapter mask does not satisfy sale requirements")]
+    AdapterMaskMismatch,
+    #[msg("zk provider is not on the allowlist")]
+    ZkProviderNotAllowed,
+    #[msg("Invalid lottery configuration")]
+    InvalidLotteryConfig,
+    #[msg("Sale allocates tickets by lottery")]
+    LotteryAllocationOnly,
+    #[msg("Sale is not in lottery mode")]
+    LotteryNotEnabled,
+    #[msg("Lottery registration window is closed")]
+    LotteryRegistrationClosed,
+    #[msg("Lottery draw is not ready yet")]
+    LotteryDrawNotReady,
+    #[msg("Lottery has already been drawn")]
+    LotteryAlreadyDrawn,
+    #[msg("Lottery has not been drawn")]
+    LotteryNotDrawn,
+    #[msg("Waitlist entry did not win the lottery")]
+    LotteryEntryNotWinner,
+    #[msg("All lottery winner slots have been claimed")]
+    LotteryWinnerSlotsFilled,
+    #[msg("Waitlist entry was already claimed")]
+    WaitlistEntryClaimed,
+    #[msg("Invalid commit-reveal parameters")]
+    InvalidCommitRevealParams,
+    #[msg("Commit-reveal purchases are disabled for this sale")]
+    CommitRevealDisabled,
+    #[msg("Revealed payload does not match the commitment")]
+    CommitmentMismatch,
+    #[msg("Reveal is too early")]
+    RevealTooEarly,
+    #[msg("Reveal window has closed")]
+    RevealWindowClosed,
+    #[msg("Reveal window is still open")]
+    RevealWindowOpen,
+    #[msg("Invalid pricing strategy")]
+    InvalidPricingStrategy,
+    #[msg("Invalid sealed-bid auction configuration")]
+    InvalidSealedBidAuction,
+    #[msg("Sale allocates tickets by sealed-bid auction")]
+    SealedBidAllocationOnly,
+    #[msg("Sealed-bid auction is not in the required phase")]
+    SealedBidPhaseMismatch,
+    #[msg("Revealed bid does not match the commitment")]
+    SealedBidCommitmentMismatch,
+    #[msg("Sealed bid was already revealed")]
+    SealedBidAlreadyRevealed,
+    #[msg("Bid is below the reserve price or above the escrow")]
+    InvalidSealedBidAmount,
+    #[msg("Sealed bid did not win")]
+    SealedBidNotWinner,
+    #[msg("Winning bids must be claimed before the claim deadline, not refunded")]
+    SealedBidWinnerMustClaim,
+    #[msg("Invalid ticket class")]
+    InvalidTicketClass,
+    #[msg("Payload price does not match the ticket class")]
+    TicketClassPriceMismatch,
+    #[msg("Ticket class is sold out")]
+    TicketClassSoldOut,
+    #[msg("Ticket transfer limit reached")]
+    TicketTransferLimitReached,
+    #[msg("Sale requires a shield payload from the transfer recipient")]
+    RecipientShieldRequired,
+    #[msg("Entry secret commitment must be non-zero")]
+    InvalidEntrySecretCommitment,
+    #[msg("Hide-sis session is already finalized")]
+    HideSisSessionFinalized,
+    #[msg("Node is not the session's current node")]
+    HideSisIllegalTransition,
+    #[msg("Choice is not available at this node")]
+    HideSisInvalidChoice,
+    #[msg("Hide-sis session is not ready to finalize")]
+    HideSisNotReadyToFinalize,
+    #[msg("Hide-sis session is not finalized")]
+    HideSisSessionNotFinalized,
+    #[msg("Ending badges require a verified shield payload")]
+    EndingBadgeRequiresVerified,
+    #[msg("Graph snapshot version must increase")]
+    GraphSnapshotVersionNotMonotonic,
+    #[msg("Invalid graph snapshot root kind")]
+    InvalidGraphRootKind,
+    #[msg("Signer is not the graph snapshot publisher")]
+    GraphPublisherMismatch,
+    #[msg("Invalid membership configuration")]
+    InvalidMembershipConfig,
+    #[msg("Invalid membership tier")]
+    InvalidMembershipTier,
+    #[msg("Payload price does not match the membership charge")]
+    MembershipPriceMismatch,
+    #[msg("Sale requires a higher membership tier")]
+    MembershipTierRequired,
+    #[msg("Ticket already redeemed")]
+    TicketAlreadyRedeemed,
+    #[msg("Entry secret did not verify")]
+    InvalidEntrySecret,
+    #[msg("Reported payload does not quote as blocked")]
+    BlockedAttemptNotBlocked,
+    #[msg("Reputation account does not belong to this wallet")]
+    ReputationWalletMismatch,
+    #[msg("Account is not a v1 Ticket")]
+    InvalidV1Ticket,
+    #[msg("Missing or malformed quote return data")]
+    InvalidQuoteReturnData,
+    #[msg("Account does not have the expected layout")]
+    InvalidAccountLayout,
+    #[msg("Account layout is already current")]
+    AccountLayoutCurrent,
+    #[msg("Signer is not the authority stored in the account")]
+    LayoutAuthorityMismatch,
+    #[msg("Cluster id must be the cluster's genesis hash")]
+    InvalidClusterId,
+    #[msg("Waitlist entry was already ranked")]
+    WaitlistEntryRanked,
+    #[msg("Every waitlist entry must be ranked before claims open")]
+    LotteryRankingIncomplete,
+    #[msg("Sale requires commit-reveal purchases")]
+    CommitRevealRequired,
+    #[msg("Payload price does not match the Dutch start price")]
+    DutchStartPriceMismatch,
+    #[msg("This sale sells by ticket class; pass the class account")]
+    TicketClassRequired,
+    #[msg("Signer is not the sale's gate scanner")]
+    ScannerMismatch,
+    #[msg("Revealed seed does not match the lottery's seed commitment")]
+    LotterySeedMismatch,
+    #[msg("Lottery seed must be revealed before the draw")]
+    LotterySeedNotRevealed,
 }
 
 #[account]
@@ -110,6 +381,7 @@ pub enum UnforgivenV2Error {
 pub struct GlobalConfigV2 {
     pub authority: Pubkey,
     pub bump: u8,
+    pub layout_version: u8,
 }
 
 #[account]
@@ -119,6 +391,30 @@ pub struct AdminConfig {
     pub oracle_pubkey: [u8; 32],
     pub active_scoring_model_hash: [u8; 32],
     pub bump: u8,
+    pub zk_provider_allowlist: [u8; ZK_PROVIDER_ALLOWLIST_LEN],
+    pub cluster_id: [u8; 32],
+    pub layout_version: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct AuditLog {
+    pub total_entries: u64,
+    pub next_index: u16,
+    pub entries: [AuditEntry; AUDIT_LOG_CAPACITY],
+    pub bump: u8,
+}
+
+#[derive(
+    AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+pub struct AuditEntry {
+    pub sequence: u64,
+    pub action: u8,
+    pub signer: Pubkey,
+    pub timestamp: i64,
+    pub old_value: [u8; 32],
+    pub new_value: [u8; 32],
 }
 
 #[account]
@@ -131,6 +427,163 @@ pub struct ProofUse {
     pub bump: u8,
 }
 
+#[account]
+#[derive(InitSpace)]
+pub struct SaleConfig {
+    pub event_key: Pubkey,
+    pub max_uses_per_proof: u16,
+    pub mode_policies: [UserModePolicy; USER_MODE_COUNT],
+    pub required_adapter_mask: u8,
+    pub forbidden_adapter_mask: u8,
+    pub bump: u8,
+    pub allocation_mode: u8,
+    pub commit_deposit_lamports: u64,
+    pub commit_min_delay_slots: u64,
+    pub commit_reveal_window_slots: u64,
+    pub commit_forfeit_bps: u16,
+    pub pricing_strategy: u8,
+    pub dutch_floor_price: u64,
+    pub dutch_step_amount: u64,
+    pub dutch_step_interval_secs: u64,
+    pub max_transfers_per_ticket: u16,
+    pub transfer_requires_shield: bool,
+    pub min_membership_tier: u8,
+    pub dutch_start_price: u64,
+    pub ticket_class_count: u8,
+    pub scanner: Pubkey,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UserModePolicy {
+    pub allowed_actions: u8,
+    pub price_multiplier_bps: u16,
+    pub dignity_floor: u8,
+}
+
+impl UserModePolicy {
+    pub const PERMISSIVE: Self = Self {
+        allowed_actions: MODE_ACTION_ALL,
+        price_multiplier_bps: PRICE_MULTIPLIER_BASE_BPS,
+        dignity_floor: 0,
+    };
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ProofQuota {
+    pub event_key: Pubkey,
+    pub zk_proof_hash: [u8; 32],
+    pub uses: u16,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Lottery {
+    pub event_key: Pubkey,
+    pub registration_start: i64,
+    pub registration_end: i64,
+    pub winner_slots: u32,
+    pub entry_count: u32,
+    pub total_weight: u64,
+    pub draw_slot: u64,
+    pub randomness: [u8; 32],
+    pub drawn: bool,
+    pub claimed: u32,
+    pub bump: u8,
+    pub ranked_count: u32,
+    pub board: [LotteryRank; MAX_LOTTERY_WINNERS as usize],
+    pub board_len: u8,
+    pub seed_commitment: [u8; 32],
+    pub seed: [u8; 32],
+    pub seed_revealed: bool,
+}
+
+#[derive(
+    AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+pub struct LotteryRank {
+    pub zk_proof_hash: [u8; 32],
+    pub score: u128,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct WaitlistEntry {
+    pub lottery: Pubkey,
+    pub user: Pubkey,
+    pub zk_proof_hash: [u8; 32],
+    pub weight: u8,
+    pub registered_at: i64,
+    pub claimed: bool,
+    pub bump: u8,
+    pub ranked: bool,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct PurchaseCommitment {
+    pub user: Pubkey,
+    pub event_key: Pubkey,
+    pub commitment: [u8; 32],
+    pub deposit_lamports: u64,
+    pub reveal_after_slot: u64,
+    pub reveal_deadline_slot: u64,
+    pub forfeit_bps: u16,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct SealedBidAuction {
+    pub event_key: Pubkey,
+    pub commit_end: i64,
+    pub reveal_end: i64,
+    pub winner_slots: u8,
+    pub reserve_price: u64,
+    pub board: [SealedBidRank; SEALED_BID_BOARD_LEN],
+    pub board_len: u8,
+    pub bump: u8,
+    pub ticket_class: u8,
+    pub claim_end: i64,
+}
+
+#[derive(
+    AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+pub struct SealedBidRank {
+    pub bid: Pubkey,
+    pub amount: u64,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct SealedBid {
+    pub auction: Pubkey,
+    pub bidder: Pubkey,
+    pub zk_proof_hash: [u8; 32],
+    pub nonce: u64,
+    pub user_mode: u8,
+    pub commitment: [u8; 32],
+    pub escrow_lamports: u64,
+    pub revealed: bool,
+    pub amount: u64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct TicketClass {
+    pub event_key: Pubkey,
+    pub class_id: u8,
+    pub base_price: u64,
+    pub velocity_multiplier_bps: u16,
+    pub supply: u32,
+    pub sold: u32,
+    pub bump: u8,
+    pub allocation_mode: u8,
+}
+
 #[account]
 #[derive(InitSpace)]
 pub struct TicketReceipt {
@@ -147,6 +600,120 @@ pub struct TicketReceipt {
     pub listed: bool,
     pub resale_count: u64,
     pub bump: u8,
+    pub holder_user_mode: u8,
+    pub class_id: u8,
+    pub transfer_count: u16,
+    pub entry_secret_commitment: [u8; 32],
+    pub entry_secret_rotated_at: i64,
+    pub redeemed_at: i64,
+    pub layout_version: u8,
+}
+
+impl TicketReceipt {
+    fn reset_entry_secret(&mut self) {
+        self.entry_secret_commitment = [0u8; 32];
+        self.entry_secret_rotated_at = 0;
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct HideSisSession {
+    pub player: Pubkey,
+    pub session_id: u64,
+    pub user_mode: u8,
+    pub dignity_score: u8,
+    pub relation_score: i8,
+    pub pollution_score: u8,
+    pub pollution_flag: bool,
+    pub c2_n3_passed: bool,
+    pub truth_unlocked: bool,
+    pub current_node: u8,
+    pub chapter_budget_bps: [u16; CHAPTER_COUNT],
+    pub final_decision_code: u8,
+    pub ending_code: u8,
+    pub humanity_score: u8,
+    pub finalized: bool,
+    pub started_at: i64,
+    pub updated_at: i64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct EndingBadge {
+    pub player: Pubkey,
+    pub mint: Pubkey,
+    pub ending_code: u8,
+    pub session_id: u64,
+    pub humanity_score: u8,
+    pub truth_unlocked: bool,
+    pub issued_at: i64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct GraphRegistry {
+    pub publisher: Pubkey,
+    pub latest_version: u64,
+    pub latest_snapshot_hash: [u8; 32],
+    pub snapshot_count: u64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct GraphSnapshot {
+    pub version: u64,
+    pub snapshot_hash: [u8; 32],
+    pub root_kind: u8,
+    pub publisher: Pubkey,
+    pub anchored_at: i64,
+    pub slot: u64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct V1Migration {
+    pub v1_ticket: Pubkey,
+    pub buyer: Pubkey,
+    pub mint: Pubkey,
+    pub v1_purchase_time: i64,
+    pub migrated_at: i64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Reputation {
+    pub wallet: Pubkey,
+    pub fair_purchases: u32,
+    pub redeemed_tickets: u32,
+    pub flips: u32,
+    pub blocked_attempts: u32,
+    pub updated_at: i64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct MembershipConfig {
+    pub tier_prices: [u64; MEMBERSHIP_TIER_COUNT],
+    pub period_secs: i64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Membership {
+    pub wallet: Pubkey,
+    pub tier: u8,
+    pub started_at: i64,
+    pub expires_at: i64,
+    pub total_paid: u64,
+    pub bump: u8,
 }
 
 #[account]
@@ -176,6 +743,32 @@ pub struct ShieldPayloadV0 {
     pub nonce: u64,
 }
 
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ShieldAccountBumps {
+    pub proof_use: u8,
+    pub ticket_mint: u8,
+    pub ticket_mint_authority: u8,
+    pub user_ticket_token: u8,
+    pub ticket_receipt: u8,
+    pub proof_quota: u8,
+    pub reputation: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RecipientShield {
+    pub payload: ShieldPayloadV0,
+    pub oracle_signature: [u8; 64],
+    pub sig_instruction_index: u16,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SigningDomain {
+    pub program_id: Pubkey,
+    pub cluster_id: [u8; 32],
+    pub purpose: u8,
+    pub ticket_class: u8,
+}
+
 #[event]
 pub struct PreviewPriceEvent {
     pub final_price: u64,
@@ -188,6 +781,223 @@ pub struct PreviewPriceEvent {
     pub user_mode: u8,
 }
 
+#[event]
+pub struct GlobalConfigInitializedEvent {
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AdminConfigInitializedEvent {
+    pub authority: Pubkey,
+    pub oracle_pubkey: [u8; 32],
+    pub active_scoring_model_hash: [u8; 32],
+    pub cluster_id: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OracleRotatedEvent {
+    pub authority: Pubkey,
+    pub old_oracle_pubkey: [u8; 32],
+    pub new_oracle_pubkey: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ScoringModelHashUpdatedEvent {
+    pub authority: Pubkey,
+    pub old_scoring_model_hash: [u8; 32],
+    pub new_scoring_model_hash: [u8; 32],
+    pub guardrails_reset: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SaleConfigInitializedEvent {
+    pub event_key: Pubkey,
+    pub authority: Pubkey,
+    pub max_uses_per_proof: u16,
+}
+
+#[event]
+pub struct ProofQuotaUpdatedEvent {
+    pub event_key: Pubkey,
+    pub authority: Pubkey,
+    pub old_max_uses_per_proof: u16,
+    pub new_max_uses_per_proof: u16,
+}
+
+#[event]
+pub struct CommitRevealParamsUpdatedEvent {
+    pub event_key: Pubkey,
+    pub authority: Pubkey,
+    pub deposit_lamports: u64,
+    pub min_delay_slots: u64,
+    pub reveal_window_slots: u64,
+    pub forfeit_bps: u16,
+}
+
+#[event]
+pub struct UserModePolicyUpdatedEvent {
+    pub event_key: Pubkey,
+    pub user_mode: u8,
+    pub allowed_actions: u8,
+    pub price_multiplier_bps: u16,
+    pub dignity_floor: u8,
+}
+
+#[event]
+pub struct AdapterRequirementsUpdatedEvent {
+    pub event_key: Pubkey,
+    pub required_adapter_mask: u8,
+    pub forbidden_adapter_mask: u8,
+}
+
+#[event]
+pub struct PricingStrategyUpdatedEvent {
+    pub event_key: Pubkey,
+    pub pricing_strategy: u8,
+    pub dutch_start_price: u64,
+    pub dutch_floor_price: u64,
+    pub dutch_step_amount: u64,
+    pub dutch_step_interval_secs: u64,
+}
+
+#[event]
+pub struct TicketClassUpdatedEvent {
+    pub event_key: Pubkey,
+    pub class_id: u8,
+    pub base_price: u64,
+    pub velocity_multiplier_bps: u16,
+    pub supply: u32,
+    pub old_base_price: u64,
+    pub old_velocity_multiplier_bps: u16,
+    pub old_supply: u32,
+}
+
+#[event]
+pub struct TransferPolicyUpdatedEvent {
+    pub event_key: Pubkey,
+    pub max_transfers_per_ticket: u16,
+    pub transfer_requires_shield: bool,
+}
+
+#[event]
+pub struct ZkProviderAllowlistUpdatedEvent {
+    pub authority: Pubkey,
+    pub zk_provider: u8,
+    pub allowed: bool,
+}
+
+#[event]
+pub struct ClusterIdUpdatedEvent {
+    pub authority: Pubkey,
+    pub old_cluster_id: [u8; 32],
+    pub new_cluster_id: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct WaitlistRegisteredEvent {
+    pub lottery: Pubkey,
+    pub user: Pubkey,
+    pub zk_proof_hash: [u8; 32],
+    pub weight: u8,
+}
+
+#[event]
+pub struct LotteryInitializedEvent {
+    pub lottery: Pubkey,
+    pub event_key: Pubkey,
+    pub registration_start: i64,
+    pub registration_end: i64,
+    pub winner_slots: u32,
+    pub seed_commitment: [u8; 32],
+}
+
+#[event]
+pub struct LotteryDrawScheduledEvent {
+    pub lottery: Pubkey,
+    pub draw_slot: u64,
+}
+
+#[event]
+pub struct LotterySeedRevealedEvent {
+    pub lottery: Pubkey,
+    pub seed: [u8; 32],
+}
+
+#[event]
+pub struct LotteryDrawnEvent {
+    pub lottery: Pubkey,
+    pub randomness: [u8; 32],
+    pub entry_count: u32,
+    pub total_weight: u64,
+}
+
+#[event]
+pub struct WaitlistEntryRankedEvent {
+    pub lottery: Pubkey,
+    pub zk_proof_hash: [u8; 32],
+    pub score: u128,
+    pub ranked_count: u32,
+}
+
+#[event]
+pub struct PurchaseCommittedEvent {
+    pub user: Pubkey,
+    pub event_key: Pubkey,
+    pub commitment: [u8; 32],
+    pub deposit_lamports: u64,
+    pub reveal_after_slot: u64,
+    pub reveal_deadline_slot: u64,
+}
+
+#[event]
+pub struct PurchaseCommitmentForfeitedEvent {
+    pub user: Pubkey,
+    pub event_key: Pubkey,
+    pub forfeited_lamports: u64,
+    pub refunded_lamports: u64,
+}
+
+#[event]
+pub struct SealedBidAuctionInitializedEvent {
+    pub auction: Pubkey,
+    pub event_key: Pubkey,
+    pub ticket_class: u8,
+    pub commit_end: i64,
+    pub reveal_end: i64,
+    pub claim_end: i64,
+    pub winner_slots: u8,
+    pub reserve_price: u64,
+}
+
+#[event]
+pub struct SealedBidCommittedEvent {
+    pub auction: Pubkey,
+    pub bidder: Pubkey,
+    pub escrow_lamports: u64,
+}
+
+#[event]
+pub struct SealedBidRevealedEvent {
+    pub auction: Pubkey,
+    pub bidder: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct SealedBidSettledEvent {
+    pub auction: Pubkey,
+    pub bidder: Pubkey,
+    pub won: bool,
+    pub paid_lamports: u64,
+    pub refunded_lamports: u64,
+    pub ticket_class: u8,
+}
+
 #[event]
 pub struct ShieldExecutionEvent {
     pub final_price: u64,
@@ -231,32 +1041,298 @@ pub struct TicketSaleEvent {
     pub resale_count: u64,
 }
 
-#[program]
-pub mod unforgiven_v2 {
-    use super::*;
+#[event]
+pub struct AccountLayoutMigratedEvent {
+    pub account: Pubkey,
+    pub previous_version: u8,
+    pub layout_version: u8,
+    pub data_len: u32,
+}
 
-    pub fn initialize_v2(ctx: Context<InitializeV2>) -> Result<()> {
-        let cfg = &mut ctx.accounts.global_config_v2;
-        cfg.authority = ctx.accounts.authority.key();
-        cfg.bump = ctx.bumps.global_config_v2;
-        Ok(())
-    }
+#[event]
+pub struct ShieldGatePassedEvent {
+    pub user: Pubkey,
+    pub final_price: u64,
+    pub user_mode: u8,
+    pub nonce: u64,
+    pub zk_proof_hash: [u8; 32],
+}
 
-    pub fn initialize_admin_config(
-        ctx: Context<InitializeAdminConfig>,
-        oracle_pubkey: [u8; 32],
-        active_scoring_model_hash: [u8; 32],
-    ) -> Result<()> {
-        let admin = &mut ctx.accounts.admin_config;
+#[event]
+pub struct V1TicketMigratedEvent {
+    pub v1_ticket: Pubkey,
+    pub buyer: Pubkey,
+    pub mint: Pubkey,
+    pub v1_purchase_time: i64,
+}
+
+#[event]
+pub struct TicketRedeemedEvent {
+    pub mint: Pubkey,
+    pub holder: Pubkey,
+    pub redeemed_at: i64,
+}
+
+#[event]
+pub struct ReputationUpdatedEvent {
+    pub wallet: Pubkey,
+    pub fair_purchases: u32,
+    pub redeemed_tickets: u32,
+    pub flips: u32,
+    pub blocked_attempts: u32,
+}
+
+#[event]
+pub struct BlockedAttemptRecordedEvent {
+    pub wallet: Pubkey,
+    pub nonce: u64,
+    pub zk_proof_hash: [u8; 32],
+}
+
+#[event]
+pub struct EntrySecretRotatedEvent {
+    pub mint: Pubkey,
+    pub holder: Pubkey,
+    pub entry_secret_commitment: [u8; 32],
+    pub rotated_at: i64,
+}
+
+#[event]
+pub struct EndingBadgeIssuedEvent {
+    pub player: Pubkey,
+    pub mint: Pubkey,
+    pub ending_code: u8,
+    pub session_id: u64,
+    pub humanity_score: u8,
+    pub truth_unlocked: bool,
+}
+
+#[event]
+pub struct GraphPublisherUpdatedEvent {
+    pub authority: Pubkey,
+    pub old_publisher: Pubkey,
+    pub new_publisher: Pubkey,
+}
+
+#[event]
+pub struct GraphSnapshotAnchoredEvent {
+    pub version: u64,
+    pub snapshot_hash: [u8; 32],
+    pub root_kind: u8,
+    pub publisher: Pubkey,
+    pub anchored_at: i64,
+}
+
+#[event]
+pub struct MembershipConfigUpdatedEvent {
+    pub authority: Pubkey,
+    pub tier_prices: [u64; MEMBERSHIP_TIER_COUNT],
+    pub period_secs: i64,
+    pub old_tier_prices: [u64; MEMBERSHIP_TIER_COUNT],
+    pub old_period_secs: i64,
+}
+
+#[event]
+pub struct MembershipRequirementUpdatedEvent {
+    pub event_key: Pubkey,
+    pub min_membership_tier: u8,
+}
+
+#[event]
+pub struct ScannerUpdatedEvent {
+    pub event_key: Pubkey,
+    pub scanner: Pubkey,
+}
+
+#[event]
+pub struct MembershipUpdatedEvent {
+    pub wallet: Pubkey,
+    pub previous_tier: u8,
+    pub tier: u8,
+    pub expires_at: i64,
+    pub price_paid: u64,
+}
+
+#[event]
+pub struct TicketTransferredEvent {
+    pub mint: Pubkey,
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub holder_user_mode: u8,
+    pub transfer_count: u16,
+    pub shielded: bool,
+}
+
+#[program]
+pub mod unforgiven_v2 {
+    use super::*;
+
+    pub fn initialize_v2(ctx: Context<InitializeV2>) -> Result<()> {
+        let cfg = &mut ctx.accounts.global_config_v2;
+        cfg.authority = ctx.accounts.authority.key();
+        cfg.bump = ctx.bumps.global_config_v2;
+        cfg.layout_version = account_layout::global_config_v2::VERSION;
+
+        let now = Clock::get()?.unix_timestamp;
+        let audit_log = &mut ctx.accounts.audit_log;
+        audit_log.bump = ctx.bumps.audit_log;
+        push_audit_entry(
+            audit_log,
+            AUDIT_ACTION_GLOBAL_CONFIG_INITIALIZED,
+            cfg.authority,
+            now,
+            [0u8; 32],
+            cfg.authority.to_bytes(),
+        );
+
+        emit!(GlobalConfigInitializedEvent {
+            authority: cfg.authority,
+            timestamp: now,
+        });
+        Ok(())
+    }
+
+    pub fn initialize_admin_config(
+        ctx: Context<InitializeAdminConfig>,
+        oracle_pubkey: [u8; 32],
+        active_scoring_model_hash: [u8; 32],
+        cluster_id: [u8; 32],
+    ) -> Result<()> {
+        require!(cluster_id != [0u8; 32], UnforgivenV2Error::InvalidClusterId);
+        let admin = &mut ctx.accounts.admin_config;
         admin.authority = ctx.accounts.authority.key();
         admin.oracle_pubkey = oracle_pubkey;
         admin.active_scoring_model_hash = active_scoring_model_hash;
         admin.bump = ctx.bumps.admin_config;
+        admin.zk_provider_allowlist = [u8::MAX; ZK_PROVIDER_ALLOWLIST_LEN];
+        admin.cluster_id = cluster_id;
+        admin.layout_version = account_layout::admin_config::VERSION;
+
+        let now = Clock::get()?.unix_timestamp;
+        push_audit_entry(
+            &mut ctx.accounts.audit_log,
+            AUDIT_ACTION_ADMIN_CONFIG_INITIALIZED,
+            admin.authority,
+            now,
+            [0u8; 32],
+            oracle_pubkey,
+        );
+
+        emit!(AdminConfigInitializedEvent {
+            authority: admin.authority,
+            oracle_pubkey,
+            active_scoring_model_hash,
+            cluster_id,
+            timestamp: now,
+        });
+        Ok(())
+    }
+
+    pub fn migrate_global_config(ctx: Context<MigrateGlobalConfig>) -> Result<()> {
+        use account_layout::global_config_v2 as layout;
+        let account = ctx.accounts.global_config_v2.to_account_info();
+        require_layout_authority(&account, layout::AUTHORITY, &ctx.accounts.authority.key())?;
+        let (_, previous_version) = migrate_account_layout(
+            &account,
+            &ctx.accounts.authority.to_account_info(),
+            ctx.program_id,
+            &GlobalConfigV2::DISCRIMINATOR,
+            layout::LEN,
+            layout::LAYOUT_VERSION,
+            layout::VERSION,
+        )?;
+        push_layout_audit_entry(
+            &mut ctx.accounts.audit_log,
+            AUDIT_ACTION_GLOBAL_CONFIG_MIGRATED,
+            ctx.accounts.authority.key(),
+            previous_version,
+            layout::VERSION,
+        )
+    }
+
+    // `cluster_id` is only written if the account predates the field; later changes go
+    // through `set_cluster_id`.
+    pub fn migrate_admin_config(
+        ctx: Context<MigrateAdminConfig>,
+        cluster_id: [u8; 32],
+    ) -> Result<()> {
+        use account_layout::admin_config as layout;
+        require!(cluster_id != [0u8; 32], UnforgivenV2Error::InvalidClusterId);
+        let account = ctx.accounts.admin_config.to_account_info();
+        require_layout_authority(&account, layout::AUTHORITY, &ctx.accounts.authority.key())?;
+        let (previous_len, previous_version) = migrate_account_layout(
+            &account,
+            &ctx.accounts.authority.to_account_info(),
+            ctx.program_id,
+            &AdminConfig::DISCRIMINATOR,
+            layout::LEN,
+            layout::LAYOUT_VERSION,
+            layout::VERSION,
+        )?;
+        write_admin_config_defaults(
+            &mut account.try_borrow_mut_data()?,
+            previous_len,
+            &cluster_id,
+        );
+        push_layout_audit_entry(
+            &mut ctx.accounts.audit_log,
+            AUDIT_ACTION_ADMIN_CONFIG_MIGRATED,
+            ctx.accounts.authority.key(),
+            previous_version,
+            layout::VERSION,
+        )
+    }
+
+    // Permissionless: growing a receipt only appends zeroed fields, and the payer covers
+    // the extra rent. It is left out of the audit log, which anyone could otherwise flush;
+    // `AccountLayoutMigratedEvent` records it.
+    pub fn migrate_ticket_receipt(ctx: Context<MigrateTicketReceipt>) -> Result<()> {
+        use account_layout::ticket_receipt as layout;
+        let account = ctx.accounts.ticket_receipt.to_account_info();
+        let (previous_len, _) = migrate_account_layout(
+            &account,
+            &ctx.accounts.payer.to_account_info(),
+            ctx.program_id,
+            &TicketReceipt::DISCRIMINATOR,
+            layout::LEN,
+            layout::LAYOUT_VERSION,
+            layout::VERSION,
+        )?;
+        write_ticket_receipt_defaults(&mut account.try_borrow_mut_data()?, previous_len);
+        Ok(())
+    }
+
+    // The admin config is read raw so a deployment can create the log before migrating it.
+    pub fn initialize_audit_log(ctx: Context<InitializeAuditLog>) -> Result<()> {
+        require_layout_authority(
+            &ctx.accounts.admin_config.to_account_info(),
+            account_layout::admin_config::AUTHORITY,
+            &ctx.accounts.authority.key(),
+        )?;
+        ctx.accounts.audit_log.bump = ctx.bumps.audit_log;
         Ok(())
     }
 
     pub fn rotate_oracle(ctx: Context<AdminOnly>, oracle_pubkey: [u8; 32]) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let authority = ctx.accounts.authority.key();
+        let old_oracle_pubkey = ctx.accounts.admin_config.oracle_pubkey;
         ctx.accounts.admin_config.oracle_pubkey = oracle_pubkey;
+
+        push_audit_entry(
+            &mut ctx.accounts.audit_log,
+            AUDIT_ACTION_ORACLE_ROTATED,
+            authority,
+            now,
+            old_oracle_pubkey,
+            oracle_pubkey,
+        );
+        emit!(OracleRotatedEvent {
+            authority,
+            old_oracle_pubkey,
+            new_oracle_pubkey: oracle_pubkey,
+            timestamp: now,
+        });
         Ok(())
     }
 
@@ -264,457 +1340,1576 @@ pub mod unforgiven_v2 {
         ctx: Context<AdminOnly>,
         active_scoring_model_hash: [u8; 32],
     ) -> Result<()> {
-        ctx.accounts.admin_config.active_scoring_model_hash = active_scoring_model_hash;
-        Ok(())
+        replace_scoring_model_hash(ctx.accounts, active_scoring_model_hash, false)
     }
 
     pub fn reset_admin_guardrails(
         ctx: Context<AdminOnly>,
         baseline_scoring_model_hash: [u8; 32],
     ) -> Result<()> {
-        ctx.accounts.admin_config.active_scoring_model_hash = baseline_scoring_model_hash;
+        replace_scoring_model_hash(ctx.accounts, baseline_scoring_model_hash, true)
+    }
+
+    pub fn set_zk_provider_allowed(
+        ctx: Context<AdminOnly>,
+        zk_provider: u8,
+        allowed: bool,
+    ) -> Result<()> {
+        let was_allowed = zk_provider_allowed(
+            &ctx.accounts.admin_config.zk_provider_allowlist,
+            zk_provider,
+        );
+        set_zk_provider_bit(
+            &mut ctx.accounts.admin_config.zk_provider_allowlist,
+            zk_provider,
+            allowed,
+        );
+
+        let mut old_value = [0u8; 32];
+        old_value[0] = zk_provider;
+        old_value[1] = u8::from(was_allowed);
+        let mut new_value = old_value;
+        new_value[1] = u8::from(allowed);
+        push_audit_entry(
+            &mut ctx.accounts.audit_log,
+            AUDIT_ACTION_ZK_PROVIDER_ALLOWED,
+            ctx.accounts.authority.key(),
+            Clock::get()?.unix_timestamp,
+            old_value,
+            new_value,
+        );
+        emit!(ZkProviderAllowlistUpdatedEvent {
+            authority: ctx.accounts.authority.key(),
+            zk_provider,
+            allowed,
+        });
         Ok(())
     }
 
-    pub fn preview_price(
-        ctx: Context<PreviewPrice>,
-        payload: ShieldPayloadV0,
-        oracle_signature: [u8; 64],
+    // Every outstanding oracle signature is bound to the old id, so the oracle must be
+    // re-pointed at the same time.
+    pub fn set_cluster_id(ctx: Context<AdminOnly>, cluster_id: [u8; 32]) -> Result<()> {
+        require!(cluster_id != [0u8; 32], UnforgivenV2Error::InvalidClusterId);
+        let now = Clock::get()?.unix_timestamp;
+        let authority = ctx.accounts.authority.key();
+        let old_cluster_id = ctx.accounts.admin_config.cluster_id;
+        ctx.accounts.admin_config.cluster_id = cluster_id;
+
+        push_audit_entry(
+            &mut ctx.accounts.audit_log,
+            AUDIT_ACTION_CLUSTER_ID_SET,
+            authority,
+            now,
+            old_cluster_id,
+            cluster_id,
+        );
+        emit!(ClusterIdUpdatedEvent {
+            authority,
+            old_cluster_id,
+            new_cluster_id: cluster_id,
+            timestamp: now,
+        });
+        Ok(())
+    }
+
+    pub fn initialize_sale_config(
+        ctx: Context<InitializeSaleConfig>,
+        max_uses_per_proof: u16,
     ) -> Result<()> {
-        let clock = Clock::get()?;
-        let payload_bytes = serialize_shield_payload_v0(&payload);
-        validate_preview_request_fields(
-            &payload,
-            &ctx.accounts.admin_config,
-            &ctx.accounts.user.key(),
-            clock.unix_timestamp,
-        )?;
-        verify_ed25519_ix(
-            &ctx.accounts.instructions.to_account_info(),
-            &payload_bytes,
-            &oracle_signature,
-            &ctx.accounts.admin_config.oracle_pubkey,
+        require!(max_uses_per_proof > 0, UnforgivenV2Error::InvalidProofQuota);
+        let sale = &mut ctx.accounts.sale_config;
+        sale.event_key = ctx.accounts.global_config_v2.key();
+        sale.max_uses_per_proof = max_uses_per_proof;
+        sale.mode_policies = [UserModePolicy::PERMISSIVE; USER_MODE_COUNT];
+        sale.required_adapter_mask = 0;
+        sale.forbidden_adapter_mask = 0;
+        sale.bump = ctx.bumps.sale_config;
+        sale.allocation_mode = ALLOCATION_MODE_OPEN;
+        sale.commit_deposit_lamports = 0;
+        sale.commit_min_delay_slots = 0;
+        sale.commit_reveal_window_slots = 0;
+        sale.commit_forfeit_bps = 0;
+        sale.pricing_strategy = PRICING_STRATEGY_VRGDA;
+        sale.dutch_floor_price = 0;
+        sale.dutch_step_amount = 0;
+        sale.dutch_step_interval_secs = 0;
+        sale.max_transfers_per_ticket = 0;
+        sale.transfer_requires_shield = true;
+        sale.min_membership_tier = MEMBERSHIP_TIER_NONE;
+        sale.dutch_start_price = 0;
+        sale.ticket_class_count = 0;
+        sale.scanner = ctx.accounts.authority.key();
+        push_sale_init_audit_entry(
+            &mut ctx.accounts.audit_log,
+            AUDIT_ACTION_SALE_CONFIG_INITIALIZED,
+            ctx.accounts.authority.key(),
+            &sale.event_key,
+            &max_uses_per_proof,
         )?;
 
-        let quote = quote_from_payload(&payload)?;
-        emit!(PreviewPriceEvent {
-            final_price: quote.final_price,
-            is_infinite: quote.is_infinite,
-            blocked: quote.blocked,
-            effective_velocity_bps: quote.effective_velocity_bps,
-            dignity_score: payload.dignity_score,
-            adapter_mask: payload.adapter_mask,
-            dignity_bucket: dignity_bucket(payload.dignity_score),
-            user_mode: payload.user_mode,
+        emit!(SaleConfigInitializedEvent {
+            event_key: sale.event_key,
+            authority: ctx.accounts.authority.key(),
+            max_uses_per_proof,
         });
         Ok(())
     }
 
-    pub fn execute_shield(
-        ctx: Context<ExecuteShield>,
-        seed_payload: ShieldPayloadV0,
-        _oracle_signature: [u8; 64],
+    // Permissionless: a proof's quota starts unused, and purchases require it to exist.
+    pub fn initialize_proof_quota(
+        ctx: Context<InitializeProofQuota>,
+        zk_proof_hash: [u8; 32],
     ) -> Result<()> {
-        let clock = Clock::get()?;
-        let current_ix_idx = load_current_index_checked(&ctx.accounts.instructions.to_account_info())
-            .map_err(|_| error!(UnforgivenV2Error::InvalidEd25519Instruction))?;
-        let current_ix = load_instruction_at_checked(
-            current_ix_idx as usize,
-            &ctx.accounts.instructions.to_account_info(),
-        )
-        .map_err(|_| error!(UnforgivenV2Error::InvalidEd25519Instruction))?;
-        require_keys_eq!(
-            current_ix.program_id,
-            crate::id(),
-            UnforgivenV2Error::InvalidEd25519Instruction
-        );
+        let proof_quota = &mut ctx.accounts.proof_quota;
+        proof_quota.event_key = ctx.accounts.sale_config.event_key;
+        proof_quota.zk_proof_hash = zk_proof_hash;
+        proof_quota.uses = 0;
+        proof_quota.bump = ctx.bumps.proof_quota;
+        Ok(())
+    }
+
+    pub fn set_proof_quota(ctx: Context<SaleAdminOnly>, max_uses_per_proof: u16) -> Result<()> {
+        require!(max_uses_per_proof > 0, UnforgivenV2Error::InvalidProofQuota);
+        let sale = &mut ctx.accounts.sale_config;
+        let old_max_uses_per_proof = sale.max_uses_per_proof;
+        sale.max_uses_per_proof = max_uses_per_proof;
+        push_sale_audit_entry(
+            &mut ctx.accounts.audit_log,
+            AUDIT_ACTION_PROOF_QUOTA_SET,
+            ctx.accounts.authority.key(),
+            &sale.event_key,
+            &old_max_uses_per_proof,
+            &max_uses_per_proof,
+        )?;
+
+        emit!(ProofQuotaUpdatedEvent {
+            event_key: sale.event_key,
+            authority: ctx.accounts.authority.key(),
+            old_max_uses_per_proof,
+            new_max_uses_per_proof: max_uses_per_proof,
+        });
+        Ok(())
+    }
 
-        let current_data = current_ix.data.as_slice();
+    pub fn set_user_mode_policy(
+        ctx: Context<SaleAdminOnly>,
+        user_mode: u8,
+        policy: UserModePolicy,
+    ) -> Result<()> {
         require!(
-            current_data.len() == 8 + SHIELD_PAYLOAD_V0_LEN + ED25519_SIGNATURE_LEN,
-            UnforgivenV2Error::InvalidEd25519Instruction
+            user_mode <= USER_MODE_VERIFIED,
+            UnforgivenV2Error::InvalidUserMode
         );
         require!(
-            current_data[..8] == EXECUTE_SHIELD_DISCRIMINATOR,
-            UnforgivenV2Error::InvalidEd25519Instruction
+            policy.allowed_actions & !MODE_ACTION_ALL == 0
+                && policy.price_multiplier_bps > 0
+                && policy.dignity_floor <= 100,
+            UnforgivenV2Error::InvalidUserModePolicy
         );
 
-        let payload_bytes = &current_data[8..8 + SHIELD_PAYLOAD_V0_LEN];
-        let oracle_signature = &current_data[8 + SHIELD_PAYLOAD_V0_LEN..];
-        let expected_payload_bytes = serialize_shield_payload_v0(&seed_payload);
-        require!(
-            payload_bytes == expected_payload_bytes.as_slice(),
-            UnforgivenV2Error::InvalidEd25519Instruction
-        );
-        validate_preview_request_fields(
-            &seed_payload,
-            &ctx.accounts.admin_config,
-            &ctx.accounts.user.key(),
-            clock.unix_timestamp,
-        )?;
-        verify_ed25519_ix(
-            &ctx.accounts.instructions.to_account_info(),
-            payload_bytes,
-            oracle_signature,
-            &ctx.accounts.admin_config.oracle_pubkey,
+        let sale = &mut ctx.accounts.sale_config;
+        let old_policy = sale.mode_policies[user_mode as usize];
+        sale.mode_policies[user_mode as usize] = policy;
+        push_sale_audit_entry(
+            &mut ctx.accounts.audit_log,
+            AUDIT_ACTION_USER_MODE_POLICY_SET,
+            ctx.accounts.authority.key(),
+            &sale.event_key,
+            &(user_mode, old_policy),
+            &(user_mode, policy),
         )?;
 
-        require_keys_eq!(
-            ctx.accounts.treasury.key(),
-            ctx.accounts.global_config_v2.authority,
-            UnforgivenV2Error::TreasuryMismatch
-        );
+        emit!(UserModePolicyUpdatedEvent {
+            event_key: sale.event_key,
+            user_mode,
+            allowed_actions: policy.allowed_actions,
+            price_multiplier_bps: policy.price_multiplier_bps,
+            dignity_floor: policy.dignity_floor,
+        });
+        Ok(())
+    }
 
-        let nonce_bytes = seed_payload.nonce.to_le_bytes();
-        let user_key = ctx.accounts.user.key();
-        let (expected_proof_use, proof_use_bump) = Pubkey::find_program_address(
-            &[
-                b"proof_use",
-                seed_payload.user_pubkey.as_ref(),
-                seed_payload.zk_proof_hash.as_ref(),
-                nonce_bytes.as_ref(),
-            ],
-            ctx.program_id,
-        );
-        require_keys_eq!(
-            ctx.accounts.proof_use.key(),
-            expected_proof_use,
-            UnforgivenV2Error::InvalidExecuteShieldAccount
+    pub fn set_adapter_requirements(
+        ctx: Context<SaleAdminOnly>,
+        required_adapter_mask: u8,
+        forbidden_adapter_mask: u8,
+    ) -> Result<()> {
+        require!(
+            required_adapter_mask & forbidden_adapter_mask == 0,
+            UnforgivenV2Error::InvalidAdapterRequirements
         );
 
-        let (expected_ticket_mint, ticket_mint_bump) = Pubkey::find_program_address(
-            &[
-                TICKET_MINT_SEED,
-                seed_payload.user_pubkey.as_ref(),
-                seed_payload.zk_proof_hash.as_ref(),
-                nonce_bytes.as_ref(),
-            ],
-            ctx.program_id,
-        );
-        require_keys_eq!(
-            ctx.accounts.ticket_mint.key(),
-            expected_ticket_mint,
-            UnforgivenV2Error::InvalidExecuteShieldAccount
-        );
+        let sale = &mut ctx.accounts.sale_config;
+        let old_masks = (sale.required_adapter_mask, sale.forbidden_adapter_mask);
+        sale.required_adapter_mask = required_adapter_mask;
+        sale.forbidden_adapter_mask = forbidden_adapter_mask;
+        push_sale_audit_entry(
+            &mut ctx.accounts.audit_log,
+            AUDIT_ACTION_ADAPTER_REQUIREMENTS_SET,
+            ctx.accounts.authority.key(),
+            &sale.event_key,
+            &old_masks,
+            &(required_adapter_mask, forbidden_adapter_mask),
+        )?;
 
-        let (expected_user_ticket_token, user_ticket_token_bump) = Pubkey::find_program_address(
-            &[TICKET_TOKEN_SEED, expected_ticket_mint.as_ref(), user_key.as_ref()],
-            ctx.program_id,
-        );
-        require_keys_eq!(
-            ctx.accounts.user_ticket_token.key(),
-            expected_user_ticket_token,
-            UnforgivenV2Error::InvalidExecuteShieldAccount
-        );
+        emit!(AdapterRequirementsUpdatedEvent {
+            event_key: sale.event_key,
+            required_adapter_mask,
+            forbidden_adapter_mask,
+        });
+        Ok(())
+    }
 
-        let (expected_ticket_receipt, ticket_receipt_bump) = Pubkey::find_program_address(
-            &[TICKET_RECEIPT_SEED, expected_ticket_mint.as_ref()],
-            ctx.program_id,
+    pub fn set_commit_reveal_params(
+        ctx: Context<SaleAdminOnly>,
+        deposit_lamports: u64,
+        min_delay_slots: u64,
+        reveal_window_slots: u64,
+        forfeit_bps: u16,
+    ) -> Result<()> {
+        require!(
+            forfeit_bps <= 10_000 && (deposit_lamports == 0 || reveal_window_slots > 0),
+            UnforgivenV2Error::InvalidCommitRevealParams
         );
-        require_keys_eq!(
-            ctx.accounts.ticket_receipt.key(),
-            expected_ticket_receipt,
-            UnforgivenV2Error::InvalidExecuteShieldAccount
+        let sale = &mut ctx.accounts.sale_config;
+        let old_params = (
+            sale.commit_deposit_lamports,
+            sale.commit_min_delay_slots,
+            sale.commit_reveal_window_slots,
+            sale.commit_forfeit_bps,
         );
-
-        create_pda_account(
-            &ctx.accounts.user.to_account_info(),
-            &ctx.accounts.proof_use.to_account_info(),
-            &ctx.accounts.system_program.to_account_info(),
-            ctx.program_id,
-            8 + ProofUse::INIT_SPACE,
-            &[
-                b"proof_use",
-                seed_payload.user_pubkey.as_ref(),
-                seed_payload.zk_proof_hash.as_ref(),
-                nonce_bytes.as_ref(),
-                &[proof_use_bump],
-            ],
+        sale.commit_deposit_lamports = deposit_lamports;
+        sale.commit_min_delay_slots = min_delay_slots;
+        sale.commit_reveal_window_slots = reveal_window_slots;
+        sale.commit_forfeit_bps = forfeit_bps;
+        push_sale_audit_entry(
+            &mut ctx.accounts.audit_log,
+            AUDIT_ACTION_COMMIT_REVEAL_PARAMS_SET,
+            ctx.accounts.authority.key(),
+            &sale.event_key,
+            &old_params,
+            &(
+                deposit_lamports,
+                min_delay_slots,
+                reveal_window_slots,
+                forfeit_bps,
+            ),
         )?;
-        create_pda_account(
-            &ctx.accounts.user.to_account_info(),
-            &ctx.accounts.ticket_mint.to_account_info(),
-            &ctx.accounts.system_program.to_account_info(),
-            &spl_token::id(),
-            SPL_TOKEN_MINT_LEN,
-            &[
-                TICKET_MINT_SEED,
-                seed_payload.user_pubkey.as_ref(),
-                seed_payload.zk_proof_hash.as_ref(),
-                nonce_bytes.as_ref(),
-                &[ticket_mint_bump],
-            ],
-        )?;
-        initialize_ticket_mint(
-            &ctx.accounts.token_program.to_account_info(),
-            &ctx.accounts.ticket_mint.to_account_info(),
-            &ctx.accounts.ticket_mint_authority.key(),
+
+        emit!(CommitRevealParamsUpdatedEvent {
+            event_key: sale.event_key,
+            authority: ctx.accounts.authority.key(),
+            deposit_lamports,
+            min_delay_slots,
+            reveal_window_slots,
+            forfeit_bps,
+        });
+        Ok(())
+    }
+
+    pub fn set_pricing_strategy(
+        ctx: Context<SaleAdminOnly>,
+        pricing_strategy: u8,
+        dutch_start_price: u64,
+        dutch_floor_price: u64,
+        dutch_step_amount: u64,
+        dutch_step_interval_secs: u64,
+    ) -> Result<()> {
+        validate_pricing_strategy(
+            pricing_strategy,
+            dutch_start_price,
+            dutch_floor_price,
+            dutch_step_amount,
+            dutch_step_interval_secs,
         )?;
-        create_pda_account(
-            &ctx.accounts.user.to_account_info(),
-            &ctx.accounts.user_ticket_token.to_account_info(),
-            &ctx.accounts.system_program.to_account_info(),
-            &spl_token::id(),
-            SPL_TOKEN_ACCOUNT_LEN,
-            &[
-                TICKET_TOKEN_SEED,
-                expected_ticket_mint.as_ref(),
-                user_key.as_ref(),
-                &[user_ticket_token_bump],
-            ],
-        )?;
-        initialize_ticket_token_account(
-            &ctx.accounts.token_program.to_account_info(),
-            &ctx.accounts.user_ticket_token.to_account_info(),
-            &ctx.accounts.ticket_mint.to_account_info(),
-            &user_key,
+
+        let sale = &mut ctx.accounts.sale_config;
+        let old_params = (
+            sale.pricing_strategy,
+            sale.dutch_start_price,
+            sale.dutch_floor_price,
+            sale.dutch_step_amount,
+            sale.dutch_step_interval_secs,
+        );
+        sale.pricing_strategy = pricing_strategy;
+        sale.dutch_start_price = dutch_start_price;
+        sale.dutch_floor_price = dutch_floor_price;
+        sale.dutch_step_amount = dutch_step_amount;
+        sale.dutch_step_interval_secs = dutch_step_interval_secs;
+        push_sale_audit_entry(
+            &mut ctx.accounts.audit_log,
+            AUDIT_ACTION_PRICING_STRATEGY_SET,
+            ctx.accounts.authority.key(),
+            &sale.event_key,
+            &old_params,
+            &(
+                pricing_strategy,
+                dutch_start_price,
+                dutch_floor_price,
+                dutch_step_amount,
+                dutch_step_interval_secs,
+            ),
         )?;
-        create_pda_account(
-            &ctx.accounts.user.to_account_info(),
-            &ctx.accounts.ticket_receipt.to_account_info(),
-            &ctx.accounts.system_program.to_account_info(),
-            ctx.program_id,
-            8 + TicketReceipt::INIT_SPACE,
-            &[
-                TICKET_RECEIPT_SEED,
-                expected_ticket_mint.as_ref(),
-                &[ticket_receipt_bump],
-            ],
+
+        emit!(PricingStrategyUpdatedEvent {
+            event_key: sale.event_key,
+            pricing_strategy,
+            dutch_start_price,
+            dutch_floor_price,
+            dutch_step_amount,
+            dutch_step_interval_secs,
+        });
+        Ok(())
+    }
+
+    pub fn set_transfer_policy(
+        ctx: Context<SaleAdminOnly>,
+        max_transfers_per_ticket: u16,
+        transfer_requires_shield: bool,
+    ) -> Result<()> {
+        let sale = &mut ctx.accounts.sale_config;
+        let old_policy = (sale.max_transfers_per_ticket, sale.transfer_requires_shield);
+        sale.max_transfers_per_ticket = max_transfers_per_ticket;
+        sale.transfer_requires_shield = transfer_requires_shield;
+        push_sale_audit_entry(
+            &mut ctx.accounts.audit_log,
+            AUDIT_ACTION_TRANSFER_POLICY_SET,
+            ctx.accounts.authority.key(),
+            &sale.event_key,
+            &old_policy,
+            &(max_transfers_per_ticket, transfer_requires_shield),
         )?;
 
-        let quote = quote_from_payload(&seed_payload)?;
-        require!(!quote.blocked, UnforgivenV2Error::ShieldBlocked);
+        emit!(TransferPolicyUpdatedEvent {
+            event_key: sale.event_key,
+            max_transfers_per_ticket,
+            transfer_requires_shield,
+        });
+        Ok(())
+    }
 
-        write_proof_use_account(
-            &ctx.accounts.proof_use.to_account_info(),
-            &seed_payload,
-            clock.unix_timestamp,
-            proof_use_bump,
+    pub fn set_membership_requirement(
+        ctx: Context<SaleAdminOnly>,
+        min_membership_tier: u8,
+    ) -> Result<()> {
+        require!(
+            usize::from(min_membership_tier) <= MEMBERSHIP_TIER_COUNT,
+            UnforgivenV2Error::InvalidMembershipTier
+        );
+        let sale = &mut ctx.accounts.sale_config;
+        let old_min_membership_tier = sale.min_membership_tier;
+        sale.min_membership_tier = min_membership_tier;
+        push_sale_audit_entry(
+            &mut ctx.accounts.audit_log,
+            AUDIT_ACTION_MEMBERSHIP_REQUIREMENT_SET,
+            ctx.accounts.authority.key(),
+            &sale.event_key,
+            &old_min_membership_tier,
+            &min_membership_tier,
         )?;
 
-        transfer_lamports(
-            &ctx.accounts.user.to_account_info(),
-            &ctx.accounts.treasury.to_account_info(),
-            quote.final_price,
+        emit!(MembershipRequirementUpdatedEvent {
+            event_key: sale.event_key,
+            min_membership_tier,
+        });
+        Ok(())
+    }
+
+    // Gate staff scan with their own key, so the protocol authority never has to sit on a
+    // door device.
+    pub fn set_scanner(ctx: Context<SaleAdminOnly>, scanner: Pubkey) -> Result<()> {
+        let sale = &mut ctx.accounts.sale_config;
+        let old_scanner = sale.scanner;
+        sale.scanner = scanner;
+        push_sale_audit_entry(
+            &mut ctx.accounts.audit_log,
+            AUDIT_ACTION_SCANNER_SET,
+            ctx.accounts.authority.key(),
+            &sale.event_key,
+            &old_scanner,
+            &scanner,
         )?;
 
-        let mint_authority_bump = [ctx.bumps.ticket_mint_authority];
-        let mint_authority_seeds: &[&[u8]] =
-            &[TICKET_MINT_AUTHORITY_SEED, &mint_authority_bump];
+        emit!(ScannerUpdatedEvent {
+            event_key: sale.event_key,
+            scanner,
+        });
+        Ok(())
+    }
 
-        token::mint_to(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                MintTo {
-                    mint: ctx.accounts.ticket_mint.to_account_info(),
-                    to: ctx.accounts.user_ticket_token.to_account_info(),
-                    authority: ctx.accounts.ticket_mint_authority.to_account_info(),
-                },
-                &[mint_authority_seeds],
-            ),
-            TICKET_AMOUNT,
+    pub fn initialize_ticket_class(
+        ctx: Context<InitializeTicketClass>,
+        class_id: u8,
+        base_price: u64,
+        velocity_multiplier_bps: u16,
+        supply: u32,
+    ) -> Result<()> {
+        require!(
+            class_id != TICKET_CLASS_NONE && base_price > 0,
+            UnforgivenV2Error::InvalidTicketClass
+        );
+        let class = &mut ctx.accounts.ticket_class;
+        class.event_key = ctx.accounts.sale_config.event_key;
+        class.class_id = class_id;
+        class.base_price = base_price;
+        class.velocity_multiplier_bps = velocity_multiplier_bps;
+        class.supply = supply;
+        class.sold = 0;
+        class.bump = ctx.bumps.ticket_class;
+        class.allocation_mode = ALLOCATION_MODE_OPEN;
+
+        let sale = &mut ctx.accounts.sale_config;
+        sale.ticket_class_count = sale
+            .ticket_class_count
+            .checked_add(1)
+            .ok_or(error!(UnforgivenV2Error::InvalidTicketClass))?;
+        push_sale_init_audit_entry(
+            &mut ctx.accounts.audit_log,
+            AUDIT_ACTION_TICKET_CLASS_INITIALIZED,
+            ctx.accounts.authority.key(),
+            &class.event_key,
+            &(class_id, base_price, velocity_multiplier_bps, supply),
         )?;
 
-        token::set_authority(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                SetAuthority {
-                    account_or_mint: ctx.accounts.ticket_mint.to_account_info(),
-                    current_authority: ctx.accounts.ticket_mint_authority.to_account_info(),
-                },
-                &[mint_authority_seeds],
-            ),
-            AuthorityType::MintTokens,
-            None,
+        emit!(TicketClassUpdatedEvent {
+            event_key: class.event_key,
+            class_id,
+            base_price,
+            velocity_multiplier_bps,
+            supply,
+            old_base_price: 0,
+            old_velocity_multiplier_bps: 0,
+            old_supply: 0,
+        });
+        Ok(())
+    }
+
+    pub fn update_ticket_class(
+        ctx: Context<UpdateTicketClass>,
+        base_price: u64,
+        velocity_multiplier_bps: u16,
+        supply: u32,
+    ) -> Result<()> {
+        let class = &mut ctx.accounts.ticket_class;
+        require!(
+            base_price > 0 && supply >= class.sold,
+            UnforgivenV2Error::InvalidTicketClass
+        );
+        let old_settings = (
+            class.class_id,
+            class.base_price,
+            class.velocity_multiplier_bps,
+            class.supply,
+        );
+        class.base_price = base_price;
+        class.velocity_multiplier_bps = velocity_multiplier_bps;
+        class.supply = supply;
+        push_sale_audit_entry(
+            &mut ctx.accounts.audit_log,
+            AUDIT_ACTION_TICKET_CLASS_UPDATED,
+            ctx.accounts.authority.key(),
+            &class.event_key,
+            &old_settings,
+            &(class.class_id, base_price, velocity_multiplier_bps, supply),
         )?;
 
-        token::set_authority(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                SetAuthority {
-                    account_or_mint: ctx.accounts.ticket_mint.to_account_info(),
-                    current_authority: ctx.accounts.ticket_mint_authority.to_account_info(),
-                },
-                &[mint_authority_seeds],
+        emit!(TicketClassUpdatedEvent {
+            event_key: class.event_key,
+            class_id: class.class_id,
+            base_price,
+            velocity_multiplier_bps,
+            supply,
+            old_base_price: old_settings.1,
+            old_velocity_multiplier_bps: old_settings.2,
+            old_supply: old_settings.3,
+        });
+        Ok(())
+    }
+
+    pub fn preview_price(
+        ctx: Context<PreviewPrice>,
+        payload: ShieldPayloadV0,
+        oracle_signature: [u8; 64],
+        sig_instruction_index: u16,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let message = serialize_shield_message_v0(
+            &payload,
+            &shield_signing_domain(
+                &ctx.accounts.admin_config,
+                SIGNING_PURPOSE_PREVIEW,
+                ticket_class_id(ctx.accounts.ticket_class.as_deref().map(|class| &**class)),
             ),
-            AuthorityType::FreezeAccount,
-            None,
+        );
+        validate_preview_request_fields(
+            &payload,
+            &ctx.accounts.admin_config,
+            &ctx.accounts.sale_config,
+            &ctx.accounts.user.key(),
+            clock.unix_timestamp,
+        )?;
+        verify_ed25519_ix(
+            &ctx.accounts.instructions.to_account_info(),
+            sig_instruction_index,
+            &message,
+            &oracle_signature,
+            &ctx.accounts.admin_config.oracle_pubkey,
         )?;
 
-        write_ticket_receipt_account(
-            &ctx.accounts.ticket_receipt.to_account_info(),
-            &ctx.accounts.ticket_mint.key(),
-            &ctx.accounts.global_config_v2.key(),
+        let (event, quote_return) = preview_outcome(
+            &payload,
+            &ctx.accounts.sale_config,
+            ctx.accounts.ticket_class.as_deref().map(|class| &**class),
+        )?;
+        emit!(event);
+        // Simulation clients read this instead of decoding the event from logs.
+        set_return_data(&encode_quote_return_v0(&quote_return));
+        Ok(())
+    }
+
+    pub fn execute_shield(
+        ctx: Context<ExecuteShield>,
+        seed_payload: ShieldPayloadV0,
+        oracle_signature: [u8; 64],
+        sig_instruction_index: u16,
+        bumps: ShieldAccountBumps,
+    ) -> Result<()> {
+        ensure_open_allocation(&ctx.accounts.sale_config)?;
+        // With a deposit set, buyers must go through commit_purchase/reveal_purchase.
+        require!(
+            ctx.accounts.sale_config.commit_deposit_lamports == 0,
+            UnforgivenV2Error::CommitRevealRequired
+        );
+        process_shield_purchase(
+            ctx.accounts,
+            ctx.program_id,
+            &seed_payload,
+            &oracle_signature,
+            sig_instruction_index,
+            bumps,
+            true,
+        )
+    }
+
+    // CPI entry point for other programs: verifies and consumes a payload (purpose 10)
+    // without minting, and returns the quote as `QuoteReturnV0` return data. Mode policy
+    // is left to the caller, which receives `user_mode`.
+    pub fn shield_gate(
+        ctx: Context<ShieldGate>,
+        payload: ShieldPayloadV0,
+        oracle_signature: [u8; 64],
+        sig_instruction_index: u16,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let user_key = ctx.accounts.user.key();
+        // A gate pass spends the sale's per-proof quota, so it is held to the sale's
+        // purchase rules too.
+        validate_preview_request_fields(
+            &payload,
+            &ctx.accounts.admin_config,
+            &ctx.accounts.sale_config,
             &user_key,
-            quote.final_price,
             clock.unix_timestamp,
-            &seed_payload,
-            ticket_receipt_bump,
         )?;
+        let message = serialize_shield_message_v0(
+            &payload,
+            &shield_signing_domain(
+                &ctx.accounts.admin_config,
+                SIGNING_PURPOSE_SHIELD_GATE,
+                TICKET_CLASS_NONE,
+            ),
+        );
+        verify_ed25519_ix(
+            &ctx.accounts.instructions.to_account_info(),
+            sig_instruction_index,
+            &message,
+            &oracle_signature,
+            &ctx.accounts.admin_config.oracle_pubkey,
+        )?;
+        enforce_user_mode_policy(
+            &ctx.accounts.sale_config,
+            payload.user_mode,
+            payload.dignity_score,
+            MODE_ACTION_BUY,
+        )?;
+        ensure_membership_tier(
+            &ctx.accounts.sale_config,
+            ctx.accounts
+                .membership
+                .as_deref()
+                .map(|membership| &**membership),
+            clock.unix_timestamp,
+        )?;
+        let quote = quote_from_payload(&payload)?;
+        require!(!quote.blocked, UnforgivenV2Error::ShieldBlocked);
 
-        emit!(ShieldExecutionEvent {
-            final_price: quote.final_price,
-            blocked: quote.blocked,
-            effective_velocity_bps: quote.effective_velocity_bps,
-            dignity_score: seed_payload.dignity_score,
-            adapter_mask: seed_payload.adapter_mask,
-            user_mode: seed_payload.user_mode,
-            nonce: seed_payload.nonce,
-            zk_proof_hash: seed_payload.zk_proof_hash,
-        });
+        let proof_quota = &mut ctx.accounts.proof_quota;
+        proof_quota.uses = next_proof_quota_use(
+            proof_quota.uses,
+            ctx.accounts.sale_config.max_uses_per_proof,
+        )?;
+        record_proof_use(
+            &mut ctx.accounts.proof_use,
+            &payload,
+            clock.unix_timestamp,
+            ctx.bumps.proof_use,
+        );
 
-        emit!(TicketMintedEvent {
-            mint: ctx.accounts.ticket_mint.key(),
-            owner: ctx.accounts.user.key(),
+        emit!(ShieldGatePassedEvent {
+            user: user_key,
             final_price: quote.final_price,
-            nonce: seed_payload.nonce,
+            user_mode: payload.user_mode,
+            nonce: payload.nonce,
+            zk_proof_hash: payload.zk_proof_hash,
         });
-
+        set_return_data(&encode_quote_return_v0(&quote_return_v0(
+            SIGNING_PURPOSE_SHIELD_GATE,
+            &payload,
+            &quote,
+        )));
         Ok(())
     }
 
-    pub fn list_ticket(ctx: Context<ListTicket>, ask_price: u64) -> Result<()> {
-        require!(ask_price > 0, UnforgivenV2Error::InvalidListingPrice);
-        require!(
-            !ctx.accounts.ticket_receipt.listed,
-            UnforgivenV2Error::TicketAlreadyListed
-        );
+    pub fn initialize_lottery(
+        ctx: Context<InitializeLottery>,
+        registration_start: i64,
+        registration_end: i64,
+        winner_slots: u32,
+        seed_commitment: [u8; 32],
+    ) -> Result<()> {
         require!(
-            ctx.accounts.seller_ticket_token.amount == TICKET_AMOUNT,
-            UnforgivenV2Error::InvalidTicketAmount
+            registration_start < registration_end
+                && winner_slots > 0
+                && winner_slots <= MAX_LOTTERY_WINNERS,
+            UnforgivenV2Error::InvalidLotteryConfig
         );
-
-        let clock = Clock::get()?;
-
-        token::transfer_checked(
-            CpiContext::new(
-                ctx.accounts.token_program.to_account_info(),
-                TransferChecked {
-                    from: ctx.accounts.seller_ticket_token.to_account_info(),
-                    mint: ctx.accounts.ticket_mint.to_account_info(),
-                    to: ctx.accounts.listing_escrow_token.to_account_info(),
-                    authority: ctx.accounts.seller.to_account_info(),
-                },
+        let sale = &mut ctx.accounts.sale_config;
+        sale.allocation_mode = ALLOCATION_MODE_LOTTERY;
+
+        let lottery = &mut ctx.accounts.lottery;
+        lottery.event_key = sale.event_key;
+        lottery.registration_start = registration_start;
+        lottery.registration_end = registration_end;
+        lottery.winner_slots = winner_slots;
+        lottery.entry_count = 0;
+        lottery.total_weight = 0;
+        lottery.draw_slot = 0;
+        lottery.randomness = [0; 32];
+        lottery.drawn = false;
+        lottery.claimed = 0;
+        lottery.bump = ctx.bumps.lottery;
+        lottery.ranked_count = 0;
+        lottery.board = [LotteryRank::default(); MAX_LOTTERY_WINNERS as usize];
+        lottery.board_len = 0;
+        lottery.seed_commitment = seed_commitment;
+        lottery.seed = [0; 32];
+        lottery.seed_revealed = false;
+        push_sale_init_audit_entry(
+            &mut ctx.accounts.audit_log,
+            AUDIT_ACTION_LOTTERY_INITIALIZED,
+            ctx.accounts.authority.key(),
+            &lottery.event_key,
+            &(
+                registration_start,
+                registration_end,
+                winner_slots,
+                seed_commitment,
             ),
-            TICKET_AMOUNT,
-            TICKET_DECIMALS,
         )?;
 
-        token::close_account(CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            CloseAccount {
-                account: ctx.accounts.seller_ticket_token.to_account_info(),
-                destination: ctx.accounts.seller.to_account_info(),
-                authority: ctx.accounts.seller.to_account_info(),
-            },
-        ))?;
-
-        let listing = &mut ctx.accounts.listing;
-        listing.seller = ctx.accounts.seller.key();
-        listing.mint = ctx.accounts.ticket_mint.key();
-        listing.ask_price = ask_price;
-        listing.created_at = clock.unix_timestamp;
-        listing.bump = ctx.bumps.listing;
-
-        ctx.accounts.ticket_receipt.listed = true;
-
-        emit!(TicketListedEvent {
-            mint: ctx.accounts.ticket_mint.key(),
-            seller: ctx.accounts.seller.key(),
-            ask_price,
+        emit!(LotteryInitializedEvent {
+            lottery: lottery.key(),
+            event_key: lottery.event_key,
+            registration_start,
+            registration_end,
+            winner_slots,
+            seed_commitment,
         });
-
         Ok(())
     }
 
-    pub fn cancel_ticket_listing(ctx: Context<CancelTicketListing>) -> Result<()> {
+    // The authority opens the seed it committed to at `initialize_lottery`. The draw mixes it
+    // with a slot hash nobody knows yet, so neither side alone picks the winners.
+    pub fn reveal_lottery_seed(ctx: Context<RevealLotterySeed>, seed: [u8; 32]) -> Result<()> {
+        let lottery_key = ctx.accounts.lottery.key();
+        let lottery = &mut ctx.accounts.lottery;
+        require!(!lottery.drawn, UnforgivenV2Error::LotteryAlreadyDrawn);
         require!(
-            ctx.accounts.ticket_receipt.listed,
-            UnforgivenV2Error::TicketNotListed
+            lottery_seed_commitment(&seed) == lottery.seed_commitment,
+            UnforgivenV2Error::LotterySeedMismatch
         );
+        lottery.seed = seed;
+        lottery.seed_revealed = true;
+        emit!(LotterySeedRevealedEvent {
+            lottery: lottery_key,
+            seed,
+        });
+        Ok(())
+    }
+
+    pub fn register_waitlist(
+        ctx: Context<RegisterWaitlist>,
+        payload: ShieldPayloadV0,
+        oracle_signature: [u8; 64],
+        sig_instruction_index: u16,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
         require!(
-            ctx.accounts.listing_escrow_token.amount == TICKET_AMOUNT,
-            UnforgivenV2Error::InvalidTicketAmount
+            now >= ctx.accounts.lottery.registration_start
+                && now < ctx.accounts.lottery.registration_end,
+            UnforgivenV2Error::LotteryRegistrationClosed
         );
-
-        let ticket_mint_key = ctx.accounts.ticket_mint.key();
-        let listing_bump = [ctx.accounts.listing.bump];
-        let listing_seeds: &[&[u8]] =
-            &[TICKET_LISTING_SEED, ticket_mint_key.as_ref(), &listing_bump];
-
-        token::transfer_checked(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                TransferChecked {
-                    from: ctx.accounts.listing_escrow_token.to_account_info(),
-                    mint: ctx.accounts.ticket_mint.to_account_info(),
-                    to: ctx.accounts.seller_ticket_token.to_account_info(),
-                    authority: ctx.accounts.listing.to_account_info(),
-                },
-                &[listing_seeds],
+        validate_preview_request_fields(
+            &payload,
+            &ctx.accounts.admin_config,
+            &ctx.accounts.sale_config,
+            &ctx.accounts.user.key(),
+            now,
+        )?;
+        let message = serialize_shield_message_v0(
+            &payload,
+            &shield_signing_domain(
+                &ctx.accounts.admin_config,
+                SIGNING_PURPOSE_WAITLIST,
+                TICKET_CLASS_NONE,
             ),
-            TICKET_AMOUNT,
-            TICKET_DECIMALS,
+        );
+        verify_ed25519_ix(
+            &ctx.accounts.instructions.to_account_info(),
+            sig_instruction_index,
+            &message,
+            &oracle_signature,
+            &ctx.accounts.admin_config.oracle_pubkey,
+        )?;
+        enforce_user_mode_policy(
+            &ctx.accounts.sale_config,
+            payload.user_mode,
+            payload.dignity_score,
+            MODE_ACTION_BUY,
         )?;
+        require!(
+            !quote_for_sale(&payload, &ctx.accounts.sale_config)?.blocked,
+            UnforgivenV2Error::ShieldBlocked
+        );
 
-        token::close_account(CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            CloseAccount {
-                account: ctx.accounts.listing_escrow_token.to_account_info(),
-                destination: ctx.accounts.seller.to_account_info(),
-                authority: ctx.accounts.listing.to_account_info(),
-            },
-            &[listing_seeds],
-        ))?;
+        let weight = lottery_weight(payload.dignity_score);
+        let lottery = &mut ctx.accounts.lottery;
+        lottery.entry_count = lottery
+            .entry_count
+            .checked_add(1)
+            .ok_or(error!(UnforgivenV2Error::InvalidLotteryConfig))?;
+        lottery.total_weight = lottery
+            .total_weight
+            .checked_add(weight as u64)
+            .ok_or(error!(UnforgivenV2Error::InvalidLotteryConfig))?;
+
+        let entry = &mut ctx.accounts.waitlist_entry;
+        entry.lottery = lottery.key();
+        entry.user = ctx.accounts.user.key();
+        entry.zk_proof_hash = payload.zk_proof_hash;
+        entry.weight = weight;
+        entry.registered_at = now;
+        entry.claimed = false;
+        entry.bump = ctx.bumps.waitlist_entry;
+        entry.ranked = false;
+
+        emit!(WaitlistRegisteredEvent {
+            lottery: entry.lottery,
+            user: entry.user,
+            zk_proof_hash: entry.zk_proof_hash,
+            weight,
+        });
+        Ok(())
+    }
 
-        ctx.accounts.ticket_receipt.listed = false;
+    pub fn draw_lottery(ctx: Context<DrawLottery>) -> Result<()> {
+        let clock = Clock::get()?;
+        let lottery_key = ctx.accounts.lottery.key();
+        let lottery = &mut ctx.accounts.lottery;
+        require!(!lottery.drawn, UnforgivenV2Error::LotteryAlreadyDrawn);
+        require!(
+            clock.unix_timestamp >= lottery.registration_end,
+            UnforgivenV2Error::LotteryDrawNotReady
+        );
 
-        emit!(TicketListingCanceledEvent {
-            mint: ticket_mint_key,
-            seller: ctx.accounts.seller.key(),
+        if lottery.draw_slot != 0 {
+            require!(
+                clock.slot > lottery.draw_slot,
+                UnforgivenV2Error::LotteryDrawNotReady
+            );
+            let slot_hashes_data = ctx.accounts.slot_hashes.try_borrow_data()?;
+            if let Some(slot_hash) = slot_hash_at_or_after(&slot_hashes_data, lottery.draw_slot) {
+                require!(
+                    lottery.seed_revealed,
+                    UnforgivenV2Error::LotterySeedNotRevealed
+                );
+                lottery.randomness = lottery_randomness(
+                    &lottery_key,
+                    &lottery.seed,
+                    &slot_hash,
+                    lottery.entry_count,
+                    lottery.total_weight,
+                );
+                lottery.drawn = true;
+                emit!(LotteryDrawnEvent {
+                    lottery: lottery_key,
+                    randomness: lottery.randomness,
+                    entry_count: lottery.entry_count,
+                    total_weight: lottery.total_weight,
+                });
+                return Ok(());
+            }
+            // Until the committed slot can have aged out of SlotHashes, keep waiting for it.
+            require!(
+                clock.slot - lottery.draw_slot >= slot_hashes::MAX_ENTRIES as u64,
+                UnforgivenV2Error::LotteryDrawNotReady
+            );
+        }
+
+        lottery.draw_slot = clock.slot + LOTTERY_DRAW_DELAY_SLOTS;
+        emit!(LotteryDrawScheduledEvent {
+            lottery: lottery_key,
+            draw_slot: lottery.draw_slot,
         });
+        Ok(())
+    }
 
+    // Permissionless: every entry is scored once after the draw and kept on the board if
+    // it is among the top `winner_slots`. Claims open once all entries are ranked.
+    pub fn rank_waitlist_entry(ctx: Context<RankWaitlistEntry>) -> Result<()> {
+        let lottery_key = ctx.accounts.lottery.key();
+        let lottery = &mut ctx.accounts.lottery;
+        let entry = &mut ctx.accounts.waitlist_entry;
+        require!(lottery.drawn, UnforgivenV2Error::LotteryNotDrawn);
+        require!(!entry.ranked, UnforgivenV2Error::WaitlistEntryRanked);
+
+        let rank = LotteryRank {
+            zk_proof_hash: entry.zk_proof_hash,
+            score: lottery_entry_score(&lottery.randomness, &entry.zk_proof_hash, entry.weight),
+        };
+        let capacity = lottery.winner_slots as usize;
+        let board_len = lottery.board_len;
+        lottery.board_len = insert_lottery_rank(&mut lottery.board[..capacity], board_len, rank);
+        lottery.ranked_count = lottery
+            .ranked_count
+            .checked_add(1)
+            .ok_or(error!(UnforgivenV2Error::InvalidLotteryConfig))?;
+        entry.ranked = true;
+
+        emit!(WaitlistEntryRankedEvent {
+            lottery: lottery_key,
+            zk_proof_hash: rank.zk_proof_hash,
+            score: rank.score,
+            ranked_count: lottery.ranked_count,
+        });
         Ok(())
     }
 
-    pub fn fill_ticket_listing(ctx: Context<FillTicketListing>) -> Result<()> {
+    pub fn claim_lottery_ticket(
+        ctx: Context<ClaimLotteryTicket>,
+        seed_payload: ShieldPayloadV0,
+        oracle_signature: [u8; 64],
+        sig_instruction_index: u16,
+        bumps: ShieldAccountBumps,
+    ) -> Result<()> {
         require!(
-            ctx.accounts.ticket_receipt.listed,
-            UnforgivenV2Error::TicketNotListed
+            ctx.accounts.shield.sale_config.allocation_mode == ALLOCATION_MODE_LOTTERY,
+            UnforgivenV2Error::LotteryNotEnabled
+        );
+        let lottery = &mut ctx.accounts.lottery;
+        let entry = &mut ctx.accounts.waitlist_entry;
+        require!(lottery.drawn, UnforgivenV2Error::LotteryNotDrawn);
+        require_keys_eq!(
+            entry.user,
+            ctx.accounts.shield.user.key(),
+            UnforgivenV2Error::UserPubkeyMismatch
         );
+        require!(!entry.claimed, UnforgivenV2Error::WaitlistEntryClaimed);
         require!(
-            ctx.accounts.listing_escrow_token.amount == TICKET_AMOUNT,
-            UnforgivenV2Error::InvalidTicketAmount
+            seed_payload.zk_proof_hash == entry.zk_proof_hash,
+            UnforgivenV2Error::InvalidExecuteShieldAccount
         );
-        require_keys_neq!(
-            ctx.accounts.buyer.key(),
-            ctx.accounts.seller.key(),
-            UnforgivenV2Error::SelfTradeForbidden
+        require!(
+            lottery.ranked_count == lottery.entry_count,
+            UnforgivenV2Error::LotteryRankingIncomplete
         );
-        require_keys_eq!(
-            ctx.accounts.fee_recipient.key(),
-            ctx.accounts.global_config_v2.authority,
-            UnforgivenV2Error::TreasuryMismatch
+        require!(
+            lottery_is_winner(lottery, &entry.zk_proof_hash),
+            UnforgivenV2Error::LotteryEntryNotWinner
+        );
+        require!(
+            lottery.claimed < lottery.winner_slots,
+            UnforgivenV2Error::LotteryWinnerSlotsFilled
         );
+        entry.claimed = true;
+        lottery.claimed += 1;
 
-        let clock = Clock::get()?;
-        let sale_price = ctx.accounts.listing.ask_price;
-        let protocol_fee = compute_resale_fee(sale_price)?;
-        let seller_proceeds = sale_price
-            .checked_sub(protocol_fee)
-            .ok_or(error!(UnforgivenV2Error::InvalidListingPrice))?;
+        process_shield_purchase(
+            &mut ctx.accounts.shield,
+            ctx.program_id,
+            &seed_payload,
+            &oracle_signature,
+            sig_instruction_index,
+            bumps,
+            false,
+        )
+    }
+
+    pub fn commit_purchase(ctx: Context<CommitPurchase>, commitment: [u8; 32]) -> Result<()> {
+        let sale = &ctx.accounts.sale_config;
+        require!(
+            sale.commit_deposit_lamports > 0,
+            UnforgivenV2Error::CommitRevealDisabled
+        );
+        let slot = Clock::get()?.slot;
+        let reveal_after_slot = slot
+            .checked_add(sale.commit_min_delay_slots)
+            .ok_or(error!(UnforgivenV2Error::InvalidCommitRevealParams))?;
+        let reveal_deadline_slot = reveal_after_slot
+            .checked_add(sale.commit_reveal_window_slots)
+            .ok_or(error!(UnforgivenV2Error::InvalidCommitRevealParams))?;
 
         transfer_lamports(
-            &ctx.accounts.buyer.to_account_info(),
-            &ctx.accounts.seller.to_account_info(),
-            seller_proceeds,
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.purchase_commitment.to_account_info(),
+            sale.commit_deposit_lamports,
+        )?;
+
+        let pending = &mut ctx.accounts.purchase_commitment;
+        pending.user = ctx.accounts.user.key();
+        pending.event_key = sale.event_key;
+        pending.commitment = commitment;
+        pending.deposit_lamports = sale.commit_deposit_lamports;
+        pending.reveal_after_slot = reveal_after_slot;
+        pending.reveal_deadline_slot = reveal_deadline_slot;
+        pending.forfeit_bps = sale.commit_forfeit_bps;
+        pending.bump = ctx.bumps.purchase_commitment;
+
+        emit!(PurchaseCommittedEvent {
+            user: pending.user,
+            event_key: pending.event_key,
+            commitment,
+            deposit_lamports: pending.deposit_lamports,
+            reveal_after_slot,
+            reveal_deadline_slot,
+        });
+        Ok(())
+    }
+
+    pub fn reveal_purchase(
+        ctx: Context<RevealPurchase>,
+        seed_payload: ShieldPayloadV0,
+        oracle_signature: [u8; 64],
+        sig_instruction_index: u16,
+        bumps: ShieldAccountBumps,
+        salt: [u8; 32],
+    ) -> Result<()> {
+        ensure_open_allocation(&ctx.accounts.shield.sale_config)?;
+        let pending = &ctx.accounts.purchase_commitment;
+        ensure_reveal_window(
+            Clock::get()?.slot,
+            pending.reveal_after_slot,
+            pending.reveal_deadline_slot,
+        )?;
+        require!(
+            purchase_commitment_hash(&seed_payload, &salt) == pending.commitment,
+            UnforgivenV2Error::CommitmentMismatch
+        );
+
+        process_shield_purchase(
+            &mut ctx.accounts.shield,
+            ctx.program_id,
+            &seed_payload,
+            &oracle_signature,
+            sig_instruction_index,
+            bumps,
+            false,
+        )?;
+        ctx.accounts
+            .purchase_commitment
+            .close(ctx.accounts.shield.user.to_account_info())
+    }
+
+    pub fn forfeit_purchase_commitment(ctx: Context<ForfeitPurchaseCommitment>) -> Result<()> {
+        let pending = &ctx.accounts.purchase_commitment;
+        require!(
+            Clock::get()?.slot > pending.reveal_deadline_slot,
+            UnforgivenV2Error::RevealWindowOpen
+        );
+        let (forfeited, refunded) =
+            split_commit_deposit(pending.deposit_lamports, pending.forfeit_bps)?;
+
+        transfer_owned_lamports(
+            &ctx.accounts.purchase_commitment.to_account_info(),
+            &ctx.accounts.treasury.to_account_info(),
+            forfeited,
+        )?;
+
+        emit!(PurchaseCommitmentForfeitedEvent {
+            user: pending.user,
+            event_key: pending.event_key,
+            forfeited_lamports: forfeited,
+            refunded_lamports: refunded,
+        });
+        ctx.accounts
+            .purchase_commitment
+            .close(ctx.accounts.user.to_account_info())
+    }
+
+    // Auctions off one ticket class; the rest of the sale stays on its own allocation mode.
+    pub fn initialize_sealed_bid_auction(
+        ctx: Context<InitializeSealedBidAuction>,
+        commit_end: i64,
+        reveal_end: i64,
+        claim_end: i64,
+        winner_slots: u8,
+        reserve_price: u64,
+    ) -> Result<()> {
+        let class = &mut ctx.accounts.ticket_class;
+        require!(
+            commit_end < reveal_end
+                && reveal_end < claim_end
+                && winner_slots > 0
+                && winner_slots <= MAX_SEALED_BID_WINNERS
+                && class.sold == 0
+                && winner_slots as u32 <= class.supply,
+            UnforgivenV2Error::InvalidSealedBidAuction
+        );
+        class.allocation_mode = ALLOCATION_MODE_SEALED_BID;
+
+        let auction = &mut ctx.accounts.auction;
+        auction.event_key = class.event_key;
+        auction.ticket_class = class.class_id;
+        auction.commit_end = commit_end;
+        auction.claim_end = claim_end;
+        auction.reveal_end = reveal_end;
+        auction.winner_slots = winner_slots;
+        auction.reserve_price = reserve_price;
+        auction.board = [SealedBidRank::default(); SEALED_BID_BOARD_LEN];
+        auction.board_len = 0;
+        auction.bump = ctx.bumps.auction;
+        push_sale_init_audit_entry(
+            &mut ctx.accounts.audit_log,
+            AUDIT_ACTION_SEALED_BID_AUCTION_INITIALIZED,
+            ctx.accounts.authority.key(),
+            &auction.event_key,
+            &(
+                auction.ticket_class,
+                commit_end,
+                reveal_end,
+                claim_end,
+                winner_slots,
+                reserve_price,
+            ),
+        )?;
+
+        emit!(SealedBidAuctionInitializedEvent {
+            auction: auction.key(),
+            event_key: auction.event_key,
+            ticket_class: auction.ticket_class,
+            commit_end,
+            reveal_end,
+            claim_end,
+            winner_slots,
+            reserve_price,
+        });
+        Ok(())
+    }
+
+    pub fn commit_sealed_bid(
+        ctx: Context<CommitSealedBid>,
+        payload: ShieldPayloadV0,
+        oracle_signature: [u8; 64],
+        sig_instruction_index: u16,
+        commitment: [u8; 32],
+        escrow_lamports: u64,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            now < ctx.accounts.auction.commit_end,
+            UnforgivenV2Error::SealedBidPhaseMismatch
+        );
+        require!(
+            escrow_lamports >= ctx.accounts.auction.reserve_price,
+            UnforgivenV2Error::InvalidSealedBidAmount
+        );
+        validate_preview_request_fields(
+            &payload,
+            &ctx.accounts.admin_config,
+            &ctx.accounts.sale_config,
+            &ctx.accounts.bidder.key(),
+            now,
+        )?;
+        let message = serialize_shield_message_v0(
+            &payload,
+            &shield_signing_domain(
+                &ctx.accounts.admin_config,
+                SIGNING_PURPOSE_SEALED_BID,
+                ctx.accounts.auction.ticket_class,
+            ),
+        );
+        verify_ed25519_ix(
+            &ctx.accounts.instructions.to_account_info(),
+            sig_instruction_index,
+            &message,
+            &oracle_signature,
+            &ctx.accounts.admin_config.oracle_pubkey,
+        )?;
+        ensure_membership_tier(
+            &ctx.accounts.sale_config,
+            ctx.accounts
+                .membership
+                .as_deref()
+                .map(|membership| &**membership),
+            now,
+        )?;
+        enforce_user_mode_policy(
+            &ctx.accounts.sale_config,
+            payload.user_mode,
+            payload.dignity_score,
+            MODE_ACTION_BUY,
+        )?;
+        require!(
+            !quote_for_class(
+                &payload,
+                &ctx.accounts.sale_config,
+                Some(&ctx.accounts.ticket_class)
+            )?
+            .blocked,
+            UnforgivenV2Error::ShieldBlocked
+        );
+        // The quota is spent when a winning bid claims its ticket; a proof that is already
+        // out of uses cannot bid.
+        next_proof_quota_use(
+            ctx.accounts.proof_quota.uses,
+            ctx.accounts.sale_config.max_uses_per_proof,
         )?;
+        record_proof_use(
+            &mut ctx.accounts.proof_use,
+            &payload,
+            now,
+            ctx.bumps.proof_use,
+        );
+
         transfer_lamports(
+            &ctx.accounts.bidder.to_account_info(),
+            &ctx.accounts.sealed_bid.to_account_info(),
+            escrow_lamports,
+        )?;
+
+        let bid = &mut ctx.accounts.sealed_bid;
+        bid.auction = ctx.accounts.auction.key();
+        bid.bidder = ctx.accounts.bidder.key();
+        bid.zk_proof_hash = payload.zk_proof_hash;
+        bid.nonce = payload.nonce;
+        bid.user_mode = payload.user_mode;
+        bid.commitment = commitment;
+        bid.escrow_lamports = escrow_lamports;
+        bid.revealed = false;
+        bid.amount = 0;
+        bid.bump = ctx.bumps.sealed_bid;
+
+        emit!(SealedBidCommittedEvent {
+            auction: bid.auction,
+            bidder: bid.bidder,
+            escrow_lamports,
+        });
+        Ok(())
+    }
+
+    pub fn reveal_sealed_bid(
+        ctx: Context<RevealSealedBid>,
+        amount: u64,
+        salt: [u8; 32],
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let auction = &mut ctx.accounts.auction;
+        let bid_key = ctx.accounts.sealed_bid.key();
+        let bid = &mut ctx.accounts.sealed_bid;
+        require!(
+            now >= auction.commit_end && now < auction.reveal_end,
+            UnforgivenV2Error::SealedBidPhaseMismatch
+        );
+        require!(!bid.revealed, UnforgivenV2Error::SealedBidAlreadyRevealed);
+        require!(
+            sealed_bid_commitment(&bid.bidder, amount, &salt) == bid.commitment,
+            UnforgivenV2Error::SealedBidCommitmentMismatch
+        );
+        require!(
+            amount >= auction.reserve_price && amount <= bid.escrow_lamports,
+            UnforgivenV2Error::InvalidSealedBidAmount
+        );
+
+        bid.revealed = true;
+        bid.amount = amount;
+        let capacity = auction.winner_slots as usize + 1;
+        let board_len = auction.board_len;
+        auction.board_len = insert_sealed_bid_rank(
+            &mut auction.board[..capacity],
+            board_len,
+            SealedBidRank {
+                bid: bid_key,
+                amount,
+            },
+        );
+
+        emit!(SealedBidRevealedEvent {
+            auction: bid.auction,
+            bidder: bid.bidder,
+            amount,
+        });
+        Ok(())
+    }
+
+    pub fn claim_sealed_bid_ticket(
+        ctx: Context<ClaimSealedBidTicket>,
+        bumps: ShieldAccountBumps,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let auction = &ctx.accounts.auction;
+        require!(
+            clock.unix_timestamp >= auction.reveal_end && clock.unix_timestamp < auction.claim_end,
+            UnforgivenV2Error::SealedBidPhaseMismatch
+        );
+        let bid_key = ctx.accounts.sealed_bid.key();
+        require!(
+            sealed_bid_is_winner(auction, &bid_key),
+            UnforgivenV2Error::SealedBidNotWinner
+        );
+        // Membership is checked again at claim: it may have lapsed since the commit.
+        ensure_membership_tier(
+            &ctx.accounts.sale_config,
+            ctx.accounts
+                .membership
+                .as_deref()
+                .map(|membership| &**membership),
+            clock.unix_timestamp,
+        )?;
+        let class = &mut ctx.accounts.ticket_class;
+        class.sold = next_ticket_class_sale(class.sold, class.supply)?;
+        let proof_quota = &mut ctx.accounts.proof_quota;
+        proof_quota.uses = next_proof_quota_use(
+            proof_quota.uses,
+            ctx.accounts.sale_config.max_uses_per_proof,
+        )?;
+        let price = sealed_bid_clearing_price(auction);
+        let bid = &ctx.accounts.sealed_bid;
+        let bidder_key = ctx.accounts.bidder.key();
+        let rent: &Rent = &ctx.accounts.rent;
+
+        let ticket_mint_key = ctx.accounts.ticket_mint.key();
+        let ticket_mint_seeds: &[&[u8]] =
+            &[TICKET_MINT_SEED, bid_key.as_ref(), &[bumps.ticket_mint]];
+        let user_ticket_token_seeds: &[&[u8]] = &[
+            TICKET_TOKEN_SEED,
+            ticket_mint_key.as_ref(),
+            bidder_key.as_ref(),
+            &[bumps.user_ticket_token],
+        ];
+        let ticket_receipt_seeds: &[&[u8]] = &[
+            TICKET_RECEIPT_SEED,
+            ticket_mint_key.as_ref(),
+            &[bumps.ticket_receipt],
+        ];
+        require_pda_address(&ticket_mint_key, ticket_mint_seeds, ctx.program_id)?;
+        require_pda_address(
+            &ctx.accounts.user_ticket_token.key(),
+            user_ticket_token_seeds,
+            ctx.program_id,
+        )?;
+        require_pda_address(
+            &ctx.accounts.ticket_receipt.key(),
+            ticket_receipt_seeds,
+            ctx.program_id,
+        )?;
+
+        transfer_owned_lamports(
+            &ctx.accounts.sealed_bid.to_account_info(),
+            &ctx.accounts.treasury.to_account_info(),
+            price,
+        )?;
+        mint_ticket_to_owner(
+            TicketMintAccounts {
+                payer: &ctx.accounts.bidder.to_account_info(),
+                ticket_mint: &ctx.accounts.ticket_mint.to_account_info(),
+                ticket_mint_authority: &ctx.accounts.ticket_mint_authority.to_account_info(),
+                owner_ticket_token: &ctx.accounts.user_ticket_token.to_account_info(),
+                token_program: &ctx.accounts.token_program.to_account_info(),
+                system_program: &ctx.accounts.system_program.to_account_info(),
+            },
+            &bidder_key,
+            ticket_mint_seeds,
+            user_ticket_token_seeds,
+            ctx.bumps.ticket_mint_authority,
+            rent,
+        )?;
+        create_pda_account(
+            &ctx.accounts.bidder.to_account_info(),
+            &ctx.accounts.ticket_receipt.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            ctx.program_id,
+            8 + TicketReceipt::INIT_SPACE,
+            ticket_receipt_seeds,
+            rent,
+        )?;
+        write_ticket_receipt_account(
+            &ctx.accounts.ticket_receipt.to_account_info(),
+            IssuedTicketReceipt {
+                mint: &ticket_mint_key,
+                event_key: &auction.event_key,
+                owner: &bidder_key,
+                price,
+                issued_at: clock.unix_timestamp,
+                nonce: bid.nonce,
+                zk_proof_hash: &bid.zk_proof_hash,
+                holder_user_mode: bid.user_mode,
+                class_id: auction.ticket_class,
+                bump: bumps.ticket_receipt,
+            },
+        )?;
+
+        emit!(SealedBidSettledEvent {
+            auction: bid.auction,
+            bidder: bidder_key,
+            won: true,
+            paid_lamports: price,
+            refunded_lamports: bid.escrow_lamports - price,
+            ticket_class: auction.ticket_class,
+        });
+        emit!(TicketMintedEvent {
+            mint: ticket_mint_key,
+            owner: bidder_key,
+            final_price: price,
+            nonce: bid.nonce,
+        });
+        let reputation = &mut ctx.accounts.reputation;
+        reputation.fair_purchases = reputation.fair_purchases.saturating_add(1);
+        touch_reputation(reputation, clock.unix_timestamp);
+        ctx.accounts
+            .sealed_bid
+            .close(ctx.accounts.bidder.to_account_info())
+    }
+
+    // Reissues a v1 `Ticket` PDA as a v2 ticket mint + receipt. The v1 program cannot be
+    // written from here, so the `V1Migration` marker is what stops a second migration.
+    pub fn migrate_v1_ticket(
+        ctx: Context<MigrateV1Ticket>,
+        v1_nonce: u64,
+        bumps: ShieldAccountBumps,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let buyer_key = ctx.accounts.buyer.key();
+        let (v1_buyer, v1_purchase_time) =
+            parse_v1_ticket(&ctx.accounts.v1_ticket.try_borrow_data()?)?;
+        require_keys_eq!(v1_buyer, buyer_key, UnforgivenV2Error::TicketOwnerMismatch);
+
+        let v1_ticket_key = ctx.accounts.v1_ticket.key();
+        let rent: &Rent = &ctx.accounts.rent;
+        let ticket_mint_key = ctx.accounts.ticket_mint.key();
+        let ticket_mint_seeds: &[&[u8]] = &[
+            TICKET_MINT_SEED,
+            v1_ticket_key.as_ref(),
+            &[bumps.ticket_mint],
+        ];
+        let user_ticket_token_seeds: &[&[u8]] = &[
+            TICKET_TOKEN_SEED,
+            ticket_mint_key.as_ref(),
+            buyer_key.as_ref(),
+            &[bumps.user_ticket_token],
+        ];
+        let ticket_receipt_seeds: &[&[u8]] = &[
+            TICKET_RECEIPT_SEED,
+            ticket_mint_key.as_ref(),
+            &[bumps.ticket_receipt],
+        ];
+        require_pda_address(&ticket_mint_key, ticket_mint_seeds, ctx.program_id)?;
+        require_pda_address(
+            &ctx.accounts.user_ticket_token.key(),
+            user_ticket_token_seeds,
+            ctx.program_id,
+        )?;
+        require_pda_address(
+            &ctx.accounts.ticket_receipt.key(),
+            ticket_receipt_seeds,
+            ctx.program_id,
+        )?;
+
+        mint_ticket_to_owner(
+            TicketMintAccounts {
+                payer: &ctx.accounts.buyer.to_account_info(),
+                ticket_mint: &ctx.accounts.ticket_mint.to_account_info(),
+                ticket_mint_authority: &ctx.accounts.ticket_mint_authority.to_account_info(),
+                owner_ticket_token: &ctx.accounts.user_ticket_token.to_account_info(),
+                token_program: &ctx.accounts.token_program.to_account_info(),
+                system_program: &ctx.accounts.system_program.to_account_info(),
+            },
+            &buyer_key,
+            ticket_mint_seeds,
+            user_ticket_token_seeds,
+            ctx.bumps.ticket_mint_authority,
+            rent,
+        )?;
+        create_pda_account(
             &ctx.accounts.buyer.to_account_info(),
-            &ctx.accounts.fee_recipient.to_account_info(),
-            protocol_fee,
+            &ctx.accounts.ticket_receipt.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            ctx.program_id,
+            8 + TicketReceipt::INIT_SPACE,
+            ticket_receipt_seeds,
+            rent,
+        )?;
+        // v1 never stored the price paid or an attestation, so the receipt starts at zero
+        // price with a guest holder.
+        write_ticket_receipt_account(
+            &ctx.accounts.ticket_receipt.to_account_info(),
+            IssuedTicketReceipt {
+                mint: &ticket_mint_key,
+                event_key: &ctx.accounts.global_config_v2.key(),
+                owner: &buyer_key,
+                price: 0,
+                issued_at: clock.unix_timestamp,
+                nonce: v1_nonce,
+                zk_proof_hash: &[0u8; 32],
+                holder_user_mode: USER_MODE_GUEST,
+                class_id: TICKET_CLASS_NONE,
+                bump: bumps.ticket_receipt,
+            },
+        )?;
+
+        let migration = &mut ctx.accounts.v1_migration;
+        migration.v1_ticket = v1_ticket_key;
+        migration.buyer = buyer_key;
+        migration.mint = ticket_mint_key;
+        migration.v1_purchase_time = v1_purchase_time;
+        migration.migrated_at = clock.unix_timestamp;
+        migration.bump = ctx.bumps.v1_migration;
+
+        emit!(V1TicketMigratedEvent {
+            v1_ticket: v1_ticket_key,
+            buyer: buyer_key,
+            mint: ticket_mint_key,
+            v1_purchase_time,
+        });
+        emit!(TicketMintedEvent {
+            mint: ticket_mint_key,
+            owner: buyer_key,
+            final_price: 0,
+            nonce: v1_nonce,
+        });
+        Ok(())
+    }
+
+    // Losing bids are refunded in full. A winner that let `claim_end` pass forfeits the
+    // clearing price to the treasury and gets the rest of its escrow back.
+    pub fn refund_sealed_bid(ctx: Context<RefundSealedBid>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let auction = &ctx.accounts.auction;
+        require!(
+            now >= auction.reveal_end,
+            UnforgivenV2Error::SealedBidPhaseMismatch
+        );
+        let forfeited = sealed_bid_forfeit(auction, &ctx.accounts.sealed_bid.key(), now)?;
+        transfer_owned_lamports(
+            &ctx.accounts.sealed_bid.to_account_info(),
+            &ctx.accounts.treasury.to_account_info(),
+            forfeited,
+        )?;
+
+        let bid = &ctx.accounts.sealed_bid;
+        emit!(SealedBidSettledEvent {
+            auction: bid.auction,
+            bidder: bid.bidder,
+            won: false,
+            paid_lamports: forfeited,
+            refunded_lamports: bid.escrow_lamports - forfeited,
+            ticket_class: auction.ticket_class,
+        });
+        ctx.accounts
+            .sealed_bid
+            .close(ctx.accounts.bidder.to_account_info())
+    }
+
+    pub fn list_ticket(ctx: Context<ListTicket>, ask_price: u64) -> Result<()> {
+        require!(ask_price > 0, UnforgivenV2Error::InvalidListingPrice);
+        ensure_user_mode_action(
+            &ctx.accounts.sale_config,
+            ctx.accounts.ticket_receipt.holder_user_mode,
+            MODE_ACTION_LIST,
+        )?;
+        require!(
+            !ctx.accounts.ticket_receipt.listed,
+            UnforgivenV2Error::TicketAlreadyListed
+        );
+        require!(
+            ctx.accounts.ticket_receipt.redeemed_at == 0,
+            UnforgivenV2Error::TicketAlreadyRedeemed
+        );
+        require!(
+            ctx.accounts.seller_ticket_token.amount == TICKET_AMOUNT,
+            UnforgivenV2Error::InvalidTicketAmount
+        );
+
+        let clock = Clock::get()?;
+
+        token::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.seller_ticket_token.to_account_info(),
+                    mint: ctx.accounts.ticket_mint.to_account_info(),
+                    to: ctx.accounts.listing_escrow_token.to_account_info(),
+                    authority: ctx.accounts.seller.to_account_info(),
+                },
+            ),
+            TICKET_AMOUNT,
+            TICKET_DECIMALS,
+        )?;
+
+        token::close_account(CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account: ctx.accounts.seller_ticket_token.to_account_info(),
+                destination: ctx.accounts.seller.to_account_info(),
+                authority: ctx.accounts.seller.to_account_info(),
+            },
+        ))?;
+
+        let listing = &mut ctx.accounts.listing;
+        listing.seller = ctx.accounts.seller.key();
+        listing.mint = ctx.accounts.ticket_mint.key();
+        listing.ask_price = ask_price;
+        listing.created_at = clock.unix_timestamp;
+        listing.bump = ctx.bumps.listing;
+
+        ctx.accounts.ticket_receipt.listed = true;
+        load_or_create_reputation(
+            &ctx.accounts.seller.to_account_info(),
+            &ctx.accounts.seller_reputation.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            ctx.program_id,
+            &ctx.accounts.seller.key(),
+            &ctx.accounts.rent,
         )?;
 
+        emit!(TicketListedEvent {
+            mint: ctx.accounts.ticket_mint.key(),
+            seller: ctx.accounts.seller.key(),
+            ask_price,
+        });
+
+        Ok(())
+    }
+
+    pub fn cancel_ticket_listing(ctx: Context<CancelTicketListing>) -> Result<()> {
+        require!(
+            ctx.accounts.ticket_receipt.listed,
+            UnforgivenV2Error::TicketNotListed
+        );
+        require!(
+            ctx.accounts.listing_escrow_token.amount == TICKET_AMOUNT,
+            UnforgivenV2Error::InvalidTicketAmount
+        );
+
         let ticket_mint_key = ctx.accounts.ticket_mint.key();
         let listing_bump = [ctx.accounts.listing.bump];
         let listing_seeds: &[&[u8]] =
@@ -726,7 +2921,7 @@ pub mod unforgiven_v2 {
                 TransferChecked {
                     from: ctx.accounts.listing_escrow_token.to_account_info(),
                     mint: ctx.accounts.ticket_mint.to_account_info(),
-                    to: ctx.accounts.buyer_ticket_token.to_account_info(),
+                    to: ctx.accounts.seller_ticket_token.to_account_info(),
                     authority: ctx.accounts.listing.to_account_info(),
                 },
                 &[listing_seeds],
@@ -745,16 +2940,140 @@ pub mod unforgiven_v2 {
             &[listing_seeds],
         ))?;
 
-        let receipt = &mut ctx.accounts.ticket_receipt;
-        receipt.current_holder = ctx.accounts.buyer.key();
-        receipt.last_sale_price = sale_price;
-        receipt.last_transfer_at = clock.unix_timestamp;
-        receipt.listed = false;
-        receipt.resale_count = receipt
+        ctx.accounts.ticket_receipt.listed = false;
+
+        emit!(TicketListingCanceledEvent {
+            mint: ticket_mint_key,
+            seller: ctx.accounts.seller.key(),
+        });
+
+        Ok(())
+    }
+
+    pub fn fill_ticket_listing(
+        ctx: Context<FillTicketListing>,
+        buyer_payload: ShieldPayloadV0,
+        oracle_signature: [u8; 64],
+        sig_instruction_index: u16,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let message = serialize_shield_message_v0(
+            &buyer_payload,
+            &shield_signing_domain(
+                &ctx.accounts.admin_config,
+                SIGNING_PURPOSE_BUY_RESALE,
+                TICKET_CLASS_NONE,
+            ),
+        );
+        validate_preview_request_fields(
+            &buyer_payload,
+            &ctx.accounts.admin_config,
+            &ctx.accounts.sale_config,
+            &ctx.accounts.buyer.key(),
+            clock.unix_timestamp,
+        )?;
+        verify_ed25519_ix(
+            &ctx.accounts.instructions.to_account_info(),
+            sig_instruction_index,
+            &message,
+            &oracle_signature,
+            &ctx.accounts.admin_config.oracle_pubkey,
+        )?;
+        enforce_user_mode_policy(
+            &ctx.accounts.sale_config,
+            buyer_payload.user_mode,
+            buyer_payload.dignity_score,
+            MODE_ACTION_BUY_RESALE,
+        )?;
+
+        require!(
+            ctx.accounts.ticket_receipt.listed,
+            UnforgivenV2Error::TicketNotListed
+        );
+        require!(
+            ctx.accounts.listing_escrow_token.amount == TICKET_AMOUNT,
+            UnforgivenV2Error::InvalidTicketAmount
+        );
+        require_keys_neq!(
+            ctx.accounts.buyer.key(),
+            ctx.accounts.seller.key(),
+            UnforgivenV2Error::SelfTradeForbidden
+        );
+        require_keys_eq!(
+            ctx.accounts.fee_recipient.key(),
+            ctx.accounts.global_config_v2.authority,
+            UnforgivenV2Error::TreasuryMismatch
+        );
+
+        let sale_price = ctx.accounts.listing.ask_price;
+        let protocol_fee = compute_resale_fee(sale_price)?;
+        let seller_proceeds = sale_price
+            .checked_sub(protocol_fee)
+            .ok_or(error!(UnforgivenV2Error::InvalidListingPrice))?;
+
+        transfer_lamports(
+            &ctx.accounts.buyer.to_account_info(),
+            &ctx.accounts.seller.to_account_info(),
+            seller_proceeds,
+        )?;
+        transfer_lamports(
+            &ctx.accounts.buyer.to_account_info(),
+            &ctx.accounts.fee_recipient.to_account_info(),
+            protocol_fee,
+        )?;
+
+        let ticket_mint_key = ctx.accounts.ticket_mint.key();
+        let listing_bump = [ctx.accounts.listing.bump];
+        let listing_seeds: &[&[u8]] =
+            &[TICKET_LISTING_SEED, ticket_mint_key.as_ref(), &listing_bump];
+
+        token::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.listing_escrow_token.to_account_info(),
+                    mint: ctx.accounts.ticket_mint.to_account_info(),
+                    to: ctx.accounts.buyer_ticket_token.to_account_info(),
+                    authority: ctx.accounts.listing.to_account_info(),
+                },
+                &[listing_seeds],
+            ),
+            TICKET_AMOUNT,
+            TICKET_DECIMALS,
+        )?;
+
+        token::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account: ctx.accounts.listing_escrow_token.to_account_info(),
+                destination: ctx.accounts.seller.to_account_info(),
+                authority: ctx.accounts.listing.to_account_info(),
+            },
+            &[listing_seeds],
+        ))?;
+
+        let receipt = &mut ctx.accounts.ticket_receipt;
+        receipt.current_holder = ctx.accounts.buyer.key();
+        receipt.holder_user_mode = buyer_payload.user_mode;
+        receipt.last_sale_price = sale_price;
+        receipt.last_transfer_at = clock.unix_timestamp;
+        receipt.listed = false;
+        receipt.reset_entry_secret();
+        receipt.resale_count = receipt
             .resale_count
             .checked_add(1)
             .ok_or(error!(UnforgivenV2Error::InvalidListingPrice))?;
 
+        let seller_reputation = &mut ctx.accounts.seller_reputation;
+        seller_reputation.flips = seller_reputation.flips.saturating_add(1);
+        touch_reputation(seller_reputation, clock.unix_timestamp);
+        record_proof_use(
+            &mut ctx.accounts.buyer_proof_use,
+            &buyer_payload,
+            clock.unix_timestamp,
+            ctx.bumps.buyer_proof_use,
+        );
+
         emit!(TicketSaleEvent {
             mint: ticket_mint_key,
             seller: ctx.accounts.seller.key(),
@@ -766,713 +3085,4618 @@ pub mod unforgiven_v2 {
 
         Ok(())
     }
-}
-
-fn compute_resale_fee(sale_price: u64) -> Result<u64> {
-    sale_price
-        .checked_mul(RESALE_FEE_BPS)
-        .and_then(|value| value.checked_div(10_000))
-        .ok_or(error!(UnforgivenV2Error::InvalidListingPrice))
-}
-
-fn transfer_lamports<'info>(
-    from: &AccountInfo<'info>,
-    to: &AccountInfo<'info>,
-    amount: u64,
-) -> Result<()> {
-    if amount == 0 {
-        return Ok(());
-    }
 
-    let ix = system_instruction::transfer(&from.key(), &to.key(), amount);
-    invoke(&ix, &[from.clone(), to.clone()])?;
-    Ok(())
-}
+    pub fn transfer_ticket(
+        ctx: Context<TransferTicket>,
+        recipient_shield: Option<RecipientShield>,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let sale_config = &ctx.accounts.sale_config;
+        let recipient = ctx.accounts.recipient.key();
 
-fn create_pda_account<'info>(
-    payer: &AccountInfo<'info>,
-    new_account: &AccountInfo<'info>,
-    system_program_info: &AccountInfo<'info>,
-    owner: &Pubkey,
-    space: usize,
-    signer_seeds: &[&[u8]],
-) -> Result<()> {
-    require!(
-        new_account.owner == &anchor_lang::system_program::ID,
-        UnforgivenV2Error::InvalidExecuteShieldAccount
-    );
-    require!(
-        new_account.lamports() == 0 && new_account.data_is_empty(),
-        UnforgivenV2Error::InvalidExecuteShieldAccount
-    );
+        require!(
+            !ctx.accounts.ticket_receipt.listed,
+            UnforgivenV2Error::TicketAlreadyListed
+        );
+        require!(
+            ctx.accounts.ticket_receipt.redeemed_at == 0,
+            UnforgivenV2Error::TicketAlreadyRedeemed
+        );
+        require!(
+            ctx.accounts.holder_ticket_token.amount == TICKET_AMOUNT,
+            UnforgivenV2Error::InvalidTicketAmount
+        );
+        require_keys_neq!(
+            recipient,
+            ctx.accounts.holder.key(),
+            UnforgivenV2Error::SelfTradeForbidden
+        );
+        let transfer_count = next_ticket_transfer_count(
+            ctx.accounts.ticket_receipt.transfer_count,
+            sale_config.max_transfers_per_ticket,
+        )?;
 
-    let rent = Rent::get()?;
-    let lamports = rent.minimum_balance(space);
-    let ix = system_instruction::create_account(
-        payer.key,
-        new_account.key,
-        lamports,
-        space as u64,
-        owner,
-    );
-    invoke_signed(
-        &ix,
-        &[payer.clone(), new_account.clone(), system_program_info.clone()],
-        &[signer_seeds],
-    )?;
-    Ok(())
-}
+        // An unattested recipient is recorded as a guest, and a transfer never launders the
+        // sender's mode: the ticket keeps the worse of the two.
+        let recipient_user_mode = match &recipient_shield {
+            Some(shield) => {
+                let message = serialize_shield_message_v0(
+                    &shield.payload,
+                    &shield_signing_domain(
+                        &ctx.accounts.admin_config,
+                        SIGNING_PURPOSE_TRANSFER,
+                        TICKET_CLASS_NONE,
+                    ),
+                );
+                validate_preview_request_fields(
+                    &shield.payload,
+                    &ctx.accounts.admin_config,
+                    sale_config,
+                    &recipient,
+                    clock.unix_timestamp,
+                )?;
+                verify_ed25519_ix(
+                    &ctx.accounts.instructions.to_account_info(),
+                    shield.sig_instruction_index,
+                    &message,
+                    &shield.oracle_signature,
+                    &ctx.accounts.admin_config.oracle_pubkey,
+                )?;
+                enforce_user_mode_policy(
+                    sale_config,
+                    shield.payload.user_mode,
+                    shield.payload.dignity_score,
+                    MODE_ACTION_BUY_RESALE,
+                )?;
+                let proof_use = ctx
+                    .accounts
+                    .recipient_proof_use
+                    .as_ref()
+                    .ok_or(error!(UnforgivenV2Error::InvalidExecuteShieldAccount))?;
+                consume_payload_nonce(
+                    &ctx.accounts.holder.to_account_info(),
+                    &proof_use.to_account_info(),
+                    &ctx.accounts.system_program.to_account_info(),
+                    ctx.program_id,
+                    &shield.payload,
+                    clock.unix_timestamp,
+                    &ctx.accounts.rent,
+                )?;
+                shield.payload.user_mode
+            }
+            None => {
+                require!(
+                    !sale_config.transfer_requires_shield,
+                    UnforgivenV2Error::RecipientShieldRequired
+                );
+                USER_MODE_GUEST
+            }
+        };
+        let holder_user_mode = transferred_user_mode(
+            ctx.accounts.ticket_receipt.holder_user_mode,
+            recipient_user_mode,
+        );
 
-fn initialize_ticket_mint<'info>(
-    token_program_info: &AccountInfo<'info>,
-    mint_info: &AccountInfo<'info>,
-    mint_authority: &Pubkey,
-) -> Result<()> {
-    let ix = spl_token::instruction::initialize_mint2(
-        &spl_token::id(),
-        mint_info.key,
-        mint_authority,
-        Some(mint_authority),
-        TICKET_DECIMALS,
-    )
-    .map_err(|_| error!(UnforgivenV2Error::InvalidExecuteShieldAccount))?;
-    invoke(&ix, &[mint_info.clone(), token_program_info.clone()])?;
-    Ok(())
-}
+        token::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.holder_ticket_token.to_account_info(),
+                    mint: ctx.accounts.ticket_mint.to_account_info(),
+                    to: ctx.accounts.recipient_ticket_token.to_account_info(),
+                    authority: ctx.accounts.holder.to_account_info(),
+                },
+            ),
+            TICKET_AMOUNT,
+            TICKET_DECIMALS,
+        )?;
 
-fn initialize_ticket_token_account<'info>(
-    token_program_info: &AccountInfo<'info>,
-    token_account_info: &AccountInfo<'info>,
-    mint_info: &AccountInfo<'info>,
-    owner: &Pubkey,
-) -> Result<()> {
-    let ix = spl_token::instruction::initialize_account3(
-        &spl_token::id(),
-        token_account_info.key,
-        mint_info.key,
-        owner,
-    )
-    .map_err(|_| error!(UnforgivenV2Error::InvalidExecuteShieldAccount))?;
-    invoke(
-        &ix,
-        &[
-            token_account_info.clone(),
-            mint_info.clone(),
-            token_program_info.clone(),
-        ],
-    )?;
-    Ok(())
-}
+        token::close_account(CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account: ctx.accounts.holder_ticket_token.to_account_info(),
+                destination: ctx.accounts.holder.to_account_info(),
+                authority: ctx.accounts.holder.to_account_info(),
+            },
+        ))?;
 
-fn write_proof_use_account<'info>(
-    account: &AccountInfo<'info>,
-    payload: &ShieldPayloadV0,
-    used_at: i64,
-    bump: u8,
-) -> Result<()> {
-    let mut data = account.try_borrow_mut_data()?;
-    require!(
-        data.len() >= 8 + ProofUse::INIT_SPACE,
-        UnforgivenV2Error::InvalidExecuteShieldAccount
-    );
-    data[..8].copy_from_slice(&ProofUse::DISCRIMINATOR);
-    data[8..40].copy_from_slice(&payload.user_pubkey);
-    data[40..72].copy_from_slice(&payload.zk_proof_hash);
-    data[72..80].copy_from_slice(&payload.nonce.to_le_bytes());
-    data[80..88].copy_from_slice(&used_at.to_le_bytes());
-    data[88] = bump;
-    Ok(())
-}
+        let receipt = &mut ctx.accounts.ticket_receipt;
+        receipt.current_holder = recipient;
+        receipt.holder_user_mode = holder_user_mode;
+        receipt.last_transfer_at = clock.unix_timestamp;
+        receipt.transfer_count = transfer_count;
+        receipt.reset_entry_secret();
 
-fn write_ticket_receipt_account<'info>(
-    account: &AccountInfo<'info>,
-    mint: &Pubkey,
-    event_key: &Pubkey,
-    owner: &Pubkey,
-    final_price: u64,
-    now: i64,
-    payload: &ShieldPayloadV0,
-    bump: u8,
-) -> Result<()> {
-    let mut data = account.try_borrow_mut_data()?;
-    require!(
-        data.len() >= 8 + TicketReceipt::INIT_SPACE,
-        UnforgivenV2Error::InvalidExecuteShieldAccount
-    );
-    data[..8].copy_from_slice(&TicketReceipt::DISCRIMINATOR);
-    data[8..40].copy_from_slice(mint.as_ref());
-    data[40..72].copy_from_slice(event_key.as_ref());
-    data[72..104].copy_from_slice(owner.as_ref());
-    data[104..136].copy_from_slice(owner.as_ref());
-    data[136..144].copy_from_slice(&final_price.to_le_bytes());
-    data[144..152].copy_from_slice(&final_price.to_le_bytes());
-    data[152..160].copy_from_slice(&now.to_le_bytes());
-    data[160..168].copy_from_slice(&now.to_le_bytes());
-    data[168..176].copy_from_slice(&payload.nonce.to_le_bytes());
-    data[176..208].copy_from_slice(&payload.zk_proof_hash);
-    data[208] = 0;
-    data[209..217].copy_from_slice(&0u64.to_le_bytes());
-    data[217] = bump;
-    Ok(())
-}
+        emit!(TicketTransferredEvent {
+            mint: ctx.accounts.ticket_mint.key(),
+            from: ctx.accounts.holder.key(),
+            to: recipient,
+            holder_user_mode,
+            transfer_count,
+            shielded: recipient_shield.is_some(),
+        });
 
-pub fn serialize_shield_payload_v0(payload: &ShieldPayloadV0) -> [u8; SHIELD_PAYLOAD_V0_LEN] {
-    let mut out = [0u8; SHIELD_PAYLOAD_V0_LEN];
+        Ok(())
+    }
 
-    out[0] = payload.policy_version;
-    out[1..33].copy_from_slice(&payload.user_pubkey);
-    out[33..41].copy_from_slice(&payload.initial_price.to_le_bytes());
-    out[41..49].copy_from_slice(&payload.sales_velocity_bps.to_le_bytes());
-    out[49..57].copy_from_slice(&payload.time_elapsed.to_le_bytes());
-    out[57] = payload.dignity_score;
-    out[58] = payload.adapter_mask;
-    out[59] = payload.user_mode;
-    out[60] = payload.zk_provider;
-    out[61..93].copy_from_slice(&payload.zk_proof_hash);
-    out[93..125].copy_from_slice(&payload.scoring_model_hash);
-    out[125..133].copy_from_slice(&payload.attestation_expiry.to_le_bytes());
-    out[133..141].copy_from_slice(&payload.nonce.to_le_bytes());
+    pub fn rotate_entry_secret(
+        ctx: Context<RotateEntrySecret>,
+        entry_secret_commitment: [u8; 32],
+    ) -> Result<()> {
+        require!(
+            entry_secret_commitment != [0u8; 32],
+            UnforgivenV2Error::InvalidEntrySecretCommitment
+        );
+        require!(
+            !ctx.accounts.ticket_receipt.listed,
+            UnforgivenV2Error::TicketAlreadyListed
+        );
 
-    out
+        let clock = Clock::get()?;
+        let receipt = &mut ctx.accounts.ticket_receipt;
+        receipt.entry_secret_commitment = entry_secret_commitment;
+        receipt.entry_secret_rotated_at = clock.unix_timestamp;
+
+        emit!(EntrySecretRotatedEvent {
+            mint: receipt.mint,
+            holder: receipt.current_holder,
+            entry_secret_commitment,
+            rotated_at: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    // Gate scan: the sale's scanner checks the holder's rotating entry secret and marks
+    // the ticket used.
+    pub fn redeem_ticket(
+        ctx: Context<RedeemTicket>,
+        entry_secret: [u8; 32],
+        max_age_secs: i64,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        require!(
+            verify_entry_secret(
+                &ctx.accounts.ticket_receipt,
+                &entry_secret,
+                clock.unix_timestamp,
+                max_age_secs,
+            ),
+            UnforgivenV2Error::InvalidEntrySecret
+        );
+
+        let receipt = &mut ctx.accounts.ticket_receipt;
+        receipt.redeemed_at = clock.unix_timestamp;
+        receipt.reset_entry_secret();
+
+        let holder_reputation_info = ctx.accounts.holder_reputation.to_account_info();
+        let mut holder_reputation = load_or_create_reputation(
+            &ctx.accounts.scanner.to_account_info(),
+            &holder_reputation_info,
+            &ctx.accounts.system_program.to_account_info(),
+            ctx.program_id,
+            &receipt.current_holder,
+            &Rent::get()?,
+        )?;
+        holder_reputation.redeemed_tickets = holder_reputation.redeemed_tickets.saturating_add(1);
+        touch_reputation(&mut holder_reputation, clock.unix_timestamp);
+        store_reputation(&holder_reputation_info, &holder_reputation)?;
+
+        emit!(TicketRedeemedEvent {
+            mint: receipt.mint,
+            holder: receipt.current_holder,
+            redeemed_at: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    pub fn initialize_reputation(ctx: Context<InitializeReputation>) -> Result<()> {
+        let reputation = &mut ctx.accounts.reputation;
+        reputation.wallet = ctx.accounts.wallet.key();
+        reputation.bump = ctx.bumps.reputation;
+        Ok(())
+    }
+
+    // Anyone may report an unexpired, oracle-signed execute payload that quotes as
+    // blocked for the sale (and class, if one is passed). The payload's ProofUse is
+    // created here, so it counts once and can no longer be executed.
+    pub fn record_blocked_attempt(
+        ctx: Context<RecordBlockedAttempt>,
+        payload: ShieldPayloadV0,
+        oracle_signature: [u8; 64],
+        sig_instruction_index: u16,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        validate_attestation_fields(
+            &payload,
+            &ctx.accounts.admin_config,
+            &ctx.accounts.reputation.wallet,
+            clock.unix_timestamp,
+        )?;
+        let ticket_class = ctx.accounts.ticket_class.as_deref().map(|class| &**class);
+        let message = serialize_shield_message_v0(
+            &payload,
+            &shield_signing_domain(
+                &ctx.accounts.admin_config,
+                SIGNING_PURPOSE_EXECUTE,
+                ticket_class_id(ticket_class),
+            ),
+        );
+        verify_ed25519_ix(
+            &ctx.accounts.instructions.to_account_info(),
+            sig_instruction_index,
+            &message,
+            &oracle_signature,
+            &ctx.accounts.admin_config.oracle_pubkey,
+        )?;
+        require!(
+            shield_purchase_quote(&payload, &ctx.accounts.sale_config, ticket_class)?.blocked,
+            UnforgivenV2Error::BlockedAttemptNotBlocked
+        );
+
+        record_proof_use(
+            &mut ctx.accounts.proof_use,
+            &payload,
+            clock.unix_timestamp,
+            ctx.bumps.proof_use,
+        );
+        note_blocked_attempt(&mut ctx.accounts.reputation, &payload, clock.unix_timestamp);
+        Ok(())
+    }
+
+    pub fn start_session(
+        ctx: Context<StartHideSisSession>,
+        session_id: u64,
+        payload: ShieldPayloadV0,
+        oracle_signature: [u8; 64],
+        sig_instruction_index: u16,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let message = serialize_shield_message_v0(
+            &payload,
+            &shield_signing_domain(
+                &ctx.accounts.admin_config,
+                SIGNING_PURPOSE_HIDE_SIS,
+                TICKET_CLASS_NONE,
+            ),
+        );
+        validate_attestation_fields(
+            &payload,
+            &ctx.accounts.admin_config,
+            &ctx.accounts.player.key(),
+            clock.unix_timestamp,
+        )?;
+        verify_ed25519_ix(
+            &ctx.accounts.instructions.to_account_info(),
+            sig_instruction_index,
+            &message,
+            &oracle_signature,
+            &ctx.accounts.admin_config.oracle_pubkey,
+        )?;
+        record_proof_use(
+            &mut ctx.accounts.proof_use,
+            &payload,
+            clock.unix_timestamp,
+            ctx.bumps.proof_use,
+        );
+
+        let session = &mut ctx.accounts.session;
+        session.player = ctx.accounts.player.key();
+        session.session_id = session_id;
+        session.user_mode = payload.user_mode;
+        session.dignity_score = payload.dignity_score;
+        session.relation_score = 0;
+        session.pollution_score = 0;
+        session.pollution_flag = false;
+        session.c2_n3_passed = false;
+        session.truth_unlocked = false;
+        session.current_node = C1_N1_OPENING_PROBE;
+        session.chapter_budget_bps = [CHAPTER_BUDGET_BPS; CHAPTER_COUNT];
+        session.final_decision_code = DECISION_NONE;
+        session.ending_code = ENDING_NONE;
+        session.humanity_score = 0;
+        session.finalized = false;
+        session.started_at = clock.unix_timestamp;
+        session.updated_at = clock.unix_timestamp;
+        session.bump = ctx.bumps.session;
+        Ok(())
+    }
+
+    pub fn commit_turn(ctx: Context<HideSisSessionTurn>, node_id: u8, choice_id: u8) -> Result<()> {
+        let session = &mut ctx.accounts.session;
+        let event = apply_interrogation_turn(session, node_id, choice_id)?;
+        session.updated_at = Clock::get()?.unix_timestamp;
+        emit!(event);
+        Ok(())
+    }
+
+    pub fn commit_chapter_decision(
+        ctx: Context<HideSisSessionTurn>,
+        node_id: u8,
+        decision_code: u8,
+    ) -> Result<()> {
+        let session = &mut ctx.accounts.session;
+        let event = apply_chapter_decision(session, node_id, decision_code)?;
+        session.updated_at = Clock::get()?.unix_timestamp;
+        emit!(event);
+        Ok(())
+    }
+
+    pub fn finalize_session(ctx: Context<HideSisSessionTurn>) -> Result<()> {
+        let session = &mut ctx.accounts.session;
+        let event = finalize_hide_sis_session(session)?;
+        session.updated_at = Clock::get()?.unix_timestamp;
+        emit!(event);
+        Ok(())
+    }
+
+    pub fn claim_ending_badge(
+        ctx: Context<ClaimEndingBadge>,
+        payload: ShieldPayloadV0,
+        oracle_signature: [u8; 64],
+        sig_instruction_index: u16,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let message = serialize_shield_message_v0(
+            &payload,
+            &shield_signing_domain(
+                &ctx.accounts.admin_config,
+                SIGNING_PURPOSE_ENDING_BADGE,
+                TICKET_CLASS_NONE,
+            ),
+        );
+        validate_attestation_fields(
+            &payload,
+            &ctx.accounts.admin_config,
+            &ctx.accounts.player.key(),
+            clock.unix_timestamp,
+        )?;
+        verify_ed25519_ix(
+            &ctx.accounts.instructions.to_account_info(),
+            sig_instruction_index,
+            &message,
+            &oracle_signature,
+            &ctx.accounts.admin_config.oracle_pubkey,
+        )?;
+        require!(
+            payload.user_mode == USER_MODE_VERIFIED,
+            UnforgivenV2Error::EndingBadgeRequiresVerified
+        );
+        let session = &ctx.accounts.session;
+        require!(
+            session.finalized,
+            UnforgivenV2Error::HideSisSessionNotFinalized
+        );
+
+        let mint_authority_bump = [ctx.bumps.ticket_mint_authority];
+        let mint_authority_seeds: &[&[u8]] = &[TICKET_MINT_AUTHORITY_SEED, &mint_authority_bump];
+
+        token::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.badge_mint.to_account_info(),
+                    to: ctx.accounts.badge_token.to_account_info(),
+                    authority: ctx.accounts.ticket_mint_authority.to_account_info(),
+                },
+                &[mint_authority_seeds],
+            ),
+            TICKET_AMOUNT,
+        )?;
+        // A frozen token account is what makes the badge soulbound.
+        token::freeze_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            FreezeAccount {
+                account: ctx.accounts.badge_token.to_account_info(),
+                mint: ctx.accounts.badge_mint.to_account_info(),
+                authority: ctx.accounts.ticket_mint_authority.to_account_info(),
+            },
+            &[mint_authority_seeds],
+        ))?;
+        token::set_authority(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                SetAuthority {
+                    current_authority: ctx.accounts.ticket_mint_authority.to_account_info(),
+                    account_or_mint: ctx.accounts.badge_mint.to_account_info(),
+                },
+                &[mint_authority_seeds],
+            ),
+            AuthorityType::MintTokens,
+            None,
+        )?;
+
+        let badge = &mut ctx.accounts.badge;
+        badge.player = session.player;
+        badge.mint = ctx.accounts.badge_mint.key();
+        badge.ending_code = session.ending_code;
+        badge.session_id = session.session_id;
+        badge.humanity_score = session.humanity_score;
+        badge.truth_unlocked = session.truth_unlocked;
+        badge.issued_at = clock.unix_timestamp;
+        badge.bump = ctx.bumps.badge;
+
+        emit!(EndingBadgeIssuedEvent {
+            player: badge.player,
+            mint: badge.mint,
+            ending_code: badge.ending_code,
+            session_id: badge.session_id,
+            humanity_score: badge.humanity_score,
+            truth_unlocked: badge.truth_unlocked,
+        });
+        Ok(())
+    }
+
+    pub fn initialize_membership_config(
+        ctx: Context<InitializeMembershipConfig>,
+        tier_prices: [u64; MEMBERSHIP_TIER_COUNT],
+        period_secs: i64,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.membership_config;
+        config.bump = ctx.bumps.membership_config;
+        apply_membership_config(
+            config,
+            &mut ctx.accounts.audit_log,
+            AUDIT_ACTION_MEMBERSHIP_CONFIG_INITIALIZED,
+            ctx.accounts.authority.key(),
+            tier_prices,
+            period_secs,
+        )
+    }
+
+    pub fn update_membership_config(
+        ctx: Context<UpdateMembershipConfig>,
+        tier_prices: [u64; MEMBERSHIP_TIER_COUNT],
+        period_secs: i64,
+    ) -> Result<()> {
+        apply_membership_config(
+            &mut ctx.accounts.membership_config,
+            &mut ctx.accounts.audit_log,
+            AUDIT_ACTION_MEMBERSHIP_CONFIG_UPDATED,
+            ctx.accounts.authority.key(),
+            tier_prices,
+            period_secs,
+        )
+    }
+
+    pub fn purchase_membership(
+        ctx: Context<PurchaseMembership>,
+        tier: u8,
+        payload: ShieldPayloadV0,
+        oracle_signature: [u8; 64],
+        sig_instruction_index: u16,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let charge_base =
+            membership_charge_base(&ctx.accounts.membership_config, MEMBERSHIP_TIER_NONE, tier)?;
+        validate_attestation_fields(
+            &payload,
+            &ctx.accounts.admin_config,
+            &ctx.accounts.wallet.key(),
+            now,
+        )?;
+        let price_paid = verify_membership_quote(
+            &payload,
+            &oracle_signature,
+            sig_instruction_index,
+            &ctx.accounts.admin_config,
+            &ctx.accounts.instructions.to_account_info(),
+            charge_base,
+        )?;
+        record_proof_use(
+            &mut ctx.accounts.proof_use,
+            &payload,
+            now,
+            ctx.bumps.proof_use,
+        );
+        transfer_lamports(
+            &ctx.accounts.wallet.to_account_info(),
+            &ctx.accounts.treasury.to_account_info(),
+            price_paid,
+        )?;
+
+        let membership = &mut ctx.accounts.membership;
+        membership.wallet = ctx.accounts.wallet.key();
+        membership.tier = tier;
+        membership.started_at = now;
+        membership.expires_at = now.saturating_add(ctx.accounts.membership_config.period_secs);
+        membership.total_paid = price_paid;
+        membership.bump = ctx.bumps.membership;
+
+        emit!(MembershipUpdatedEvent {
+            wallet: membership.wallet,
+            previous_tier: MEMBERSHIP_TIER_NONE,
+            tier,
+            expires_at: membership.expires_at,
+            price_paid,
+        });
+        Ok(())
+    }
+
+    // An active membership upgrades for the price difference and keeps its expiry; a
+    // lapsed one renews at any tier for the full price.
+    pub fn upgrade_membership(
+        ctx: Context<UpgradeMembership>,
+        tier: u8,
+        payload: ShieldPayloadV0,
+        oracle_signature: [u8; 64],
+        sig_instruction_index: u16,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let previous_tier = active_membership_tier(Some(&ctx.accounts.membership), now);
+        let charge_base =
+            membership_charge_base(&ctx.accounts.membership_config, previous_tier, tier)?;
+        validate_attestation_fields(
+            &payload,
+            &ctx.accounts.admin_config,
+            &ctx.accounts.wallet.key(),
+            now,
+        )?;
+        let price_paid = verify_membership_quote(
+            &payload,
+            &oracle_signature,
+            sig_instruction_index,
+            &ctx.accounts.admin_config,
+            &ctx.accounts.instructions.to_account_info(),
+            charge_base,
+        )?;
+        record_proof_use(
+            &mut ctx.accounts.proof_use,
+            &payload,
+            now,
+            ctx.bumps.proof_use,
+        );
+        transfer_lamports(
+            &ctx.accounts.wallet.to_account_info(),
+            &ctx.accounts.treasury.to_account_info(),
+            price_paid,
+        )?;
+
+        let membership = &mut ctx.accounts.membership;
+        if previous_tier == MEMBERSHIP_TIER_NONE {
+            membership.started_at = now;
+            membership.expires_at = now.saturating_add(ctx.accounts.membership_config.period_secs);
+        }
+        membership.tier = tier;
+        membership.total_paid = membership.total_paid.saturating_add(price_paid);
+
+        emit!(MembershipUpdatedEvent {
+            wallet: membership.wallet,
+            previous_tier,
+            tier,
+            expires_at: membership.expires_at,
+            price_paid,
+        });
+        Ok(())
+    }
+
+    pub fn initialize_graph_registry(
+        ctx: Context<InitializeGraphRegistry>,
+        publisher: Pubkey,
+    ) -> Result<()> {
+        let registry = &mut ctx.accounts.graph_registry;
+        registry.publisher = publisher;
+        registry.latest_version = 0;
+        registry.latest_snapshot_hash = [0u8; 32];
+        registry.snapshot_count = 0;
+        registry.bump = ctx.bumps.graph_registry;
+
+        emit!(GraphPublisherUpdatedEvent {
+            authority: ctx.accounts.authority.key(),
+            old_publisher: Pubkey::default(),
+            new_publisher: publisher,
+        });
+        Ok(())
+    }
+
+    pub fn set_graph_publisher(ctx: Context<GraphRegistryAdmin>, publisher: Pubkey) -> Result<()> {
+        let registry = &mut ctx.accounts.graph_registry;
+        let old_publisher = registry.publisher;
+        registry.publisher = publisher;
+        push_audit_entry(
+            &mut ctx.accounts.audit_log,
+            AUDIT_ACTION_GRAPH_PUBLISHER_SET,
+            ctx.accounts.authority.key(),
+            Clock::get()?.unix_timestamp,
+            old_publisher.to_bytes(),
+            publisher.to_bytes(),
+        );
+
+        emit!(GraphPublisherUpdatedEvent {
+            authority: ctx.accounts.authority.key(),
+            old_publisher,
+            new_publisher: publisher,
+        });
+        Ok(())
+    }
+
+    pub fn anchor_graph_snapshot(
+        ctx: Context<AnchorGraphSnapshot>,
+        version: u64,
+        snapshot_hash: [u8; 32],
+        root_kind: u8,
+    ) -> Result<()> {
+        require!(
+            root_kind <= GRAPH_ROOT_KIND_MERKLE,
+            UnforgivenV2Error::InvalidGraphRootKind
+        );
+        let registry = &mut ctx.accounts.graph_registry;
+        ensure_graph_version_advances(registry.latest_version, version)?;
+
+        let clock = Clock::get()?;
+        let snapshot = &mut ctx.accounts.graph_snapshot;
+        snapshot.version = version;
+        snapshot.snapshot_hash = snapshot_hash;
+        snapshot.root_kind = root_kind;
+        snapshot.publisher = ctx.accounts.publisher.key();
+        snapshot.anchored_at = clock.unix_timestamp;
+        snapshot.slot = clock.slot;
+        snapshot.bump = ctx.bumps.graph_snapshot;
+
+        registry.latest_version = version;
+        registry.latest_snapshot_hash = snapshot_hash;
+        registry.snapshot_count = registry.snapshot_count.saturating_add(1);
+
+        emit!(GraphSnapshotAnchoredEvent {
+            version,
+            snapshot_hash,
+            root_kind,
+            publisher: snapshot.publisher,
+            anchored_at: snapshot.anchored_at,
+        });
+        Ok(())
+    }
 }
 
-#[cfg(not(target_os = "solana"))]
-pub fn verify_oracle_signature(
-    payload: &ShieldPayloadV0,
-    oracle_signature: &[u8; 64],
-    oracle_pubkey: &[u8; 32],
+fn replace_scoring_model_hash(
+    accounts: &mut AdminOnly<'_>,
+    new_scoring_model_hash: [u8; 32],
+    guardrails_reset: bool,
 ) -> Result<()> {
-    use ed25519_dalek::{PublicKey as DalekPublicKey, Signature as DalekSignature, Verifier};
+    let now = Clock::get()?.unix_timestamp;
+    let authority = accounts.authority.key();
+    let old_scoring_model_hash = accounts.admin_config.active_scoring_model_hash;
+    accounts.admin_config.active_scoring_model_hash = new_scoring_model_hash;
+
+    push_audit_entry(
+        &mut accounts.audit_log,
+        if guardrails_reset {
+            AUDIT_ACTION_GUARDRAILS_RESET
+        } else {
+            AUDIT_ACTION_SCORING_MODEL_HASH_SET
+        },
+        authority,
+        now,
+        old_scoring_model_hash,
+        new_scoring_model_hash,
+    );
+    emit!(ScoringModelHashUpdatedEvent {
+        authority,
+        old_scoring_model_hash,
+        new_scoring_model_hash,
+        guardrails_reset,
+        timestamp: now,
+    });
+    Ok(())
+}
 
-    let public = DalekPublicKey::from_bytes(oracle_pubkey)
-        .map_err(|_| error!(UnforgivenV2Error::InvalidOraclePubkey))?;
-    let signature = DalekSignature::from_bytes(oracle_signature)
-        .map_err(|_| error!(UnforgivenV2Error::InvalidOracleSignature))?;
-    let message = serialize_shield_payload_v0(payload);
+// Overwrites the oldest entry once the log holds `AUDIT_LOG_CAPACITY` entries.
+pub fn push_audit_entry(
+    log: &mut AuditLog,
+    action: u8,
+    signer: Pubkey,
+    timestamp: i64,
+    old_value: [u8; 32],
+    new_value: [u8; 32],
+) {
+    let index = usize::from(log.next_index) % AUDIT_LOG_CAPACITY;
+    log.entries[index] = AuditEntry {
+        sequence: log.total_entries,
+        action,
+        signer,
+        timestamp,
+        old_value,
+        new_value,
+    };
+    log.total_entries = log.total_entries.saturating_add(1);
+    log.next_index = ((index + 1) % AUDIT_LOG_CAPACITY) as u16;
+}
 
-    public
-        .verify(&message, &signature)
-        .map_err(|_| error!(UnforgivenV2Error::InvalidOracleSignature))
+// Sale settings do not fit a 32-byte slot, so sale entries store
+// `sha256(event_key || borsh(settings))` for each side; the setter's event has the values.
+fn push_sale_audit_entry<T: AnchorSerialize>(
+    log: &mut AuditLog,
+    action: u8,
+    signer: Pubkey,
+    event_key: &Pubkey,
+    old_settings: &T,
+    new_settings: &T,
+) -> Result<()> {
+    push_audit_entry(
+        log,
+        action,
+        signer,
+        Clock::get()?.unix_timestamp,
+        sale_audit_value(event_key, old_settings)?,
+        sale_audit_value(event_key, new_settings)?,
+    );
+    Ok(())
 }
 
-#[cfg(target_os = "solana")]
-pub fn verify_oracle_signature(
-    _payload: &ShieldPayloadV0,
-    _oracle_signature: &[u8; 64],
-    _oracle_pubkey: &[u8; 32],
+// Initializers have no previous settings, so their entries keep `old_value` zeroed.
+fn push_sale_init_audit_entry<T: AnchorSerialize>(
+    log: &mut AuditLog,
+    action: u8,
+    signer: Pubkey,
+    event_key: &Pubkey,
+    settings: &T,
 ) -> Result<()> {
+    push_audit_entry(
+        log,
+        action,
+        signer,
+        Clock::get()?.unix_timestamp,
+        [0u8; 32],
+        sale_audit_value(event_key, settings)?,
+    );
     Ok(())
 }
 
-pub fn validate_preview_request_fields(
-    payload: &ShieldPayloadV0,
-    admin_config: &AdminConfig,
-    user_key: &Pubkey,
-    now: i64,
-) -> Result<()> {
-    require!(
-        payload.policy_version == POLICY_VERSION_V0,
-        UnforgivenV2Error::InvalidPolicyVersion
-    );
-    require!(payload.dignity_score <= 100, UnforgivenV2Error::InvalidDignityScore);
-    require!(
-        payload.sales_velocity_bps > -10_000,
-        UnforgivenV2Error::InvalidSalesVelocity
-    );
-    require!(
-        payload.user_mode <= USER_MODE_VERIFIED,
-        UnforgivenV2Error::InvalidUserMode
-    );
-    require!(
-        payload.time_elapsed <= MAX_TIME_ELAPSED_SECS,
-        UnforgivenV2Error::InvalidTimeElapsed
-    );
-    require!(
-        payload.attestation_expiry > now,
-        UnforgivenV2Error::AttestationExpired
-    );
-    require!(
-        payload.scoring_model_hash == admin_config.active_scoring_model_hash,
-        UnforgivenV2Error::ScoringModelHashMismatch
-    );
+// Migration entries store the layout version before and after in their first byte.
+fn push_layout_audit_entry(
+    log: &mut AuditLog,
+    action: u8,
+    signer: Pubkey,
+    previous_version: u8,
+    version: u8,
+) -> Result<()> {
+    let mut old_value = [0u8; 32];
+    let mut new_value = [0u8; 32];
+    old_value[0] = previous_version;
+    new_value[0] = version;
+    push_audit_entry(
+        log,
+        action,
+        signer,
+        Clock::get()?.unix_timestamp,
+        old_value,
+        new_value,
+    );
+    Ok(())
+}
+
+pub fn sale_audit_value<T: AnchorSerialize>(event_key: &Pubkey, settings: &T) -> Result<[u8; 32]> {
+    Ok(hashv(&[event_key.as_ref(), &settings.try_to_vec()?]).to_bytes())
+}
+
+// Entries still held by the log, oldest first.
+pub fn audit_log_entries(log: &AuditLog) -> Vec<AuditEntry> {
+    let held = log.total_entries.min(AUDIT_LOG_CAPACITY as u64) as usize;
+    let start = (usize::from(log.next_index) + AUDIT_LOG_CAPACITY - held) % AUDIT_LOG_CAPACITY;
+    (0..held)
+        .map(|offset| log.entries[(start + offset) % AUDIT_LOG_CAPACITY])
+        .collect()
+}
+
+fn hide_sis_step(session: &HideSisSession, node_id: u8) -> Result<(u8, usize)> {
+    require!(
+        !session.finalized,
+        UnforgivenV2Error::HideSisSessionFinalized
+    );
+    require!(
+        node_id == session.current_node,
+        UnforgivenV2Error::HideSisIllegalTransition
+    );
+    let chapter = chapter_of(node_id).ok_or(error!(UnforgivenV2Error::HideSisIllegalTransition))?;
+    Ok((chapter, usize::from(chapter - 1)))
+}
+
+// Applies relation, dignity and pollution effects, then moves to the next node.
+// Returns whether this step added pollution.
+fn apply_hide_sis_effect(
+    session: &mut HideSisSession,
+    chapter_index: usize,
+    quote: TurnQuote,
+    choice: ChoiceEffect,
+    strong_cleanse: bool,
+) -> Result<bool> {
+    session.chapter_budget_bps[chapter_index] = quote.budget_left_bps;
+    session.relation_score = session
+        .relation_score
+        .saturating_add(choice.relation_delta)
+        .clamp(RELATION_MIN, RELATION_MAX);
+    session.dignity_score =
+        (i16::from(session.dignity_score) + i16::from(choice.dignity_delta)).clamp(0, 100) as u8;
+
+    let pollution = pollution_delta(quote.blocked, choice, strong_cleanse);
+    session.pollution_score = (i16::from(session.pollution_score) + i16::from(pollution))
+        .clamp(0, i16::from(POLLUTION_SCORE_MAX)) as u8;
+    session.pollution_flag = session.pollution_score >= POLLUTION_FLAG_THRESHOLD;
+    session.truth_unlocked = truth_unlocked(
+        session.dignity_score,
+        session.relation_score,
+        session.pollution_flag,
+        session.c2_n3_passed,
+    );
+    session.current_node = next_node(session.current_node)
+        .ok_or(error!(UnforgivenV2Error::HideSisIllegalTransition))?;
+    Ok(pollution > 0)
+}
+
+pub fn apply_interrogation_turn(
+    session: &mut HideSisSession,
+    node_id: u8,
+    choice_id: u8,
+) -> Result<InterrogationTurnCommittedEvent> {
+    let (chapter, chapter_index) = hide_sis_step(session, node_id)?;
+    let choice = interrogation_choice(node_id, choice_id)
+        .ok_or(error!(UnforgivenV2Error::HideSisInvalidChoice))?;
+    let quote = quote_interrogation(
+        chapter,
+        session.dignity_score,
+        session.chapter_budget_bps[chapter_index],
+    );
+
+    // The engine also opens the reveal gate for an accusation once trust is maxed out.
+    if choice.marks_reveal || (node_id == C2_N3_KILLER_REVEAL_GATE && session.relation_score >= 3) {
+        session.c2_n3_passed = true;
+    }
+    let polluted =
+        apply_hide_sis_effect(session, chapter_index, quote, choice, choice.marks_reveal)?;
+
+    Ok(InterrogationTurnCommittedEvent {
+        chapter,
+        node_id,
+        choice_id,
+        suspicion_cost: quote.suspicion_cost,
+        budget_left_bps: quote.budget_left_bps,
+        relation_delta: choice.relation_delta,
+        pollution_flag: polluted,
+    })
+}
+
+pub fn apply_chapter_decision(
+    session: &mut HideSisSession,
+    node_id: u8,
+    decision_code: u8,
+) -> Result<ChapterDecisionCommittedEvent> {
+    let (chapter, chapter_index) = hide_sis_step(session, node_id)?;
+    let decision = chapter_decision(node_id, decision_code)
+        .ok_or(error!(UnforgivenV2Error::HideSisInvalidChoice))?;
+    let quote = quote_decision(session.chapter_budget_bps[chapter_index]);
+    let strong_cleanse = decision_code == PACT_COMMIT || decision_code == END_BURY_TRUTH;
+
+    apply_hide_sis_effect(session, chapter_index, quote, decision, strong_cleanse)?;
+    if is_final_decision(decision_code) {
+        session.final_decision_code = decision_code;
+    }
+
+    Ok(ChapterDecisionCommittedEvent {
+        chapter,
+        node_id,
+        decision_code,
+        relation_after: session.relation_score,
+        truth_progress: truth_progress(
+            session.truth_unlocked,
+            session.dignity_score,
+            session.relation_score,
+        ),
+    })
+}
+
+pub fn finalize_hide_sis_session(session: &mut HideSisSession) -> Result<SessionFinalizedEvent> {
+    require!(
+        !session.finalized,
+        UnforgivenV2Error::HideSisSessionFinalized
+    );
+    require!(
+        session.current_node == C3_N3_ENDING_RESOLVE
+            && session.final_decision_code != DECISION_NONE,
+        UnforgivenV2Error::HideSisNotReadyToFinalize
+    );
+
+    let ending_code = resolve_ending_code(
+        session.final_decision_code,
+        session.dignity_score,
+        session.relation_score,
+        session.pollution_flag,
+    );
+    let framed_flag = ending_code == FRAMED_AND_JAILED;
+    session.ending_code = ending_code;
+    session.humanity_score = humanity_score(
+        session.relation_score,
+        session.truth_unlocked,
+        session.pollution_flag,
+        framed_flag,
+    );
+    session.finalized = true;
+
+    Ok(SessionFinalizedEvent {
+        ending_code,
+        final_dignity: session.dignity_score,
+        humanity_score: session.humanity_score,
+        relation_final: session.relation_score,
+        truth_unlocked: session.truth_unlocked,
+        framed_flag,
+    })
+}
+
+// Membership pricing is not tied to a sale, so its audit values are keyed by the
+// `MembershipConfig` address. A fresh config reads as all zeroes.
+fn apply_membership_config(
+    config: &mut Account<MembershipConfig>,
+    audit_log: &mut AuditLog,
+    action: u8,
+    authority: Pubkey,
+    tier_prices: [u64; MEMBERSHIP_TIER_COUNT],
+    period_secs: i64,
+) -> Result<()> {
+    require!(
+        period_secs > 0
+            && tier_prices[0] > 0
+            && tier_prices.windows(2).all(|pair| pair[0] < pair[1]),
+        UnforgivenV2Error::InvalidMembershipConfig
+    );
+    let old_tier_prices = config.tier_prices;
+    let old_period_secs = config.period_secs;
+    config.tier_prices = tier_prices;
+    config.period_secs = period_secs;
+    push_sale_audit_entry(
+        audit_log,
+        action,
+        authority,
+        &config.key(),
+        &(old_tier_prices, old_period_secs),
+        &(tier_prices, period_secs),
+    )?;
+
+    emit!(MembershipConfigUpdatedEvent {
+        authority,
+        tier_prices,
+        period_secs,
+        old_tier_prices,
+        old_period_secs,
+    });
+    Ok(())
+}
+
+// The oracle prices memberships through the shield curve too: the payload must start at
+// `charge_base` and is signed for SIGNING_PURPOSE_MEMBERSHIP. Callers run
+// `validate_attestation_fields` first.
+fn verify_membership_quote(
+    payload: &ShieldPayloadV0,
+    oracle_signature: &[u8; 64],
+    sig_instruction_index: u16,
+    admin_config: &AdminConfig,
+    instructions_ai: &AccountInfo<'_>,
+    charge_base: u64,
+) -> Result<u64> {
+    let message = serialize_shield_message_v0(
+        payload,
+        &shield_signing_domain(admin_config, SIGNING_PURPOSE_MEMBERSHIP, TICKET_CLASS_NONE),
+    );
+    verify_ed25519_ix(
+        instructions_ai,
+        sig_instruction_index,
+        &message,
+        oracle_signature,
+        &admin_config.oracle_pubkey,
+    )?;
+    require!(
+        payload.initial_price == charge_base,
+        UnforgivenV2Error::MembershipPriceMismatch
+    );
+
+    let quote = quote_from_payload(payload)?;
+    require!(!quote.blocked, UnforgivenV2Error::ShieldBlocked);
+    Ok(quote.final_price)
+}
+
+pub fn membership_charge_base(
+    config: &MembershipConfig,
+    current_tier: u8,
+    new_tier: u8,
+) -> Result<u64> {
+    require!(
+        new_tier > current_tier && usize::from(new_tier) <= MEMBERSHIP_TIER_COUNT,
+        UnforgivenV2Error::InvalidMembershipTier
+    );
+    let tier_price = |tier: u8| match tier {
+        MEMBERSHIP_TIER_NONE => 0,
+        _ => config.tier_prices[usize::from(tier) - 1],
+    };
+    Ok(tier_price(new_tier) - tier_price(current_tier))
+}
+
+pub fn active_membership_tier(membership: Option<&Membership>, now: i64) -> u8 {
+    membership
+        .filter(|membership| membership.expires_at > now)
+        .map_or(MEMBERSHIP_TIER_NONE, |membership| membership.tier)
+}
+
+pub fn ensure_membership_tier(
+    sale_config: &SaleConfig,
+    membership: Option<&Membership>,
+    now: i64,
+) -> Result<()> {
+    require!(
+        active_membership_tier(membership, now) >= sale_config.min_membership_tier,
+        UnforgivenV2Error::MembershipTierRequired
+    );
+    Ok(())
+}
+
+// Callers create the payload's ProofUse first, which keeps each payload to one count.
+fn note_blocked_attempt(reputation: &mut Reputation, payload: &ShieldPayloadV0, now: i64) {
+    reputation.blocked_attempts = reputation.blocked_attempts.saturating_add(1);
+    touch_reputation(reputation, now);
+    emit!(BlockedAttemptRecordedEvent {
+        wallet: reputation.wallet,
+        nonce: payload.nonce,
+        zk_proof_hash: payload.zk_proof_hash,
+    });
+}
+
+fn touch_reputation(reputation: &mut Reputation, now: i64) {
+    reputation.updated_at = now;
+    emit!(ReputationUpdatedEvent {
+        wallet: reputation.wallet,
+        fair_purchases: reputation.fair_purchases,
+        redeemed_tickets: reputation.redeemed_tickets,
+        flips: reputation.flips,
+        blocked_attempts: reputation.blocked_attempts,
+    });
+}
+
+// Folds on-chain history into a dignity score. The oracle applies this off-chain before
+// signing; on-chain quotes take the signed `dignity_score` as is. Good behaviour earns at
+// most REPUTATION_BONUS_CAP points; flips and blocked attempts are not capped.
+pub fn reputation_adjusted_dignity(dignity_score: u8, reputation: &Reputation) -> u8 {
+    let bonus = reputation
+        .fair_purchases
+        .saturating_mul(REPUTATION_PURCHASE_BONUS)
+        .saturating_add(
+            reputation
+                .redeemed_tickets
+                .saturating_mul(REPUTATION_REDEEM_BONUS),
+        )
+        .min(REPUTATION_BONUS_CAP);
+    let penalty = reputation
+        .flips
+        .saturating_mul(REPUTATION_FLIP_PENALTY)
+        .saturating_add(
+            reputation
+                .blocked_attempts
+                .saturating_mul(REPUTATION_BLOCKED_PENALTY),
+        );
+    u32::from(dignity_score)
+        .saturating_add(bonus)
+        .saturating_sub(penalty)
+        .min(100) as u8
+}
+
+// Decodes a v1 `Ticket { buyer, purchase_time }` account body.
+pub fn parse_v1_ticket(data: &[u8]) -> Result<(Pubkey, i64)> {
+    require!(
+        data.len() >= V1_TICKET_LEN && data[..8] == hashv(&[b"account:Ticket"]).to_bytes()[..8],
+        UnforgivenV2Error::InvalidV1Ticket
+    );
+    let buyer = Pubkey::try_from(&data[8..40]).map_err(|_| UnforgivenV2Error::InvalidV1Ticket)?;
+    let mut purchase_time = [0u8; 8];
+    purchase_time.copy_from_slice(&data[40..48]);
+    Ok((buyer, i64::from_le_bytes(purchase_time)))
+}
+
+fn require_layout_authority(
+    account: &AccountInfo<'_>,
+    authority_offset: usize,
+    signer: &Pubkey,
+) -> Result<()> {
+    let data = account.try_borrow_data()?;
+    let authority = data
+        .get(authority_offset..authority_offset + 32)
+        .ok_or(error!(UnforgivenV2Error::InvalidAccountLayout))?;
+    require!(
+        authority == signer.as_ref(),
+        UnforgivenV2Error::LayoutAuthorityMismatch
+    );
+    Ok(())
+}
+
+// Grows an account written under an older layout to `len` (new bytes zeroed), tops up
+// rent from `payer` and stamps the current version. Accounts from before versioning
+// are too short to hold the version byte and count as version 0.
+// Returns the account's previous data length, so the caller can fill in defaults for
+// the fields it did not have yet.
+fn migrate_account_layout<'info>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    program_id: &Pubkey,
+    discriminator: &[u8; 8],
+    len: usize,
+    version_offset: usize,
+    version: u8,
+) -> Result<(usize, u8)> {
+    require_keys_eq!(
+        *account.owner,
+        *program_id,
+        UnforgivenV2Error::InvalidAccountLayout
+    );
+    let (previous_len, previous_version) = {
+        let data = account.try_borrow_data()?;
+        require!(
+            data.len() <= len && data.get(..8) == Some(&discriminator[..]),
+            UnforgivenV2Error::InvalidAccountLayout
+        );
+        (data.len(), stored_layout_version(&data, version_offset))
+    };
+    require!(
+        previous_version < version,
+        UnforgivenV2Error::AccountLayoutCurrent
+    );
+
+    let rent_top_up = Rent::get()?
+        .minimum_balance(len)
+        .saturating_sub(account.lamports());
+    transfer_lamports(payer, account, rent_top_up)?;
+    account.realloc(len, true)?;
+    account.try_borrow_mut_data()?[version_offset] = version;
+
+    emit!(AccountLayoutMigratedEvent {
+        account: account.key(),
+        previous_version,
+        layout_version: version,
+        data_len: len as u32,
+    });
+    Ok((previous_len, previous_version))
+}
+
+// New fields get what `initialize_admin_config` writes, not zeroes: an all-zero allowlist
+// would reject every zk provider and a zero cluster id would reject every signature.
+pub fn write_admin_config_defaults(data: &mut [u8], previous_len: usize, cluster_id: &[u8; 32]) {
+    use account_layout::admin_config as layout;
+    if previous_len <= layout::ZK_PROVIDER_ALLOWLIST {
+        data[layout::ZK_PROVIDER_ALLOWLIST..layout::CLUSTER_ID].fill(u8::MAX);
+    }
+    if previous_len <= layout::CLUSTER_ID {
+        data[layout::CLUSTER_ID..layout::LAYOUT_VERSION].copy_from_slice(cluster_id);
+    }
+}
+
+// A receipt from before holder modes carries no attestation, so its holder counts as a
+// guest rather than as bot-suspected (mode 0).
+pub fn write_ticket_receipt_defaults(data: &mut [u8], previous_len: usize) {
+    use account_layout::ticket_receipt as layout;
+    if previous_len <= layout::HOLDER_USER_MODE {
+        data[layout::HOLDER_USER_MODE] = USER_MODE_GUEST;
+    }
+    if previous_len <= layout::CLASS_ID {
+        data[layout::CLASS_ID] = TICKET_CLASS_NONE;
+    }
+}
+
+pub fn stored_layout_version(data: &[u8], version_offset: usize) -> u8 {
+    data.get(version_offset).copied().unwrap_or(0)
+}
+
+pub fn ensure_graph_version_advances(latest_version: u64, version: u64) -> Result<()> {
+    require!(
+        version > latest_version,
+        UnforgivenV2Error::GraphSnapshotVersionNotMonotonic
+    );
+    Ok(())
+}
+
+fn compute_resale_fee(sale_price: u64) -> Result<u64> {
+    sale_price
+        .checked_mul(RESALE_FEE_BPS)
+        .and_then(|value| value.checked_div(10_000))
+        .ok_or(error!(UnforgivenV2Error::InvalidListingPrice))
+}
+
+// Quotes an execute payload the way a purchase is charged: sale or class pricing, then
+// the user mode's price multiplier.
+fn shield_purchase_quote(
+    payload: &ShieldPayloadV0,
+    sale_config: &SaleConfig,
+    ticket_class: Option<&TicketClass>,
+) -> Result<VrgdaQuote> {
+    let policy = enforce_user_mode_policy(
+        sale_config,
+        payload.user_mode,
+        payload.dignity_score,
+        MODE_ACTION_BUY,
+    )?;
+    let quote = quote_for_class(payload, sale_config, ticket_class)?;
+    if quote.blocked {
+        return Ok(quote);
+    }
+    Ok(apply_price_multiplier(
+        quote,
+        policy.price_multiplier_bps,
+        payload.initial_price,
+    ))
+}
+
+// A blocked execute spends its payload without charging or minting: the ProofUse is
+// created and the attempt lands on the buyer's reputation.
+fn record_blocked_purchase(
+    accounts: &mut ExecuteShield<'_>,
+    program_id: &Pubkey,
+    seed_payload: &ShieldPayloadV0,
+    bumps: &ShieldAccountBumps,
+    now: i64,
+) -> Result<()> {
+    let nonce_bytes = seed_payload.nonce.to_le_bytes();
+    let proof_use_seeds: &[&[u8]] = &[
+        b"proof_use",
+        seed_payload.user_pubkey.as_ref(),
+        seed_payload.zk_proof_hash.as_ref(),
+        nonce_bytes.as_ref(),
+        &[bumps.proof_use],
+    ];
+    require_pda_address(&accounts.proof_use.key(), proof_use_seeds, program_id)?;
+    require_pda_address(
+        &accounts.reputation.key(),
+        &[
+            REPUTATION_SEED,
+            seed_payload.user_pubkey.as_ref(),
+            &[bumps.reputation],
+        ],
+        program_id,
+    )?;
+
+    create_pda_account(
+        &accounts.user.to_account_info(),
+        &accounts.proof_use.to_account_info(),
+        &accounts.system_program.to_account_info(),
+        program_id,
+        8 + ProofUse::INIT_SPACE,
+        proof_use_seeds,
+        &accounts.rent,
+    )?;
+    write_proof_use_account(
+        &accounts.proof_use.to_account_info(),
+        seed_payload,
+        now,
+        bumps.proof_use,
+    )?;
+    note_blocked_attempt(&mut accounts.reputation, seed_payload, now);
+    Ok(())
+}
+
+fn process_shield_purchase(
+    accounts: &mut ExecuteShield<'_>,
+    program_id: &Pubkey,
+    seed_payload: &ShieldPayloadV0,
+    oracle_signature: &[u8; 64],
+    sig_instruction_index: u16,
+    bumps: ShieldAccountBumps,
+    record_blocked: bool,
+) -> Result<()> {
+    require_top_level_shield_purchase(&accounts.instructions.to_account_info(), seed_payload)?;
+    let clock = Clock::get()?;
+    validate_preview_request_fields(
+        seed_payload,
+        &accounts.admin_config,
+        &accounts.sale_config,
+        &accounts.user.key(),
+        clock.unix_timestamp,
+    )?;
+    let message = serialize_shield_message_v0(
+        seed_payload,
+        &shield_signing_domain(
+            &accounts.admin_config,
+            SIGNING_PURPOSE_EXECUTE,
+            ticket_class_id(accounts.ticket_class.as_deref().map(|class| &**class)),
+        ),
+    );
+    verify_ed25519_ix(
+        &accounts.instructions.to_account_info(),
+        sig_instruction_index,
+        &message,
+        oracle_signature,
+        &accounts.admin_config.oracle_pubkey,
+    )?;
+
+    require_keys_eq!(
+        accounts.treasury.key(),
+        accounts.global_config_v2.authority,
+        UnforgivenV2Error::TreasuryMismatch
+    );
+    ensure_membership_tier(
+        &accounts.sale_config,
+        accounts
+            .membership
+            .as_deref()
+            .map(|membership| &**membership),
+        clock.unix_timestamp,
+    )?;
+    let quote = shield_purchase_quote(
+        seed_payload,
+        &accounts.sale_config,
+        accounts.ticket_class.as_deref().map(|class| &**class),
+    )?;
+    if quote.blocked {
+        require!(record_blocked, UnforgivenV2Error::ShieldBlocked);
+        return record_blocked_purchase(
+            accounts,
+            program_id,
+            seed_payload,
+            &bumps,
+            clock.unix_timestamp,
+        );
+    }
+    let class_id = match accounts.ticket_class.as_mut() {
+        Some(class) => {
+            require!(
+                class.allocation_mode == ALLOCATION_MODE_OPEN,
+                UnforgivenV2Error::SealedBidAllocationOnly
+            );
+            class.sold = next_ticket_class_sale(class.sold, class.supply)?;
+            class.class_id
+        }
+        None => TICKET_CLASS_NONE,
+    };
+
+    let nonce_bytes = seed_payload.nonce.to_le_bytes();
+    let user_key = accounts.user.key();
+    let event_key = accounts.global_config_v2.key();
+    let rent: &Rent = &accounts.rent;
+
+    // Replay protection relies on ProofUse and ProofQuota living at their canonical
+    // addresses, which `require_pda_address` enforces for the caller-supplied bumps.
+    let proof_use_seeds: &[&[u8]] = &[
+        b"proof_use",
+        seed_payload.user_pubkey.as_ref(),
+        seed_payload.zk_proof_hash.as_ref(),
+        nonce_bytes.as_ref(),
+        &[bumps.proof_use],
+    ];
+    require_pda_address(&accounts.proof_use.key(), proof_use_seeds, program_id)?;
+    require_pda_address(
+        &accounts.proof_quota.key(),
+        &[
+            PROOF_QUOTA_SEED,
+            event_key.as_ref(),
+            seed_payload.zk_proof_hash.as_ref(),
+            &[bumps.proof_quota],
+        ],
+        program_id,
+    )?;
+    record_proof_quota_use(
+        &accounts.proof_quota.to_account_info(),
+        program_id,
+        &event_key,
+        &seed_payload.zk_proof_hash,
+        accounts.sale_config.max_uses_per_proof,
+    )?;
+
+    create_pda_account(
+        &accounts.user.to_account_info(),
+        &accounts.proof_use.to_account_info(),
+        &accounts.system_program.to_account_info(),
+        program_id,
+        8 + ProofUse::INIT_SPACE,
+        proof_use_seeds,
+        rent,
+    )?;
+    write_proof_use_account(
+        &accounts.proof_use.to_account_info(),
+        seed_payload,
+        clock.unix_timestamp,
+        bumps.proof_use,
+    )?;
+
+    let ticket_mint_seeds: &[&[u8]] = &[
+        TICKET_MINT_SEED,
+        seed_payload.user_pubkey.as_ref(),
+        seed_payload.zk_proof_hash.as_ref(),
+        nonce_bytes.as_ref(),
+        &[bumps.ticket_mint],
+    ];
+    let ticket_mint_key = accounts.ticket_mint.key();
+    let user_ticket_token_seeds: &[&[u8]] = &[
+        TICKET_TOKEN_SEED,
+        ticket_mint_key.as_ref(),
+        user_key.as_ref(),
+        &[bumps.user_ticket_token],
+    ];
+    let ticket_receipt_seeds: &[&[u8]] = &[
+        TICKET_RECEIPT_SEED,
+        ticket_mint_key.as_ref(),
+        &[bumps.ticket_receipt],
+    ];
+    require_pda_address(&accounts.ticket_mint.key(), ticket_mint_seeds, program_id)?;
+    require_pda_address(
+        &accounts.user_ticket_token.key(),
+        user_ticket_token_seeds,
+        program_id,
+    )?;
+    require_pda_address(
+        &accounts.ticket_receipt.key(),
+        ticket_receipt_seeds,
+        program_id,
+    )?;
+    require_pda_address(
+        &accounts.ticket_mint_authority.key(),
+        &[TICKET_MINT_AUTHORITY_SEED, &[bumps.ticket_mint_authority]],
+        program_id,
+    )?;
+    require_pda_address(
+        &accounts.reputation.key(),
+        &[REPUTATION_SEED, user_key.as_ref(), &[bumps.reputation]],
+        program_id,
+    )?;
+
+    transfer_lamports(
+        &accounts.user.to_account_info(),
+        &accounts.treasury.to_account_info(),
+        quote.final_price,
+    )?;
+
+    mint_ticket_to_owner(
+        TicketMintAccounts {
+            payer: &accounts.user.to_account_info(),
+            ticket_mint: &accounts.ticket_mint.to_account_info(),
+            ticket_mint_authority: &accounts.ticket_mint_authority.to_account_info(),
+            owner_ticket_token: &accounts.user_ticket_token.to_account_info(),
+            token_program: &accounts.token_program.to_account_info(),
+            system_program: &accounts.system_program.to_account_info(),
+        },
+        &user_key,
+        ticket_mint_seeds,
+        user_ticket_token_seeds,
+        bumps.ticket_mint_authority,
+        rent,
+    )?;
+
+    create_pda_account(
+        &accounts.user.to_account_info(),
+        &accounts.ticket_receipt.to_account_info(),
+        &accounts.system_program.to_account_info(),
+        program_id,
+        8 + TicketReceipt::INIT_SPACE,
+        ticket_receipt_seeds,
+        rent,
+    )?;
+    write_ticket_receipt_account(
+        &accounts.ticket_receipt.to_account_info(),
+        IssuedTicketReceipt {
+            mint: &ticket_mint_key,
+            event_key: &event_key,
+            owner: &user_key,
+            price: quote.final_price,
+            issued_at: clock.unix_timestamp,
+            nonce: seed_payload.nonce,
+            zk_proof_hash: &seed_payload.zk_proof_hash,
+            holder_user_mode: seed_payload.user_mode,
+            class_id,
+            bump: bumps.ticket_receipt,
+        },
+    )?;
+
+    emit!(ShieldExecutionEvent {
+        final_price: quote.final_price,
+        blocked: quote.blocked,
+        effective_velocity_bps: quote.effective_velocity_bps,
+        dignity_score: seed_payload.dignity_score,
+        adapter_mask: seed_payload.adapter_mask,
+        user_mode: seed_payload.user_mode,
+        nonce: seed_payload.nonce,
+        zk_proof_hash: seed_payload.zk_proof_hash,
+    });
+
+    emit!(TicketMintedEvent {
+        mint: ticket_mint_key,
+        owner: user_key,
+        final_price: quote.final_price,
+        nonce: seed_payload.nonce,
+    });
+
+    let reputation = &mut accounts.reputation;
+    reputation.fair_purchases = reputation.fair_purchases.saturating_add(1);
+    touch_reputation(reputation, clock.unix_timestamp);
+
+    Ok(())
+}
+
+fn transfer_lamports<'info>(
+    from: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+
+    let ix = system_instruction::transfer(&from.key(), &to.key(), amount);
+    invoke(&ix, &[from.clone(), to.clone()])?;
+    Ok(())
+}
+
+fn transfer_owned_lamports(
+    from: &AccountInfo<'_>,
+    to: &AccountInfo<'_>,
+    amount: u64,
+) -> Result<()> {
+    let from_balance = from
+        .lamports()
+        .checked_sub(amount)
+        .ok_or(error!(UnforgivenV2Error::InvalidExecuteShieldAccount))?;
+    let to_balance = to
+        .lamports()
+        .checked_add(amount)
+        .ok_or(error!(UnforgivenV2Error::InvalidExecuteShieldAccount))?;
+    **from.try_borrow_mut_lamports()? = from_balance;
+    **to.try_borrow_mut_lamports()? = to_balance;
+    Ok(())
+}
+
+fn create_pda_account<'info>(
+    payer: &AccountInfo<'info>,
+    new_account: &AccountInfo<'info>,
+    system_program_info: &AccountInfo<'info>,
+    owner: &Pubkey,
+    space: usize,
+    signer_seeds: &[&[u8]],
+    rent: &Rent,
+) -> Result<()> {
+    require!(
+        new_account.owner == &anchor_lang::system_program::ID,
+        UnforgivenV2Error::InvalidExecuteShieldAccount
+    );
+    require!(
+        new_account.lamports() == 0 && new_account.data_is_empty(),
+        UnforgivenV2Error::InvalidExecuteShieldAccount
+    );
+
+    let lamports = rent.minimum_balance(space);
+    let ix = system_instruction::create_account(
+        payer.key,
+        new_account.key,
+        lamports,
+        space as u64,
+        owner,
+    );
+    invoke_signed(
+        &ix,
+        &[payer.clone(), new_account.clone(), system_program_info.clone()],
+        &[signer_seeds],
+    )?;
+    Ok(())
+}
+
+// Purchases mint through a fixed account set, so they must be the top-level instruction
+// rather than a CPI: the current instruction has to be ours and carry this payload first.
+fn require_top_level_shield_purchase(
+    instructions_sysvar: &AccountInfo,
+    seed_payload: &ShieldPayloadV0,
+) -> Result<()> {
+    let current_ix_idx = load_current_index_checked(instructions_sysvar)
+        .map_err(|_| error!(UnforgivenV2Error::InvalidEd25519Instruction))?;
+    let current_ix = load_instruction_at_checked(current_ix_idx as usize, instructions_sysvar)
+        .map_err(|_| error!(UnforgivenV2Error::InvalidEd25519Instruction))?;
+    require_keys_eq!(
+        current_ix.program_id,
+        crate::id(),
+        UnforgivenV2Error::InvalidEd25519Instruction
+    );
+
+    let current_data = current_ix.data.as_slice();
+    require!(
+        current_data.len() >= 8 + SHIELD_PAYLOAD_V0_LEN,
+        UnforgivenV2Error::InvalidEd25519Instruction
+    );
+    require!(
+        current_data[8..8 + SHIELD_PAYLOAD_V0_LEN] == serialize_shield_payload_v0(seed_payload),
+        UnforgivenV2Error::InvalidEd25519Instruction
+    );
+    Ok(())
+}
+
+/// Checks `expected` against `signer_seeds`, whose last seed is the caller-supplied bump.
+/// The bump must be canonical: every higher bump must fall on the curve, as
+/// `find_program_address` would have found it first. Accounts created here are later
+/// addressed with Anchor's canonical `bump` constraint.
+pub fn require_pda_address(
+    expected: &Pubkey,
+    signer_seeds: &[&[u8]],
+    program_id: &Pubkey,
+) -> Result<()> {
+    let (bump_seed, seeds) = signer_seeds
+        .split_last()
+        .ok_or(error!(UnforgivenV2Error::InvalidExecuteShieldAccount))?;
+    let [bump] = bump_seed[..] else {
+        return err!(UnforgivenV2Error::InvalidExecuteShieldAccount);
+    };
+    let derived = Pubkey::create_program_address(signer_seeds, program_id)
+        .map_err(|_| error!(UnforgivenV2Error::InvalidExecuteShieldAccount))?;
+    require_keys_eq!(
+        derived,
+        *expected,
+        UnforgivenV2Error::InvalidExecuteShieldAccount
+    );
+
+    for higher in u16::from(bump) + 1..=u16::from(u8::MAX) {
+        let higher_seed = [higher as u8];
+        let mut with_bump = seeds.to_vec();
+        with_bump.push(&higher_seed);
+        require!(
+            Pubkey::create_program_address(&with_bump, program_id).is_err(),
+            UnforgivenV2Error::InvalidExecuteShieldAccount
+        );
+    }
+    Ok(())
+}
+
+struct TicketMintAccounts<'a, 'info> {
+    payer: &'a AccountInfo<'info>,
+    ticket_mint: &'a AccountInfo<'info>,
+    ticket_mint_authority: &'a AccountInfo<'info>,
+    owner_ticket_token: &'a AccountInfo<'info>,
+    token_program: &'a AccountInfo<'info>,
+    system_program: &'a AccountInfo<'info>,
+}
+
+fn mint_ticket_to_owner(
+    accounts: TicketMintAccounts<'_, '_>,
+    owner: &Pubkey,
+    ticket_mint_seeds: &[&[u8]],
+    owner_ticket_token_seeds: &[&[u8]],
+    mint_authority_bump: u8,
+    rent: &Rent,
+) -> Result<()> {
+    create_pda_account(
+        accounts.payer,
+        accounts.ticket_mint,
+        accounts.system_program,
+        &spl_token::id(),
+        SPL_TOKEN_MINT_LEN,
+        ticket_mint_seeds,
+        rent,
+    )?;
+    initialize_ticket_mint(
+        accounts.token_program,
+        accounts.ticket_mint,
+        accounts.ticket_mint_authority.key,
+    )?;
+    create_pda_account(
+        accounts.payer,
+        accounts.owner_ticket_token,
+        accounts.system_program,
+        &spl_token::id(),
+        SPL_TOKEN_ACCOUNT_LEN,
+        owner_ticket_token_seeds,
+        rent,
+    )?;
+    initialize_ticket_token_account(
+        accounts.token_program,
+        accounts.owner_ticket_token,
+        accounts.ticket_mint,
+        owner,
+    )?;
+
+    let mint_authority_bump = [mint_authority_bump];
+    let mint_authority_seeds: &[&[u8]] = &[TICKET_MINT_AUTHORITY_SEED, &mint_authority_bump];
+    token::mint_to(
+        CpiContext::new_with_signer(
+            accounts.token_program.clone(),
+            MintTo {
+                mint: accounts.ticket_mint.clone(),
+                to: accounts.owner_ticket_token.clone(),
+                authority: accounts.ticket_mint_authority.clone(),
+            },
+            &[mint_authority_seeds],
+        ),
+        TICKET_AMOUNT,
+    )?;
+    token::set_authority(
+        CpiContext::new_with_signer(
+            accounts.token_program.clone(),
+            SetAuthority {
+                account_or_mint: accounts.ticket_mint.clone(),
+                current_authority: accounts.ticket_mint_authority.clone(),
+            },
+            &[mint_authority_seeds],
+        ),
+        AuthorityType::MintTokens,
+        None,
+    )?;
+    Ok(())
+}
+
+fn initialize_ticket_mint<'info>(
+    token_program_info: &AccountInfo<'info>,
+    mint_info: &AccountInfo<'info>,
+    mint_authority: &Pubkey,
+) -> Result<()> {
+    let ix = spl_token::instruction::initialize_mint2(
+        &spl_token::id(),
+        mint_info.key,
+        mint_authority,
+        None,
+        TICKET_DECIMALS,
+    )
+    .map_err(|_| error!(UnforgivenV2Error::InvalidExecuteShieldAccount))?;
+    invoke(&ix, &[mint_info.clone(), token_program_info.clone()])?;
+    Ok(())
+}
+
+fn initialize_ticket_token_account<'info>(
+    token_program_info: &AccountInfo<'info>,
+    token_account_info: &AccountInfo<'info>,
+    mint_info: &AccountInfo<'info>,
+    owner: &Pubkey,
+) -> Result<()> {
+    let ix = spl_token::instruction::initialize_account3(
+        &spl_token::id(),
+        token_account_info.key,
+        mint_info.key,
+        owner,
+    )
+    .map_err(|_| error!(UnforgivenV2Error::InvalidExecuteShieldAccount))?;
+    invoke(
+        &ix,
+        &[
+            token_account_info.clone(),
+            mint_info.clone(),
+            token_program_info.clone(),
+        ],
+    )?;
+    Ok(())
+}
+
+// Reputation is created on first use, so a wallet is credited even if it never called
+// `initialize_reputation`.
+fn load_or_create_reputation<'info>(
+    payer: &AccountInfo<'info>,
+    reputation: &AccountInfo<'info>,
+    system_program_info: &AccountInfo<'info>,
+    program_id: &Pubkey,
+    wallet: &Pubkey,
+    rent: &Rent,
+) -> Result<Reputation> {
+    let (expected, bump) =
+        Pubkey::find_program_address(&[REPUTATION_SEED, wallet.as_ref()], program_id);
+    require_keys_eq!(
+        reputation.key(),
+        expected,
+        UnforgivenV2Error::ReputationWalletMismatch
+    );
+    if reputation.data_is_empty() {
+        create_pda_account(
+            payer,
+            reputation,
+            system_program_info,
+            program_id,
+            8 + Reputation::INIT_SPACE,
+            &[REPUTATION_SEED, wallet.as_ref(), &[bump]],
+            rent,
+        )?;
+        let created = Reputation {
+            wallet: *wallet,
+            fair_purchases: 0,
+            redeemed_tickets: 0,
+            flips: 0,
+            blocked_attempts: 0,
+            updated_at: 0,
+            bump,
+        };
+        store_reputation(reputation, &created)?;
+        return Ok(created);
+    }
+
+    require_keys_eq!(
+        *reputation.owner,
+        *program_id,
+        UnforgivenV2Error::ReputationWalletMismatch
+    );
+    let data = reputation.try_borrow_data()?;
+    Reputation::try_deserialize(&mut &data[..])
+}
+
+fn store_reputation(account: &AccountInfo<'_>, reputation: &Reputation) -> Result<()> {
+    let mut data = account.try_borrow_mut_data()?;
+    reputation.try_serialize(&mut &mut data[..])
+}
+
+// Creates the canonical `ProofUse` PDA for a payload that arrives as an optional
+// argument, where the account cannot be `init`-ed from the instruction args.
+fn consume_payload_nonce<'info>(
+    payer: &AccountInfo<'info>,
+    proof_use: &AccountInfo<'info>,
+    system_program_info: &AccountInfo<'info>,
+    program_id: &Pubkey,
+    payload: &ShieldPayloadV0,
+    used_at: i64,
+    rent: &Rent,
+) -> Result<()> {
+    let nonce_bytes = payload.nonce.to_le_bytes();
+    let (expected, bump) = Pubkey::find_program_address(
+        &[
+            b"proof_use",
+            payload.user_pubkey.as_ref(),
+            payload.zk_proof_hash.as_ref(),
+            nonce_bytes.as_ref(),
+        ],
+        program_id,
+    );
+    require_keys_eq!(
+        proof_use.key(),
+        expected,
+        UnforgivenV2Error::InvalidExecuteShieldAccount
+    );
+    create_pda_account(
+        payer,
+        proof_use,
+        system_program_info,
+        program_id,
+        8 + ProofUse::INIT_SPACE,
+        &[
+            b"proof_use",
+            payload.user_pubkey.as_ref(),
+            payload.zk_proof_hash.as_ref(),
+            nonce_bytes.as_ref(),
+            &[bump],
+        ],
+        rent,
+    )?;
+    write_proof_use_account(proof_use, payload, used_at, bump)
+}
+
+fn write_proof_use_account(
+    account: &AccountInfo<'_>,
+    payload: &ShieldPayloadV0,
+    used_at: i64,
+    bump: u8,
+) -> Result<()> {
+    use account_layout::proof_use as layout;
+    let mut data = account.try_borrow_mut_data()?;
+    require!(
+        data.len() >= layout::LEN,
+        UnforgivenV2Error::InvalidExecuteShieldAccount
+    );
+    data[..layout::USER_PUBKEY].copy_from_slice(&ProofUse::DISCRIMINATOR);
+    data[layout::USER_PUBKEY..layout::ZK_PROOF_HASH].copy_from_slice(&payload.user_pubkey);
+    data[layout::ZK_PROOF_HASH..layout::NONCE].copy_from_slice(&payload.zk_proof_hash);
+    data[layout::NONCE..layout::USED_AT].copy_from_slice(&payload.nonce.to_le_bytes());
+    data[layout::USED_AT..layout::BUMP].copy_from_slice(&used_at.to_le_bytes());
+    data[layout::BUMP] = bump;
+    Ok(())
+}
+
+fn record_proof_use(proof_use: &mut ProofUse, payload: &ShieldPayloadV0, used_at: i64, bump: u8) {
+    proof_use.user_pubkey = payload.user_pubkey;
+    proof_use.zk_proof_hash = payload.zk_proof_hash;
+    proof_use.nonce = payload.nonce;
+    proof_use.used_at = used_at;
+    proof_use.bump = bump;
+}
+
+// Counts one use of `zk_proof_hash` against the sale's `max_uses_per_proof`. The
+// `ProofQuota` must already exist (`initialize_proof_quota`).
+fn record_proof_quota_use(
+    account: &AccountInfo<'_>,
+    program_id: &Pubkey,
+    event_key: &Pubkey,
+    zk_proof_hash: &[u8; 32],
+    max_uses: u16,
+) -> Result<()> {
+    require_keys_eq!(
+        *account.owner,
+        *program_id,
+        UnforgivenV2Error::InvalidExecuteShieldAccount
+    );
+    use account_layout::proof_quota as layout;
+    let mut data = account.try_borrow_mut_data()?;
+    require!(
+        data.len() >= layout::LEN,
+        UnforgivenV2Error::InvalidExecuteShieldAccount
+    );
+    require!(
+        data[..layout::EVENT_KEY] == ProofQuota::DISCRIMINATOR
+            && data[layout::EVENT_KEY..layout::ZK_PROOF_HASH] == event_key.to_bytes()
+            && data[layout::ZK_PROOF_HASH..layout::USES] == zk_proof_hash[..],
+        UnforgivenV2Error::InvalidExecuteShieldAccount
+    );
+
+    let uses = u16::from_le_bytes([data[layout::USES], data[layout::USES + 1]]);
+    let next_uses = next_proof_quota_use(uses, max_uses)?;
+    data[layout::USES..layout::BUMP].copy_from_slice(&next_uses.to_le_bytes());
+    Ok(())
+}
+
+pub fn next_proof_quota_use(uses: u16, max_uses: u16) -> Result<u16> {
+    require!(uses < max_uses, UnforgivenV2Error::ProofQuotaExhausted);
+    Ok(uses + 1)
+}
+
+// `max_transfers == 0` leaves gifting unlimited.
+// Modes are ordered worst first, so the lower of the two wins.
+pub fn transferred_user_mode(sender_mode: u8, recipient_mode: u8) -> u8 {
+    sender_mode.min(recipient_mode)
+}
+
+pub fn next_ticket_transfer_count(count: u16, max_transfers: u16) -> Result<u16> {
+    require!(
+        max_transfers == 0 || count < max_transfers,
+        UnforgivenV2Error::TicketTransferLimitReached
+    );
+    count
+        .checked_add(1)
+        .ok_or(error!(UnforgivenV2Error::TicketTransferLimitReached))
+}
+
+// What a freshly issued receipt records; every other field starts at zero.
+struct IssuedTicketReceipt<'a> {
+    mint: &'a Pubkey,
+    event_key: &'a Pubkey,
+    owner: &'a Pubkey,
+    price: u64,
+    issued_at: i64,
+    nonce: u64,
+    zk_proof_hash: &'a [u8; 32],
+    holder_user_mode: u8,
+    class_id: u8,
+    bump: u8,
+}
+
+fn write_ticket_receipt_account(
+    account: &AccountInfo<'_>,
+    receipt: IssuedTicketReceipt<'_>,
+) -> Result<()> {
+    use account_layout::ticket_receipt as layout;
+    let mut data = account.try_borrow_mut_data()?;
+    require!(
+        data.len() >= layout::LEN,
+        UnforgivenV2Error::InvalidExecuteShieldAccount
+    );
+    data[..layout::MINT].copy_from_slice(&TicketReceipt::DISCRIMINATOR);
+    data[layout::MINT..layout::EVENT_KEY].copy_from_slice(receipt.mint.as_ref());
+    data[layout::EVENT_KEY..layout::ORIGINAL_BUYER].copy_from_slice(receipt.event_key.as_ref());
+    data[layout::ORIGINAL_BUYER..layout::CURRENT_HOLDER].copy_from_slice(receipt.owner.as_ref());
+    data[layout::CURRENT_HOLDER..layout::PURCHASE_PRICE].copy_from_slice(receipt.owner.as_ref());
+    data[layout::PURCHASE_PRICE..layout::LAST_SALE_PRICE]
+        .copy_from_slice(&receipt.price.to_le_bytes());
+    data[layout::LAST_SALE_PRICE..layout::ISSUED_AT].copy_from_slice(&receipt.price.to_le_bytes());
+    data[layout::ISSUED_AT..layout::LAST_TRANSFER_AT]
+        .copy_from_slice(&receipt.issued_at.to_le_bytes());
+    data[layout::LAST_TRANSFER_AT..layout::NONCE].copy_from_slice(&receipt.issued_at.to_le_bytes());
+    data[layout::NONCE..layout::ZK_PROOF_HASH].copy_from_slice(&receipt.nonce.to_le_bytes());
+    data[layout::ZK_PROOF_HASH..layout::LISTED].copy_from_slice(receipt.zk_proof_hash);
+    // listed, resale_count
+    data[layout::LISTED..layout::BUMP].fill(0);
+    data[layout::BUMP] = receipt.bump;
+    data[layout::HOLDER_USER_MODE] = receipt.holder_user_mode;
+    data[layout::CLASS_ID] = receipt.class_id;
+    // transfer_count, entry secret, redeemed_at
+    data[layout::TRANSFER_COUNT..layout::LAYOUT_VERSION].fill(0);
+    data[layout::LAYOUT_VERSION] = layout::VERSION;
+    Ok(())
+}
+
+pub fn serialize_shield_payload_v0(payload: &ShieldPayloadV0) -> [u8; SHIELD_PAYLOAD_V0_LEN] {
+    let mut out = [0u8; SHIELD_PAYLOAD_V0_LEN];
+
+    out[0] = payload.policy_version;
+    out[1..33].copy_from_slice(&payload.user_pubkey);
+    out[33..41].copy_from_slice(&payload.initial_price.to_le_bytes());
+    out[41..49].copy_from_slice(&payload.sales_velocity_bps.to_le_bytes());
+    out[49..57].copy_from_slice(&payload.time_elapsed.to_le_bytes());
+    out[57] = payload.dignity_score;
+    out[58] = payload.adapter_mask;
+    out[59] = payload.user_mode;
+    out[60] = payload.zk_provider;
+    out[61..93].copy_from_slice(&payload.zk_proof_hash);
+    out[93..125].copy_from_slice(&payload.scoring_model_hash);
+    out[125..133].copy_from_slice(&payload.attestation_expiry.to_le_bytes());
+    out[133..141].copy_from_slice(&payload.nonce.to_le_bytes());
+
+    out
+}
+
+pub fn quote_return_v0(
+    purpose: u8,
+    payload: &ShieldPayloadV0,
+    quote: &VrgdaQuote,
+) -> QuoteReturnV0 {
+    QuoteReturnV0 {
+        purpose,
+        final_price: quote.final_price,
+        is_infinite: quote.is_infinite,
+        blocked: quote.blocked,
+        effective_velocity_bps: quote.effective_velocity_bps,
+        dignity_score: payload.dignity_score,
+        adapter_mask: payload.adapter_mask,
+        dignity_bucket: dignity_bucket(payload.dignity_score),
+        user_mode: payload.user_mode,
+        nonce: payload.nonce,
+        zk_proof_hash: payload.zk_proof_hash,
+    }
+}
+
+pub fn encode_quote_return_v0(quote: &QuoteReturnV0) -> [u8; QUOTE_RETURN_V0_LEN] {
+    unforgiven_v2_layout::quote_return::encode_v0(quote)
+}
+
+pub fn decode_quote_return_v0(data: &[u8]) -> Result<QuoteReturnV0> {
+    unforgiven_v2_layout::quote_return::decode_v0(data)
+        .ok_or(error!(UnforgivenV2Error::InvalidQuoteReturnData))
+}
+
+pub fn shield_signing_domain(
+    admin_config: &AdminConfig,
+    purpose: u8,
+    ticket_class: u8,
+) -> SigningDomain {
+    SigningDomain {
+        program_id: crate::id(),
+        cluster_id: admin_config.cluster_id,
+        purpose,
+        ticket_class,
+    }
+}
+
+pub fn serialize_shield_message_v0(
+    payload: &ShieldPayloadV0,
+    domain: &SigningDomain,
+) -> [u8; SHIELD_SIGNED_MESSAGE_V0_LEN] {
+    let mut out = [0u8; SHIELD_SIGNED_MESSAGE_V0_LEN];
+
+    out[..SHIELD_DOMAIN_PROGRAM_ID_OFFSET].copy_from_slice(SHIELD_DOMAIN_TAG);
+    out[SHIELD_DOMAIN_PROGRAM_ID_OFFSET..SHIELD_DOMAIN_CLUSTER_ID_OFFSET]
+        .copy_from_slice(domain.program_id.as_ref());
+    out[SHIELD_DOMAIN_CLUSTER_ID_OFFSET..SHIELD_DOMAIN_PURPOSE_OFFSET]
+        .copy_from_slice(&domain.cluster_id);
+    out[SHIELD_DOMAIN_PURPOSE_OFFSET] = domain.purpose;
+    out[SHIELD_DOMAIN_TICKET_CLASS_OFFSET] = domain.ticket_class;
+    out[SHIELD_DOMAIN_LEN..].copy_from_slice(&serialize_shield_payload_v0(payload));
+
+    out
+}
+
+#[cfg(not(target_os = "solana"))]
+pub fn verify_oracle_signature(
+    payload: &ShieldPayloadV0,
+    domain: &SigningDomain,
+    oracle_signature: &[u8; 64],
+    oracle_pubkey: &[u8; 32],
+) -> Result<()> {
+    use ed25519_dalek::{PublicKey as DalekPublicKey, Signature as DalekSignature, Verifier};
+
+    let public = DalekPublicKey::from_bytes(oracle_pubkey)
+        .map_err(|_| error!(UnforgivenV2Error::InvalidOraclePubkey))?;
+    let signature = DalekSignature::from_bytes(oracle_signature)
+        .map_err(|_| error!(UnforgivenV2Error::InvalidOracleSignature))?;
+    let message = serialize_shield_message_v0(payload, domain);
+
+    public
+        .verify(&message, &signature)
+        .map_err(|_| error!(UnforgivenV2Error::InvalidOracleSignature))
+}
+
+#[cfg(target_os = "solana")]
+pub fn verify_oracle_signature(
+    _payload: &ShieldPayloadV0,
+    _domain: &SigningDomain,
+    _oracle_signature: &[u8; 64],
+    _oracle_pubkey: &[u8; 32],
+) -> Result<()> {
+    Ok(())
+}
+
+pub fn validate_preview_request_fields(
+    payload: &ShieldPayloadV0,
+    admin_config: &AdminConfig,
+    sale_config: &SaleConfig,
+    user_key: &Pubkey,
+    now: i64,
+) -> Result<()> {
+    validate_attestation_fields(payload, admin_config, user_key, now)?;
+    require!(
+        adapter_mask_satisfies(
+            payload.adapter_mask,
+            sale_config.required_adapter_mask,
+            sale_config.forbidden_adapter_mask,
+        ),
+        UnforgivenV2Error::AdapterMaskMismatch
+    );
+    Ok(())
+}
+
+// Sale-independent payload checks, shared by flows that have no `SaleConfig`.
+pub fn validate_attestation_fields(
+    payload: &ShieldPayloadV0,
+    admin_config: &AdminConfig,
+    user_key: &Pubkey,
+    now: i64,
+) -> Result<()> {
+    require!(
+        payload.policy_version == POLICY_VERSION_V0,
+        UnforgivenV2Error::InvalidPolicyVersion
+    );
+    require!(payload.dignity_score <= 100, UnforgivenV2Error::InvalidDignityScore);
+    require!(
+        payload.sales_velocity_bps > -10_000,
+        UnforgivenV2Error::InvalidSalesVelocity
+    );
+    require!(
+        payload.user_mode <= USER_MODE_VERIFIED,
+        UnforgivenV2Error::InvalidUserMode
+    );
+    require!(
+        payload.time_elapsed <= MAX_TIME_ELAPSED_SECS,
+        UnforgivenV2Error::InvalidTimeElapsed
+    );
+    require!(
+        payload.attestation_expiry > now,
+        UnforgivenV2Error::AttestationExpired
+    );
+    require!(
+        payload.scoring_model_hash == admin_config.active_scoring_model_hash,
+        UnforgivenV2Error::ScoringModelHashMismatch
+    );
+    require!(
+        zk_provider_allowed(&admin_config.zk_provider_allowlist, payload.zk_provider),
+        UnforgivenV2Error::ZkProviderNotAllowed
+    );
+
+    let payload_user = Pubkey::new_from_array(payload.user_pubkey);
+    require_keys_eq!(payload_user, *user_key, UnforgivenV2Error::UserPubkeyMismatch);
+    Ok(())
+}
+
+pub fn zk_provider_allowed(allowlist: &[u8; ZK_PROVIDER_ALLOWLIST_LEN], zk_provider: u8) -> bool {
+    allowlist[usize::from(zk_provider / 8)] & (1 << (zk_provider % 8)) != 0
+}
+
+pub fn set_zk_provider_bit(
+    allowlist: &mut [u8; ZK_PROVIDER_ALLOWLIST_LEN],
+    zk_provider: u8,
+    allowed: bool,
+) {
+    let byte = &mut allowlist[usize::from(zk_provider / 8)];
+    let bit = 1 << (zk_provider % 8);
+    if allowed {
+        *byte |= bit;
+    } else {
+        *byte &= !bit;
+    }
+}
+
+pub fn purchase_commitment_hash(payload: &ShieldPayloadV0, salt: &[u8; 32]) -> [u8; 32] {
+    hashv(&[&serialize_shield_payload_v0(payload), salt]).to_bytes()
+}
+
+pub fn ensure_reveal_window(
+    slot: u64,
+    reveal_after_slot: u64,
+    reveal_deadline_slot: u64,
+) -> Result<()> {
+    require!(slot >= reveal_after_slot, UnforgivenV2Error::RevealTooEarly);
+    require!(
+        slot <= reveal_deadline_slot,
+        UnforgivenV2Error::RevealWindowClosed
+    );
+    Ok(())
+}
+
+pub fn split_commit_deposit(deposit_lamports: u64, forfeit_bps: u16) -> Result<(u64, u64)> {
+    let forfeited = (deposit_lamports as u128)
+        .checked_mul(forfeit_bps as u128)
+        .map(|value| value / 10_000)
+        .and_then(|value| u64::try_from(value).ok())
+        .filter(|value| *value <= deposit_lamports)
+        .ok_or(error!(UnforgivenV2Error::InvalidCommitRevealParams))?;
+    Ok((forfeited, deposit_lamports - forfeited))
+}
+
+pub fn ensure_open_allocation(sale_config: &SaleConfig) -> Result<()> {
+    match sale_config.allocation_mode {
+        ALLOCATION_MODE_OPEN => Ok(()),
+        ALLOCATION_MODE_SEALED_BID => err!(UnforgivenV2Error::SealedBidAllocationOnly),
+        _ => err!(UnforgivenV2Error::LotteryAllocationOnly),
+    }
+}
+
+pub fn entry_secret_commitment(mint: &Pubkey, holder: &Pubkey, secret: &[u8; 32]) -> [u8; 32] {
+    hashv(&[mint.as_ref(), holder.as_ref(), secret]).to_bytes()
+}
+
+// Scanner-side check: the secret must match the holder's latest rotation, and that
+// rotation must be at most `max_age_secs` old. A reset (sale or transfer) or a redeemed
+// ticket never verifies.
+pub fn verify_entry_secret(
+    receipt: &TicketReceipt,
+    secret: &[u8; 32],
+    now: i64,
+    max_age_secs: i64,
+) -> bool {
+    receipt.entry_secret_commitment != [0u8; 32]
+        && !receipt.listed
+        && receipt.redeemed_at == 0
+        && now.saturating_sub(receipt.entry_secret_rotated_at) <= max_age_secs
+        && entry_secret_commitment(&receipt.mint, &receipt.current_holder, secret)
+            == receipt.entry_secret_commitment
+}
+
+pub fn sealed_bid_commitment(bidder: &Pubkey, amount: u64, salt: &[u8; 32]) -> [u8; 32] {
+    hashv(&[bidder.as_ref(), &amount.to_le_bytes(), salt]).to_bytes()
+}
+
+// Keeps `board` sorted by amount, highest first; equal amounts keep reveal order.
+// Returns the new length, capped at `board.len()`.
+pub fn insert_sealed_bid_rank(board: &mut [SealedBidRank], len: u8, entry: SealedBidRank) -> u8 {
+    let len = (len as usize).min(board.len());
+    let position = board[..len]
+        .iter()
+        .position(|rank| rank.amount < entry.amount)
+        .unwrap_or(len);
+    if position >= board.len() {
+        return len as u8;
+    }
+    let new_len = (len + 1).min(board.len());
+    for index in (position + 1..new_len).rev() {
+        board[index] = board[index - 1];
+    }
+    board[position] = entry;
+    new_len as u8
+}
+
+pub fn sealed_bid_is_winner(auction: &SealedBidAuction, bid: &Pubkey) -> bool {
+    let winners = auction.board_len.min(auction.winner_slots) as usize;
+    auction.board[..winners].iter().any(|rank| rank.bid == *bid)
+}
+
+// What an unclaimed bid forfeits on refund: nothing for a loser, the clearing price for a
+// winner past `claim_end`. Winners still inside the claim window must claim instead.
+pub fn sealed_bid_forfeit(auction: &SealedBidAuction, bid: &Pubkey, now: i64) -> Result<u64> {
+    if !sealed_bid_is_winner(auction, bid) {
+        return Ok(0);
+    }
+    require!(
+        now >= auction.claim_end,
+        UnforgivenV2Error::SealedBidWinnerMustClaim
+    );
+    Ok(sealed_bid_clearing_price(auction))
+}
+
+// Uniform price: every winner pays the highest losing bid, or the reserve if nobody lost.
+pub fn sealed_bid_clearing_price(auction: &SealedBidAuction) -> u64 {
+    if auction.board_len > auction.winner_slots {
+        auction.board[auction.winner_slots as usize]
+            .amount
+            .max(auction.reserve_price)
+    } else {
+        auction.reserve_price
+    }
+}
+
+pub fn lottery_weight(dignity_score: u8) -> u8 {
+    dignity_bucket(dignity_score) + 1
+}
+
+pub fn lottery_seed_commitment(seed: &[u8; 32]) -> [u8; 32] {
+    hashv(&[seed]).to_bytes()
+}
+
+pub fn lottery_randomness(
+    lottery: &Pubkey,
+    seed: &[u8; 32],
+    slot_hash: &[u8; 32],
+    entry_count: u32,
+    total_weight: u64,
+) -> [u8; 32] {
+    hashv(&[
+        lottery.as_ref(),
+        seed,
+        slot_hash,
+        &entry_count.to_le_bytes(),
+        &total_weight.to_le_bytes(),
+    ])
+    .to_bytes()
+}
+
+// A uniform 64-bit draw from the randomness and proof hash, scaled by the entry's weight:
+// a weight-5 entry draws from a range five times wider than a weight-1 entry.
+pub fn lottery_entry_score(randomness: &[u8; 32], zk_proof_hash: &[u8; 32], weight: u8) -> u128 {
+    let digest = hashv(&[randomness, zk_proof_hash]).to_bytes();
+    let mut draw = [0u8; 8];
+    draw.copy_from_slice(&digest[..8]);
+    u64::from_le_bytes(draw) as u128 * weight as u128
+}
+
+// Keeps `board` sorted by score, highest first, with ties broken by proof hash, so the
+// final top-K does not depend on the order entries were ranked in.
+// Returns the new length, capped at `board.len()`.
+pub fn insert_lottery_rank(board: &mut [LotteryRank], len: u8, entry: LotteryRank) -> u8 {
+    let len = (len as usize).min(board.len());
+    let position = board[..len]
+        .iter()
+        .position(|rank| (rank.score, rank.zk_proof_hash) < (entry.score, entry.zk_proof_hash))
+        .unwrap_or(len);
+    if position >= board.len() {
+        return len as u8;
+    }
+    let new_len = (len + 1).min(board.len());
+    for index in (position + 1..new_len).rev() {
+        board[index] = board[index - 1];
+    }
+    board[position] = entry;
+    new_len as u8
+}
+
+pub fn lottery_is_winner(lottery: &Lottery, zk_proof_hash: &[u8; 32]) -> bool {
+    let winners = (lottery.board_len as u32).min(lottery.winner_slots) as usize;
+    lottery.board[..winners]
+        .iter()
+        .any(|rank| rank.zk_proof_hash == *zk_proof_hash)
+}
+
+// SlotHashes is `len: u64` followed by `(slot: u64, hash: [u8; 32])` entries, newest first.
+// Returns the hash of the first block at or after `slot` once the sysvar still covers it.
+pub fn slot_hash_at_or_after(data: &[u8], slot: u64) -> Option<[u8; 32]> {
+    let mut len = [0u8; 8];
+    len.copy_from_slice(data.get(..8)?);
+    let len = u64::from_le_bytes(len) as usize;
+
+    let mut found = None;
+    for index in 0..len {
+        let start = 8 + index * SLOT_HASH_ENTRY_LEN;
+        let entry = data.get(start..start + SLOT_HASH_ENTRY_LEN)?;
+        let mut entry_slot = [0u8; 8];
+        entry_slot.copy_from_slice(&entry[..8]);
+        let entry_slot = u64::from_le_bytes(entry_slot);
+        if entry_slot < slot {
+            return found;
+        }
+        let mut hash = [0u8; 32];
+        hash.copy_from_slice(&entry[8..]);
+        if entry_slot == slot {
+            return Some(hash);
+        }
+        found = Some(hash);
+    }
+    None
+}
+
+pub fn adapter_mask_satisfies(adapter_mask: u8, required: u8, forbidden: u8) -> bool {
+    adapter_mask & required == required && adapter_mask & forbidden == 0
+}
+
+pub fn ensure_user_mode_action(
+    sale_config: &SaleConfig,
+    user_mode: u8,
+    action: u8,
+) -> Result<UserModePolicy> {
+    let policy = *sale_config
+        .mode_policies
+        .get(user_mode as usize)
+        .ok_or(error!(UnforgivenV2Error::InvalidUserMode))?;
+    require!(
+        policy.allowed_actions & action == action,
+        UnforgivenV2Error::UserModeActionForbidden
+    );
+    Ok(policy)
+}
+
+pub fn enforce_user_mode_policy(
+    sale_config: &SaleConfig,
+    user_mode: u8,
+    dignity_score: u8,
+    action: u8,
+) -> Result<UserModePolicy> {
+    let policy = ensure_user_mode_action(sale_config, user_mode, action)?;
+    require!(
+        dignity_score >= policy.dignity_floor,
+        UnforgivenV2Error::DignityBelowModeFloor
+    );
+    Ok(policy)
+}
+
+// The bot cap is applied after the mode multiplier so no policy can price above it.
+pub fn apply_price_multiplier(
+    quote: VrgdaQuote,
+    multiplier_bps: u16,
+    reference_price: u64,
+) -> VrgdaQuote {
+    if quote.is_infinite || multiplier_bps == PRICE_MULTIPLIER_BASE_BPS {
+        return quote;
+    }
+
+    let scaled = (u128::from(quote.final_price).saturating_mul(u128::from(multiplier_bps))
+        / u128::from(PRICE_MULTIPLIER_BASE_BPS))
+    .min(bot_price_cap(reference_price));
+    match u64::try_from(scaled) {
+        Ok(price) => VrgdaQuote {
+            final_price: price.max(1),
+            ..quote
+        },
+        Err(_) => VrgdaQuote {
+            final_price: u64::MAX,
+            is_infinite: true,
+            blocked: true,
+            ..quote
+        },
+    }
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Result<u16> {
+    let bytes = data
+        .get(offset..offset + 2)
+        .ok_or(error!(UnforgivenV2Error::InvalidEd25519Instruction))?;
+    Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+pub fn verify_ed25519_ix(
+    ix_sysvar: &AccountInfo<'_>,
+    sig_instruction_index: u16,
+    message: &[u8],
+    oracle_signature: &[u8],
+    oracle_pubkey: &[u8; 32],
+) -> Result<()> {
+    let ix = load_instruction_at_checked(sig_instruction_index as usize, ix_sysvar)
+        .map_err(|_| error!(UnforgivenV2Error::MissingEd25519Instruction))?;
+    require!(
+        ix.program_id == ed25519_program::id(),
+        UnforgivenV2Error::InvalidEd25519Instruction
+    );
+
+    verify_ed25519_ix_data(
+        &ix.data,
+        sig_instruction_index,
+        message,
+        oracle_signature,
+        oracle_pubkey,
+    )
+}
+
+pub fn verify_ed25519_ix_data(
+    data: &[u8],
+    sig_instruction_index: u16,
+    message: &[u8],
+    oracle_signature: &[u8],
+    oracle_pubkey: &[u8; 32],
+) -> Result<()> {
+    require!(
+        data.len() >= ED25519_OFFSETS_START,
+        UnforgivenV2Error::InvalidEd25519Instruction
+    );
+    let num_signatures = data[0] as usize;
+    require!(
+        num_signatures > 0
+            && data.len() >= ED25519_OFFSETS_START + num_signatures * ED25519_OFFSETS_SIZE,
+        UnforgivenV2Error::InvalidEd25519Instruction
+    );
+
+    let mut pubkey_seen = false;
+    for entry in 0..num_signatures {
+        let base = ED25519_OFFSETS_START + entry * ED25519_OFFSETS_SIZE;
+        let sig_offset = read_u16(data, base)? as usize;
+        let sig_ix_index = read_u16(data, base + 2)?;
+        let pubkey_offset = read_u16(data, base + 4)? as usize;
+        let pubkey_ix_index = read_u16(data, base + 6)?;
+        let msg_offset = read_u16(data, base + 8)? as usize;
+        let msg_len = read_u16(data, base + 10)? as usize;
+        let msg_ix_index = read_u16(data, base + 12)?;
+
+        let is_local = |index: u16| index == u16::MAX || index == sig_instruction_index;
+        require!(
+            is_local(sig_ix_index) && is_local(pubkey_ix_index) && is_local(msg_ix_index),
+            UnforgivenV2Error::InvalidEd25519Instruction
+        );
+
+        let sig = data
+            .get(sig_offset..sig_offset + ED25519_SIGNATURE_LEN)
+            .ok_or(error!(UnforgivenV2Error::InvalidEd25519Instruction))?;
+        let pk = data
+            .get(pubkey_offset..pubkey_offset + ED25519_PUBKEY_LEN)
+            .ok_or(error!(UnforgivenV2Error::InvalidEd25519Instruction))?;
+        let msg = data
+            .get(msg_offset..msg_offset + msg_len)
+            .ok_or(error!(UnforgivenV2Error::InvalidEd25519Instruction))?;
+
+        pubkey_seen |= pk == oracle_pubkey;
+        if sig != oracle_signature {
+            continue;
+        }
+
+        require!(
+            pk == oracle_pubkey,
+            UnforgivenV2Error::Ed25519PubkeyMismatch
+        );
+        require!(msg == message, UnforgivenV2Error::Ed25519MessageMismatch);
+        return Ok(());
+    }
+
+    if pubkey_seen {
+        return err!(UnforgivenV2Error::Ed25519SignatureMismatch);
+    }
+    err!(UnforgivenV2Error::Ed25519PubkeyMismatch)
+}
+
+pub fn deserialize_shield_payload_v0(data: &[u8]) -> Result<ShieldPayloadV0> {
+    require!(
+        data.len() == SHIELD_PAYLOAD_V0_LEN,
+        UnforgivenV2Error::InvalidEd25519Instruction
+    );
+
+    Ok(ShieldPayloadV0 {
+        policy_version: data[0],
+        user_pubkey: data[1..33]
+            .try_into()
+            .map_err(|_| error!(UnforgivenV2Error::InvalidEd25519Instruction))?,
+        initial_price: u64::from_le_bytes(
+            data[33..41]
+                .try_into()
+                .map_err(|_| error!(UnforgivenV2Error::InvalidEd25519Instruction))?,
+        ),
+        sales_velocity_bps: i64::from_le_bytes(
+            data[41..49]
+                .try_into()
+                .map_err(|_| error!(UnforgivenV2Error::InvalidEd25519Instruction))?,
+        ),
+        time_elapsed: u64::from_le_bytes(
+            data[49..57]
+                .try_into()
+                .map_err(|_| error!(UnforgivenV2Error::InvalidEd25519Instruction))?,
+        ),
+        dignity_score: data[57],
+        adapter_mask: data[58],
+        user_mode: data[59],
+        zk_provider: data[60],
+        zk_proof_hash: data[61..93]
+            .try_into()
+            .map_err(|_| error!(UnforgivenV2Error::InvalidEd25519Instruction))?,
+        scoring_model_hash: data[93..125]
+            .try_into()
+            .map_err(|_| error!(UnforgivenV2Error::InvalidEd25519Instruction))?,
+        attestation_expiry: i64::from_le_bytes(
+            data[125..133]
+                .try_into()
+                .map_err(|_| error!(UnforgivenV2Error::InvalidEd25519Instruction))?,
+        ),
+        nonce: u64::from_le_bytes(
+            data[133..141]
+                .try_into()
+                .map_err(|_| error!(UnforgivenV2Error::InvalidEd25519Instruction))?,
+        ),
+    })
+}
+
+pub fn quote_from_payload(payload: &ShieldPayloadV0) -> Result<VrgdaQuote> {
+    let input = VrgdaInput {
+        initial_price: payload.initial_price,
+        sales_velocity_bps: payload.sales_velocity_bps,
+        time_elapsed: payload.time_elapsed,
+        dignity_score: payload.dignity_score,
+    };
+
+    calculate_vrgda_quote(input).map_err(map_math_error)
+}
+
+pub fn validate_pricing_strategy(
+    pricing_strategy: u8,
+    dutch_start_price: u64,
+    dutch_floor_price: u64,
+    dutch_step_amount: u64,
+    dutch_step_interval_secs: u64,
+) -> Result<()> {
+    match pricing_strategy {
+        PRICING_STRATEGY_VRGDA => Ok(()),
+        PRICING_STRATEGY_DUTCH => {
+            require!(
+                dutch_step_amount > 0
+                    && dutch_step_interval_secs > 0
+                    && dutch_start_price > 0
+                    && dutch_floor_price <= dutch_start_price,
+                UnforgivenV2Error::InvalidPricingStrategy
+            );
+            Ok(())
+        }
+        _ => err!(UnforgivenV2Error::InvalidPricingStrategy),
+    }
+}
+
+// The Dutch curve starts at the sale's `dutch_start_price`; the oracle has to attest the
+// same price, as it does a class's base price.
+pub fn quote_for_sale(payload: &ShieldPayloadV0, sale_config: &SaleConfig) -> Result<VrgdaQuote> {
+    if sale_config.pricing_strategy == PRICING_STRATEGY_DUTCH {
+        require!(
+            payload.initial_price == sale_config.dutch_start_price,
+            UnforgivenV2Error::DutchStartPriceMismatch
+        );
+    }
+    quote_for_curve(payload, sale_config)
+}
+
+// Prices `payload` on the sale's curve, with `payload.initial_price` as the start price.
+fn quote_for_curve(payload: &ShieldPayloadV0, sale_config: &SaleConfig) -> Result<VrgdaQuote> {
+    match sale_config.pricing_strategy {
+        PRICING_STRATEGY_VRGDA => quote_from_payload(payload),
+        PRICING_STRATEGY_DUTCH => calculate_dutch_quote(DutchAuctionInput {
+            start_price: payload.initial_price,
+            floor_price: sale_config.dutch_floor_price,
+            step_amount: sale_config.dutch_step_amount,
+            step_interval_secs: sale_config.dutch_step_interval_secs,
+            sales_velocity_bps: payload.sales_velocity_bps,
+            time_elapsed: payload.time_elapsed,
+            dignity_score: payload.dignity_score,
+        })
+        .map_err(map_math_error),
+        _ => err!(UnforgivenV2Error::InvalidPricingStrategy),
+    }
+}
+
+// A class pins the start price on-chain and scales the attested velocity for its own curve.
+pub fn quote_for_class(
+    payload: &ShieldPayloadV0,
+    sale_config: &SaleConfig,
+    ticket_class: Option<&TicketClass>,
+) -> Result<VrgdaQuote> {
+    let Some(class) = ticket_class else {
+        // Once a sale has classes, a class-less quote would price off an unconfigured base.
+        require!(
+            sale_config.ticket_class_count == 0,
+            UnforgivenV2Error::TicketClassRequired
+        );
+        return quote_for_sale(payload, sale_config);
+    };
+    require!(
+        payload.initial_price == class.base_price,
+        UnforgivenV2Error::TicketClassPriceMismatch
+    );
+    let sales_velocity_bps = (payload.sales_velocity_bps as i128)
+        .checked_mul(class.velocity_multiplier_bps as i128)
+        .map(|value| value / PRICE_MULTIPLIER_BASE_BPS as i128)
+        .and_then(|value| i64::try_from(value).ok())
+        .ok_or(error!(UnforgivenV2Error::InvalidSalesVelocity))?;
+    let class_payload = ShieldPayloadV0 {
+        sales_velocity_bps,
+        ..payload.clone()
+    };
+    quote_for_curve(&class_payload, sale_config)
+}
+
+pub fn ticket_class_id(ticket_class: Option<&TicketClass>) -> u8 {
+    ticket_class.map_or(TICKET_CLASS_NONE, |class| class.class_id)
+}
+
+pub fn next_ticket_class_sale(sold: u32, supply: u32) -> Result<u32> {
+    require!(sold < supply, UnforgivenV2Error::TicketClassSoldOut);
+    Ok(sold + 1)
+}
+
+fn map_math_error(err: VrgdaMathError) -> Error {
+    match err {
+        VrgdaMathError::InvalidDignityScore => error!(UnforgivenV2Error::InvalidDignityScore),
+        VrgdaMathError::InvalidSalesVelocity => error!(UnforgivenV2Error::InvalidSalesVelocity),
+        VrgdaMathError::InvalidTimeElapsed => error!(UnforgivenV2Error::InvalidTimeElapsed),
+        VrgdaMathError::InvalidDutchSchedule => error!(UnforgivenV2Error::InvalidPricingStrategy),
+    }
+}
+
+pub fn preview_event_from_payload(
+    payload: &ShieldPayloadV0,
+    oracle_signature: &[u8; 64],
+    admin_config: &AdminConfig,
+    sale_config: &SaleConfig,
+    user_key: &Pubkey,
+    now: i64,
+) -> Result<PreviewPriceEvent> {
+    preview_event_for_class(
+        payload,
+        oracle_signature,
+        admin_config,
+        sale_config,
+        None,
+        user_key,
+        now,
+    )
+    .map(|(event, _)| event)
+}
+
+// Returns the event `preview_price` emits together with the quote it sets as return data.
+
+pub fn preview_event_for_class(
+    payload: &ShieldPayloadV0,
+    oracle_signature: &[u8; 64],
+    admin_config: &AdminConfig,
+    sale_config: &SaleConfig,
+    ticket_class: Option<&TicketClass>,
+    user_key: &Pubkey,
+    now: i64,
+) -> Result<(PreviewPriceEvent, QuoteReturnV0)> {
+    validate_preview_request_fields(payload, admin_config, sale_config, user_key, now)?;
+    verify_oracle_signature(
+        payload,
+        &shield_signing_domain(
+            admin_config,
+            SIGNING_PURPOSE_PREVIEW,
+            ticket_class_id(ticket_class),
+        ),
+        oracle_signature,
+        &admin_config.oracle_pubkey,
+    )?;
+    preview_outcome(payload, sale_config, ticket_class)
+}
+
+fn preview_outcome(
+    payload: &ShieldPayloadV0,
+    sale_config: &SaleConfig,
+    ticket_class: Option<&TicketClass>,
+) -> Result<(PreviewPriceEvent, QuoteReturnV0)> {
+    let policy = enforce_user_mode_policy(
+        sale_config,
+        payload.user_mode,
+        payload.dignity_score,
+        MODE_ACTION_PREVIEW,
+    )?;
+    let quote = apply_price_multiplier(
+        quote_for_class(payload, sale_config, ticket_class)?,
+        policy.price_multiplier_bps,
+        payload.initial_price,
+    );
+    let event = PreviewPriceEvent {
+        final_price: quote.final_price,
+        is_infinite: quote.is_infinite,
+        blocked: quote.blocked,
+        effective_velocity_bps: quote.effective_velocity_bps,
+        dignity_score: payload.dignity_score,
+        adapter_mask: payload.adapter_mask,
+        dignity_bucket: dignity_bucket(payload.dignity_score),
+        user_mode: payload.user_mode,
+    };
+    Ok((
+        event,
+        quote_return_v0(SIGNING_PURPOSE_PREVIEW, payload, &quote),
+    ))
+}
+
+pub fn execution_event_from_payload(
+    payload: &ShieldPayloadV0,
+    oracle_signature: &[u8; 64],
+    admin_config: &AdminConfig,
+    sale_config: &SaleConfig,
+    user_key: &Pubkey,
+    now: i64,
+) -> Result<ShieldExecutionEvent> {
+    execution_event_for_class(
+        payload,
+        oracle_signature,
+        admin_config,
+        sale_config,
+        None,
+        user_key,
+        now,
+    )
+}
+
+pub fn execution_event_for_class(
+    payload: &ShieldPayloadV0,
+    oracle_signature: &[u8; 64],
+    admin_config: &AdminConfig,
+    sale_config: &SaleConfig,
+    ticket_class: Option<&TicketClass>,
+    user_key: &Pubkey,
+    now: i64,
+) -> Result<ShieldExecutionEvent> {
+    validate_preview_request_fields(payload, admin_config, sale_config, user_key, now)?;
+    verify_oracle_signature(
+        payload,
+        &shield_signing_domain(
+            admin_config,
+            SIGNING_PURPOSE_EXECUTE,
+            ticket_class_id(ticket_class),
+        ),
+        oracle_signature,
+        &admin_config.oracle_pubkey,
+    )?;
+
+    let quote = shield_purchase_quote(payload, sale_config, ticket_class)?;
+    require!(!quote.blocked, UnforgivenV2Error::ShieldBlocked);
+
+    Ok(ShieldExecutionEvent {
+        final_price: quote.final_price,
+        blocked: quote.blocked,
+        effective_velocity_bps: quote.effective_velocity_bps,
+        dignity_score: payload.dignity_score,
+        adapter_mask: payload.adapter_mask,
+        user_mode: payload.user_mode,
+        nonce: payload.nonce,
+        zk_proof_hash: payload.zk_proof_hash,
+    })
+}
+
+fn dignity_bucket(score: u8) -> u8 {
+    match score {
+        0..=20 => 0,
+        21..=40 => 1,
+        41..=60 => 2,
+        61..=80 => 3,
+        _ => 4,
+    }
+}
+
+#[derive(Accounts)]
+pub struct InitializeV2<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::UnforgivenV2>,
+    #[account(constraint = program_data.upgrade_authority_address == Some(authority.key()))]
+    pub program_data: Account<'info, ProgramData>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + GlobalConfigV2::INIT_SPACE,
+        seeds = [b"global_v2"],
+        bump
+    )]
+    pub global_config_v2: Account<'info, GlobalConfigV2>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + AuditLog::INIT_SPACE,
+        seeds = [AUDIT_LOG_SEED],
+        bump
+    )]
+    pub audit_log: Box<Account<'info, AuditLog>>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeAdminConfig<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::UnforgivenV2>,
+    #[account(constraint = program_data.upgrade_authority_address == Some(authority.key()))]
+    pub program_data: Account<'info, ProgramData>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + AdminConfig::INIT_SPACE,
+        seeds = [b"admin_config_v2"],
+        bump
+    )]
+    pub admin_config: Account<'info, AdminConfig>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED],
+        bump = audit_log.bump,
+    )]
+    pub audit_log: Box<Account<'info, AuditLog>>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateGlobalConfig<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(mut, seeds = [b"global_v2"], bump)]
+    /// CHECK: May predate the current layout; checked and grown in the handler.
+    pub global_config_v2: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED],
+        bump = audit_log.bump,
+    )]
+    pub audit_log: Box<Account<'info, AuditLog>>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateAdminConfig<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(mut, seeds = [b"admin_config_v2"], bump)]
+    /// CHECK: May predate the current layout; checked and grown in the handler.
+    pub admin_config: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED],
+        bump = audit_log.bump,
+    )]
+    pub audit_log: Box<Account<'info, AuditLog>>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateTicketReceipt<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(mut)]
+    /// CHECK: Owner and discriminator are checked in the handler.
+    pub ticket_receipt: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeAuditLog<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(seeds = [b"admin_config_v2"], bump)]
+    /// CHECK: May predate the current layout; the handler checks the stored authority.
+    pub admin_config: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + AuditLog::INIT_SPACE,
+        seeds = [AUDIT_LOG_SEED],
+        bump
+    )]
+    pub audit_log: Box<Account<'info, AuditLog>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AdminOnly<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"admin_config_v2"],
+        bump = admin_config.bump,
+    )]
+    pub admin_config: Account<'info, AdminConfig>,
+
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED],
+        bump = audit_log.bump,
+    )]
+    pub audit_log: Box<Account<'info, AuditLog>>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeSaleConfig<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        has_one = authority,
+        seeds = [b"admin_config_v2"],
+        bump = admin_config.bump,
+    )]
+    pub admin_config: Account<'info, AdminConfig>,
+
+    #[account(
+        seeds = [b"global_v2"],
+        bump = global_config_v2.bump,
+    )]
+    pub global_config_v2: Account<'info, GlobalConfigV2>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + SaleConfig::INIT_SPACE,
+        seeds = [SALE_CONFIG_SEED, global_config_v2.key().as_ref()],
+        bump
+    )]
+    pub sale_config: Account<'info, SaleConfig>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED],
+        bump = audit_log.bump,
+    )]
+    pub audit_log: Box<Account<'info, AuditLog>>,
+}
+
+#[derive(Accounts)]
+#[instruction(zk_proof_hash: [u8; 32])]
+pub struct InitializeProofQuota<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        seeds = [SALE_CONFIG_SEED, sale_config.event_key.as_ref()],
+        bump = sale_config.bump,
+    )]
+    pub sale_config: Account<'info, SaleConfig>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + ProofQuota::INIT_SPACE,
+        seeds = [PROOF_QUOTA_SEED, sale_config.event_key.as_ref(), zk_proof_hash.as_ref()],
+        bump,
+    )]
+    pub proof_quota: Account<'info, ProofQuota>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SaleAdminOnly<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        has_one = authority,
+        seeds = [b"admin_config_v2"],
+        bump = admin_config.bump,
+    )]
+    pub admin_config: Account<'info, AdminConfig>,
+
+    #[account(
+        mut,
+        seeds = [SALE_CONFIG_SEED, sale_config.event_key.as_ref()],
+        bump = sale_config.bump,
+    )]
+    pub sale_config: Account<'info, SaleConfig>,
+
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED],
+        bump = audit_log.bump,
+    )]
+    pub audit_log: Box<Account<'info, AuditLog>>,
+}
+
+#[derive(Accounts)]
+#[instruction(class_id: u8)]
+pub struct InitializeTicketClass<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        has_one = authority,
+        seeds = [b"admin_config_v2"],
+        bump = admin_config.bump,
+    )]
+    pub admin_config: Account<'info, AdminConfig>,
+
+    #[account(
+        mut,
+        seeds = [SALE_CONFIG_SEED, sale_config.event_key.as_ref()],
+        bump = sale_config.bump,
+    )]
+    pub sale_config: Account<'info, SaleConfig>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + TicketClass::INIT_SPACE,
+        seeds = [TICKET_CLASS_SEED, sale_config.event_key.as_ref(), &[class_id]],
+        bump
+    )]
+    pub ticket_class: Account<'info, TicketClass>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED],
+        bump = audit_log.bump,
+    )]
+    pub audit_log: Box<Account<'info, AuditLog>>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateTicketClass<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        has_one = authority,
+        seeds = [b"admin_config_v2"],
+        bump = admin_config.bump,
+    )]
+    pub admin_config: Account<'info, AdminConfig>,
+
+    #[account(
+        mut,
+        seeds = [TICKET_CLASS_SEED, ticket_class.event_key.as_ref(), &[ticket_class.class_id]],
+        bump = ticket_class.bump,
+    )]
+    pub ticket_class: Account<'info, TicketClass>,
+
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED],
+        bump = audit_log.bump,
+    )]
+    pub audit_log: Box<Account<'info, AuditLog>>,
+}
+
+#[derive(Accounts)]
+pub struct PreviewPrice<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [b"admin_config_v2"],
+        bump = admin_config.bump,
+    )]
+    pub admin_config: Account<'info, AdminConfig>,
+
+    #[account(address = instructions::ID)]
+    /// CHECK: Address constraint guarantees this is the instructions sysvar.
+    pub instructions: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [SALE_CONFIG_SEED, sale_config.event_key.as_ref()],
+        bump = sale_config.bump,
+    )]
+    pub sale_config: Account<'info, SaleConfig>,
+
+    #[account(
+        seeds = [TICKET_CLASS_SEED, sale_config.event_key.as_ref(), &[ticket_class.class_id]],
+        bump = ticket_class.bump,
+    )]
+    pub ticket_class: Option<Box<Account<'info, TicketClass>>>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteShield<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [b"global_v2"],
+        bump = global_config_v2.bump,
+    )]
+    pub global_config_v2: Box<Account<'info, GlobalConfigV2>>,
+
+    #[account(mut, address = global_config_v2.authority)]
+    pub treasury: SystemAccount<'info>,
+
+    #[account(
+        seeds = [b"admin_config_v2"],
+        bump = admin_config.bump,
+    )]
+    pub admin_config: Box<Account<'info, AdminConfig>>,
+
+    #[account(address = instructions::ID)]
+    /// CHECK: Address constraint guarantees this is the instructions sysvar.
+    pub instructions: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    /// CHECK: PDA is derived and created inside the handler to avoid payload-heavy pre-handler work.
+    pub proof_use: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    /// CHECK: PDA is derived and created inside the handler to avoid payload-heavy pre-handler work.
+    pub ticket_mint: UncheckedAccount<'info>,
+
+    /// CHECK: PDA signer used only as mint authority; the handler checks it against `bumps`.
+    pub ticket_mint_authority: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    /// CHECK: PDA is derived and created inside the handler to avoid payload-heavy pre-handler work.
+    pub user_ticket_token: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    /// CHECK: PDA is derived and created inside the handler to avoid payload-heavy pre-handler work.
+    pub ticket_receipt: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+
+    #[account(
+        seeds = [SALE_CONFIG_SEED, global_config_v2.key().as_ref()],
+        bump = sale_config.bump,
+    )]
+    pub sale_config: Box<Account<'info, SaleConfig>>,
+
+    #[account(mut)]
+    /// CHECK: Existing `ProofQuota`; address and contents are checked in the handler.
+    pub proof_quota: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [TICKET_CLASS_SEED, global_config_v2.key().as_ref(), &[ticket_class.class_id]],
+        bump = ticket_class.bump,
+    )]
+    pub ticket_class: Option<Box<Account<'info, TicketClass>>>,
+
+    #[account(
+        seeds = [MEMBERSHIP_SEED, user.key().as_ref()],
+        bump = membership.bump,
+    )]
+    pub membership: Option<Box<Account<'info, Membership>>>,
+
+    // Address is checked against `bumps` in the handler.
+    #[account(mut)]
+    pub reputation: Box<Account<'info, Reputation>>,
+}
+
+#[derive(Accounts)]
+#[instruction(payload: ShieldPayloadV0)]
+pub struct ShieldGate<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [b"admin_config_v2"],
+        bump = admin_config.bump,
+    )]
+    pub admin_config: Box<Account<'info, AdminConfig>>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + ProofUse::INIT_SPACE,
+        seeds = [
+            b"proof_use",
+            user.key().as_ref(),
+            payload.zk_proof_hash.as_ref(),
+            payload.nonce.to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub proof_use: Box<Account<'info, ProofUse>>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(address = instructions::ID)]
+    /// CHECK: Address constraint guarantees this is the instructions sysvar.
+    pub instructions: UncheckedAccount<'info>,
+
+    // A gated proof draws on the same per-proof quota as ticket purchases for this sale.
+    #[account(
+        seeds = [SALE_CONFIG_SEED, sale_config.event_key.as_ref()],
+        bump = sale_config.bump,
+    )]
+    pub sale_config: Box<Account<'info, SaleConfig>>,
+
+    #[account(
+        mut,
+        seeds = [
+            PROOF_QUOTA_SEED,
+            sale_config.event_key.as_ref(),
+            payload.zk_proof_hash.as_ref(),
+        ],
+        bump = proof_quota.bump,
+    )]
+    pub proof_quota: Box<Account<'info, ProofQuota>>,
+
+    #[account(
+        seeds = [MEMBERSHIP_SEED, user.key().as_ref()],
+        bump = membership.bump,
+    )]
+    pub membership: Option<Box<Account<'info, Membership>>>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeLottery<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        has_one = authority,
+        seeds = [b"admin_config_v2"],
+        bump = admin_config.bump,
+    )]
+    pub admin_config: Account<'info, AdminConfig>,
+
+    #[account(
+        mut,
+        seeds = [SALE_CONFIG_SEED, sale_config.event_key.as_ref()],
+        bump = sale_config.bump,
+    )]
+    pub sale_config: Account<'info, SaleConfig>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Lottery::INIT_SPACE,
+        seeds = [LOTTERY_SEED, sale_config.event_key.as_ref()],
+        bump
+    )]
+    pub lottery: Account<'info, Lottery>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED],
+        bump = audit_log.bump,
+    )]
+    pub audit_log: Box<Account<'info, AuditLog>>,
+}
+
+#[derive(Accounts)]
+#[instruction(payload: ShieldPayloadV0)]
+pub struct RegisterWaitlist<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [b"admin_config_v2"],
+        bump = admin_config.bump,
+    )]
+    pub admin_config: Box<Account<'info, AdminConfig>>,
+
+    #[account(
+        seeds = [SALE_CONFIG_SEED, lottery.event_key.as_ref()],
+        bump = sale_config.bump,
+    )]
+    pub sale_config: Box<Account<'info, SaleConfig>>,
+
+    #[account(
+        mut,
+        seeds = [LOTTERY_SEED, lottery.event_key.as_ref()],
+        bump = lottery.bump,
+    )]
+    pub lottery: Box<Account<'info, Lottery>>,
+
+    #[account(
+        init,
+        payer = user,
+        space = 8 + WaitlistEntry::INIT_SPACE,
+        seeds = [WAITLIST_ENTRY_SEED, lottery.key().as_ref(), payload.zk_proof_hash.as_ref()],
+        bump
+    )]
+    pub waitlist_entry: Box<Account<'info, WaitlistEntry>>,
+
+    #[account(address = instructions::ID)]
+    /// CHECK: Address constraint guarantees this is the instructions sysvar.
+    pub instructions: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevealLotterySeed<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        has_one = authority,
+        seeds = [b"admin_config_v2"],
+        bump = admin_config.bump,
+    )]
+    pub admin_config: Account<'info, AdminConfig>,
+
+    #[account(
+        mut,
+        seeds = [LOTTERY_SEED, lottery.event_key.as_ref()],
+        bump = lottery.bump,
+    )]
+    pub lottery: Account<'info, Lottery>,
+}
+
+#[derive(Accounts)]
+pub struct DrawLottery<'info> {
+    #[account(
+        mut,
+        seeds = [LOTTERY_SEED, lottery.event_key.as_ref()],
+        bump = lottery.bump,
+    )]
+    pub lottery: Account<'info, Lottery>,
+
+    #[account(address = sysvar::slot_hashes::ID)]
+    /// CHECK: Address constraint guarantees this is the SlotHashes sysvar; it is read raw.
+    pub slot_hashes: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RankWaitlistEntry<'info> {
+    #[account(
+        mut,
+        seeds = [LOTTERY_SEED, lottery.event_key.as_ref()],
+        bump = lottery.bump,
+    )]
+    pub lottery: Box<Account<'info, Lottery>>,
+
+    #[account(
+        mut,
+        has_one = lottery,
+        seeds = [WAITLIST_ENTRY_SEED, lottery.key().as_ref(), waitlist_entry.zk_proof_hash.as_ref()],
+        bump = waitlist_entry.bump,
+    )]
+    pub waitlist_entry: Box<Account<'info, WaitlistEntry>>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimLotteryTicket<'info> {
+    pub shield: ExecuteShield<'info>,
+
+    #[account(
+        mut,
+        seeds = [LOTTERY_SEED, shield.global_config_v2.key().as_ref()],
+        bump = lottery.bump,
+    )]
+    pub lottery: Box<Account<'info, Lottery>>,
+
+    #[account(
+        mut,
+        has_one = lottery,
+        seeds = [WAITLIST_ENTRY_SEED, lottery.key().as_ref(), waitlist_entry.zk_proof_hash.as_ref()],
+        bump = waitlist_entry.bump,
+    )]
+    pub waitlist_entry: Box<Account<'info, WaitlistEntry>>,
+}
+
+#[derive(Accounts)]
+pub struct CommitPurchase<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [SALE_CONFIG_SEED, sale_config.event_key.as_ref()],
+        bump = sale_config.bump,
+    )]
+    pub sale_config: Box<Account<'info, SaleConfig>>,
+
+    #[account(
+        init,
+        payer = user,
+        space = 8 + PurchaseCommitment::INIT_SPACE,
+        seeds = [PURCHASE_COMMITMENT_SEED, sale_config.event_key.as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub purchase_commitment: Account<'info, PurchaseCommitment>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevealPurchase<'info> {
+    pub shield: ExecuteShield<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            PURCHASE_COMMITMENT_SEED,
+            shield.global_config_v2.key().as_ref(),
+            shield.user.key().as_ref(),
+        ],
+        bump = purchase_commitment.bump,
+    )]
+    pub purchase_commitment: Box<Account<'info, PurchaseCommitment>>,
+}
+
+#[derive(Accounts)]
+pub struct ForfeitPurchaseCommitment<'info> {
+    #[account(
+        seeds = [b"global_v2"],
+        bump = global_config_v2.bump,
+    )]
+    pub global_config_v2: Account<'info, GlobalConfigV2>,
+
+    #[account(mut, address = global_config_v2.authority)]
+    pub treasury: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub user: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        has_one = user,
+        seeds = [PURCHASE_COMMITMENT_SEED, global_config_v2.key().as_ref(), user.key().as_ref()],
+        bump = purchase_commitment.bump,
+    )]
+    pub purchase_commitment: Account<'info, PurchaseCommitment>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeSealedBidAuction<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        has_one = authority,
+        seeds = [b"admin_config_v2"],
+        bump = admin_config.bump,
+    )]
+    pub admin_config: Account<'info, AdminConfig>,
+
+    #[account(
+        mut,
+        seeds = [TICKET_CLASS_SEED, ticket_class.event_key.as_ref(), &[ticket_class.class_id]],
+        bump = ticket_class.bump,
+    )]
+    pub ticket_class: Box<Account<'info, TicketClass>>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + SealedBidAuction::INIT_SPACE,
+        seeds = [
+            SEALED_BID_AUCTION_SEED,
+            ticket_class.event_key.as_ref(),
+            &[ticket_class.class_id],
+        ],
+        bump
+    )]
+    pub auction: Box<Account<'info, SealedBidAuction>>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED],
+        bump = audit_log.bump,
+    )]
+    pub audit_log: Box<Account<'info, AuditLog>>,
+}
+
+#[derive(Accounts)]
+#[instruction(payload: ShieldPayloadV0)]
+pub struct CommitSealedBid<'info> {
+    #[account(mut)]
+    pub bidder: Signer<'info>,
+
+    #[account(
+        seeds = [b"admin_config_v2"],
+        bump = admin_config.bump,
+    )]
+    pub admin_config: Box<Account<'info, AdminConfig>>,
+
+    #[account(
+        seeds = [SALE_CONFIG_SEED, auction.event_key.as_ref()],
+        bump = sale_config.bump,
+    )]
+    pub sale_config: Box<Account<'info, SaleConfig>>,
+
+    #[account(
+        seeds = [
+            SEALED_BID_AUCTION_SEED,
+            auction.event_key.as_ref(),
+            &[auction.ticket_class],
+        ],
+        bump = auction.bump,
+    )]
+    pub auction: Box<Account<'info, SealedBidAuction>>,
+
+    #[account(
+        seeds = [TICKET_CLASS_SEED, auction.event_key.as_ref(), &[auction.ticket_class]],
+        bump = ticket_class.bump,
+    )]
+    pub ticket_class: Box<Account<'info, TicketClass>>,
+
+    #[account(
+        init,
+        payer = bidder,
+        space = 8 + SealedBid::INIT_SPACE,
+        seeds = [SEALED_BID_SEED, auction.key().as_ref(), payload.zk_proof_hash.as_ref()],
+        bump
+    )]
+    pub sealed_bid: Box<Account<'info, SealedBid>>,
+
+    #[account(address = instructions::ID)]
+    /// CHECK: Address constraint guarantees this is the instructions sysvar.
+    pub instructions: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(
+        seeds = [MEMBERSHIP_SEED, bidder.key().as_ref()],
+        bump = membership.bump,
+    )]
+    pub membership: Option<Box<Account<'info, Membership>>>,
+
+    #[account(
+        seeds = [PROOF_QUOTA_SEED, auction.event_key.as_ref(), payload.zk_proof_hash.as_ref()],
+        bump = proof_quota.bump,
+    )]
+    pub proof_quota: Box<Account<'info, ProofQuota>>,
+
+    #[account(
+        init,
+        payer = bidder,
+        space = 8 + ProofUse::INIT_SPACE,
+        seeds = [
+            b"proof_use",
+            bidder.key().as_ref(),
+            payload.zk_proof_hash.as_ref(),
+            payload.nonce.to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub proof_use: Box<Account<'info, ProofUse>>,
+}
+
+#[derive(Accounts)]
+pub struct RevealSealedBid<'info> {
+    pub bidder: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            SEALED_BID_AUCTION_SEED,
+            auction.event_key.as_ref(),
+            &[auction.ticket_class],
+        ],
+        bump = auction.bump,
+    )]
+    pub auction: Box<Account<'info, SealedBidAuction>>,
+
+    #[account(
+        mut,
+        has_one = auction,
+        has_one = bidder,
+        seeds = [SEALED_BID_SEED, auction.key().as_ref(), sealed_bid.zk_proof_hash.as_ref()],
+        bump = sealed_bid.bump,
+    )]
+    pub sealed_bid: Box<Account<'info, SealedBid>>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimSealedBidTicket<'info> {
+    #[account(mut)]
+    pub bidder: Signer<'info>,
+
+    #[account(
+        seeds = [b"global_v2"],
+        bump = global_config_v2.bump,
+    )]
+    pub global_config_v2: Box<Account<'info, GlobalConfigV2>>,
+
+    #[account(mut, address = global_config_v2.authority)]
+    pub treasury: SystemAccount<'info>,
+
+    #[account(
+        seeds = [
+            SEALED_BID_AUCTION_SEED,
+            global_config_v2.key().as_ref(),
+            &[auction.ticket_class],
+        ],
+        bump = auction.bump,
+    )]
+    pub auction: Box<Account<'info, SealedBidAuction>>,
+
+    #[account(
+        mut,
+        seeds = [TICKET_CLASS_SEED, global_config_v2.key().as_ref(), &[auction.ticket_class]],
+        bump = ticket_class.bump,
+    )]
+    pub ticket_class: Box<Account<'info, TicketClass>>,
+
+    #[account(
+        seeds = [SALE_CONFIG_SEED, global_config_v2.key().as_ref()],
+        bump = sale_config.bump,
+    )]
+    pub sale_config: Box<Account<'info, SaleConfig>>,
+
+    #[account(
+        mut,
+        has_one = auction,
+        has_one = bidder,
+        seeds = [SEALED_BID_SEED, auction.key().as_ref(), sealed_bid.zk_proof_hash.as_ref()],
+        bump = sealed_bid.bump,
+    )]
+    pub sealed_bid: Box<Account<'info, SealedBid>>,
+
+    #[account(mut)]
+    /// CHECK: PDA is derived and created inside the handler.
+    pub ticket_mint: UncheckedAccount<'info>,
+
+    #[account(seeds = [TICKET_MINT_AUTHORITY_SEED], bump)]
+    /// CHECK: PDA signer used only as mint authority.
+    pub ticket_mint_authority: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    /// CHECK: PDA is derived and created inside the handler.
+    pub user_ticket_token: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    /// CHECK: PDA is derived and created inside the handler.
+    pub ticket_receipt: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+
+    #[account(
+        seeds = [MEMBERSHIP_SEED, bidder.key().as_ref()],
+        bump = membership.bump,
+    )]
+    pub membership: Option<Box<Account<'info, Membership>>>,
+
+    #[account(
+        mut,
+        seeds = [
+            PROOF_QUOTA_SEED,
+            global_config_v2.key().as_ref(),
+            sealed_bid.zk_proof_hash.as_ref(),
+        ],
+        bump = proof_quota.bump,
+    )]
+    pub proof_quota: Box<Account<'info, ProofQuota>>,
+
+    #[account(
+        mut,
+        seeds = [REPUTATION_SEED, bidder.key().as_ref()],
+        bump = reputation.bump,
+    )]
+    pub reputation: Box<Account<'info, Reputation>>,
+}
+
+#[derive(Accounts)]
+#[instruction(v1_nonce: u64)]
+pub struct MigrateV1Ticket<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(
+        seeds = [b"global_v2"],
+        bump = global_config_v2.bump,
+    )]
+    pub global_config_v2: Box<Account<'info, GlobalConfigV2>>,
+
+    #[account(seeds = [V1_GLOBAL_SEED], bump, seeds::program = V1_PROGRAM_ID)]
+    /// CHECK: Only its address is used, to derive the v1 ticket PDA.
+    pub v1_global_state: UncheckedAccount<'info>,
+
+    #[account(
+        owner = V1_PROGRAM_ID @ UnforgivenV2Error::InvalidV1Ticket,
+        seeds = [
+            V1_TICKET_SEED,
+            v1_global_state.key().as_ref(),
+            buyer.key().as_ref(),
+            v1_nonce.to_le_bytes().as_ref(),
+        ],
+        bump,
+        seeds::program = V1_PROGRAM_ID,
+    )]
+    /// CHECK: Owner and seeds are checked here; the body is decoded by `parse_v1_ticket`.
+    pub v1_ticket: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = buyer,
+        space = 8 + V1Migration::INIT_SPACE,
+        seeds = [V1_MIGRATION_SEED, v1_ticket.key().as_ref()],
+        bump,
+    )]
+    pub v1_migration: Box<Account<'info, V1Migration>>,
+
+    #[account(mut)]
+    /// CHECK: PDA is derived and created inside the handler.
+    pub ticket_mint: UncheckedAccount<'info>,
+
+    #[account(seeds = [TICKET_MINT_AUTHORITY_SEED], bump)]
+    /// CHECK: PDA signer used only as mint authority.
+    pub ticket_mint_authority: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    /// CHECK: PDA is derived and created inside the handler.
+    pub user_ticket_token: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    /// CHECK: PDA is derived and created inside the handler.
+    pub ticket_receipt: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct RefundSealedBid<'info> {
+    #[account(mut)]
+    pub bidder: SystemAccount<'info>,
+
+    #[account(
+        seeds = [b"global_v2"],
+        bump = global_config_v2.bump,
+    )]
+    pub global_config_v2: Box<Account<'info, GlobalConfigV2>>,
+
+    #[account(mut, address = global_config_v2.authority)]
+    pub treasury: SystemAccount<'info>,
+
+    #[account(
+        seeds = [
+            SEALED_BID_AUCTION_SEED,
+            global_config_v2.key().as_ref(),
+            &[auction.ticket_class],
+        ],
+        bump = auction.bump,
+    )]
+    pub auction: Box<Account<'info, SealedBidAuction>>,
+
+    #[account(
+        mut,
+        has_one = auction,
+        has_one = bidder,
+        seeds = [SEALED_BID_SEED, auction.key().as_ref(), sealed_bid.zk_proof_hash.as_ref()],
+        bump = sealed_bid.bump,
+    )]
+    pub sealed_bid: Box<Account<'info, SealedBid>>,
+}
+
+#[derive(Accounts)]
+pub struct ListTicket<'info> {
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    pub ticket_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [TICKET_RECEIPT_SEED, ticket_mint.key().as_ref()],
+        bump = ticket_receipt.bump,
+        constraint = ticket_receipt.mint == ticket_mint.key() @ UnforgivenV2Error::TicketMintMismatch,
+        constraint = ticket_receipt.current_holder == seller.key() @ UnforgivenV2Error::TicketOwnerMismatch,
+    )]
+    pub ticket_receipt: Account<'info, TicketReceipt>,
+
+    #[account(
+        mut,
+        seeds = [TICKET_TOKEN_SEED, ticket_mint.key().as_ref(), seller.key().as_ref()],
+        bump,
+        token::mint = ticket_mint,
+        token::authority = seller,
+    )]
+    pub seller_ticket_token: Account<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = seller,
+        space = 8 + TicketListing::INIT_SPACE,
+        seeds = [TICKET_LISTING_SEED, ticket_mint.key().as_ref()],
+        bump,
+    )]
+    pub listing: Account<'info, TicketListing>,
+
+    #[account(
+        init,
+        payer = seller,
+        token::mint = ticket_mint,
+        token::authority = listing,
+        seeds = [TICKET_ESCROW_SEED, ticket_mint.key().as_ref()],
+        bump,
+    )]
+    pub listing_escrow_token: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+
+    #[account(
+        seeds = [SALE_CONFIG_SEED, ticket_receipt.event_key.as_ref()],
+        bump = sale_config.bump,
+    )]
+    pub sale_config: Box<Account<'info, SaleConfig>>,
+
+    // Created here if missing so the flip can be recorded when the listing fills.
+    #[account(mut)]
+    /// CHECK: PDA is derived and created inside the handler if the seller has none yet.
+    pub seller_reputation: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CancelTicketListing<'info> {
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    pub ticket_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [TICKET_RECEIPT_SEED, ticket_mint.key().as_ref()],
+        bump = ticket_receipt.bump,
+        constraint = ticket_receipt.mint == ticket_mint.key() @ UnforgivenV2Error::TicketMintMismatch,
+        constraint = ticket_receipt.current_holder == seller.key() @ UnforgivenV2Error::TicketOwnerMismatch,
+    )]
+    pub ticket_receipt: Account<'info, TicketReceipt>,
+
+    #[account(
+        mut,
+        close = seller,
+        has_one = seller,
+        constraint = listing.mint == ticket_mint.key() @ UnforgivenV2Error::TicketMintMismatch,
+        seeds = [TICKET_LISTING_SEED, ticket_mint.key().as_ref()],
+        bump = listing.bump,
+    )]
+    pub listing: Account<'info, TicketListing>,
+
+    #[account(
+        init,
+        payer = seller,
+        token::mint = ticket_mint,
+        token::authority = seller,
+        seeds = [TICKET_TOKEN_SEED, ticket_mint.key().as_ref(), seller.key().as_ref()],
+        bump,
+    )]
+    pub seller_ticket_token: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [TICKET_ESCROW_SEED, ticket_mint.key().as_ref()],
+        bump,
+        token::mint = ticket_mint,
+        token::authority = listing,
+    )]
+    pub listing_escrow_token: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+#[instruction(buyer_payload: ShieldPayloadV0)]
+pub struct FillTicketListing<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(mut)]
+    pub seller: SystemAccount<'info>,
+
+    #[account(
+        seeds = [b"global_v2"],
+        bump = global_config_v2.bump,
+    )]
+    pub global_config_v2: Account<'info, GlobalConfigV2>,
+
+    #[account(mut, address = global_config_v2.authority)]
+    pub fee_recipient: SystemAccount<'info>,
+
+    pub ticket_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [TICKET_RECEIPT_SEED, ticket_mint.key().as_ref()],
+        bump = ticket_receipt.bump,
+        constraint = ticket_receipt.mint == ticket_mint.key() @ UnforgivenV2Error::TicketMintMismatch,
+        constraint = ticket_receipt.current_holder == seller.key() @ UnforgivenV2Error::TicketOwnerMismatch,
+    )]
+    pub ticket_receipt: Account<'info, TicketReceipt>,
+
+    #[account(
+        mut,
+        close = seller,
+        has_one = seller,
+        constraint = listing.mint == ticket_mint.key() @ UnforgivenV2Error::TicketMintMismatch,
+        seeds = [TICKET_LISTING_SEED, ticket_mint.key().as_ref()],
+        bump = listing.bump,
+    )]
+    pub listing: Account<'info, TicketListing>,
+
+    #[account(
+        init,
+        payer = buyer,
+        token::mint = ticket_mint,
+        token::authority = buyer,
+        seeds = [TICKET_TOKEN_SEED, ticket_mint.key().as_ref(), buyer.key().as_ref()],
+        bump,
+    )]
+    pub buyer_ticket_token: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [TICKET_ESCROW_SEED, ticket_mint.key().as_ref()],
+        bump,
+        token::mint = ticket_mint,
+        token::authority = listing,
+    )]
+    pub listing_escrow_token: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+
+    #[account(
+        seeds = [b"admin_config_v2"],
+        bump = admin_config.bump,
+    )]
+    pub admin_config: Box<Account<'info, AdminConfig>>,
+
+    #[account(
+        seeds = [SALE_CONFIG_SEED, global_config_v2.key().as_ref()],
+        bump = sale_config.bump,
+    )]
+    pub sale_config: Box<Account<'info, SaleConfig>>,
+
+    #[account(address = instructions::ID)]
+    /// CHECK: Address constraint guarantees this is the instructions sysvar.
+    pub instructions: UncheckedAccount<'info>,
 
-    let payload_user = Pubkey::new_from_array(payload.user_pubkey);
-    require_keys_eq!(payload_user, *user_key, UnforgivenV2Error::UserPubkeyMismatch);
-    Ok(())
-}
+    #[account(
+        mut,
+        seeds = [REPUTATION_SEED, seller.key().as_ref()],
+        bump = seller_reputation.bump,
+    )]
+    pub seller_reputation: Box<Account<'info, Reputation>>,
 
-fn read_u16(data: &[u8], offset: usize) -> Result<u16> {
-    let bytes = data
-        .get(offset..offset + 2)
-        .ok_or(error!(UnforgivenV2Error::InvalidEd25519Instruction))?;
-    Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    #[account(
+        init,
+        payer = buyer,
+        space = 8 + ProofUse::INIT_SPACE,
+        seeds = [
+            b"proof_use",
+            buyer.key().as_ref(),
+            buyer_payload.zk_proof_hash.as_ref(),
+            buyer_payload.nonce.to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub buyer_proof_use: Box<Account<'info, ProofUse>>,
 }
 
-pub fn verify_ed25519_ix(
-    ix_sysvar: &AccountInfo<'_>,
-    payload_bytes: &[u8],
-    oracle_signature: &[u8],
-    oracle_pubkey: &[u8; 32],
-) -> Result<()> {
-    let current_ix_idx = load_current_index_checked(ix_sysvar)
-        .map_err(|_| error!(UnforgivenV2Error::InvalidEd25519Instruction))?;
-    require!(
-        current_ix_idx > 0,
-        UnforgivenV2Error::MissingEd25519Instruction
-    );
+#[derive(Accounts)]
+pub struct TransferTicket<'info> {
+    #[account(mut)]
+    pub holder: Signer<'info>,
 
-    let ix = load_instruction_at_checked((current_ix_idx - 1) as usize, ix_sysvar)
-        .map_err(|_| error!(UnforgivenV2Error::MissingEd25519Instruction))?;
-    require!(
-        ix.program_id == ed25519_program::id(),
-        UnforgivenV2Error::InvalidEd25519Instruction
-    );
+    pub recipient: SystemAccount<'info>,
 
-    let data = ix.data.as_slice();
-    require!(
-        data.len() >= ED25519_OFFSETS_START + ED25519_OFFSETS_SIZE,
-        UnforgivenV2Error::InvalidEd25519Instruction
-    );
-    require!(data[0] == 1, UnforgivenV2Error::InvalidEd25519Instruction);
+    pub ticket_mint: Account<'info, Mint>,
 
-    let sig_offset = read_u16(data, 2)? as usize;
-    let sig_ix_index = read_u16(data, 4)?;
-    let pubkey_offset = read_u16(data, 6)? as usize;
-    let pubkey_ix_index = read_u16(data, 8)?;
-    let msg_offset = read_u16(data, 10)? as usize;
-    let msg_len = read_u16(data, 12)? as usize;
-    let msg_ix_index = read_u16(data, 14)?;
+    #[account(
+        mut,
+        seeds = [TICKET_RECEIPT_SEED, ticket_mint.key().as_ref()],
+        bump = ticket_receipt.bump,
+        constraint = ticket_receipt.mint == ticket_mint.key() @ UnforgivenV2Error::TicketMintMismatch,
+        constraint = ticket_receipt.current_holder == holder.key() @ UnforgivenV2Error::TicketOwnerMismatch,
+    )]
+    pub ticket_receipt: Account<'info, TicketReceipt>,
 
-    require!(
-        sig_ix_index == u16::MAX
-            && pubkey_ix_index == u16::MAX
-            && msg_ix_index == u16::MAX,
-        UnforgivenV2Error::InvalidEd25519Instruction
-    );
+    #[account(
+        mut,
+        seeds = [TICKET_TOKEN_SEED, ticket_mint.key().as_ref(), holder.key().as_ref()],
+        bump,
+        token::mint = ticket_mint,
+        token::authority = holder,
+    )]
+    pub holder_ticket_token: Account<'info, TokenAccount>,
 
-    let sig = data
-        .get(sig_offset..sig_offset + ED25519_SIGNATURE_LEN)
-        .ok_or(error!(UnforgivenV2Error::InvalidEd25519Instruction))?;
-    let pk = data
-        .get(pubkey_offset..pubkey_offset + ED25519_PUBKEY_LEN)
-        .ok_or(error!(UnforgivenV2Error::InvalidEd25519Instruction))?;
-    let msg = data
-        .get(msg_offset..msg_offset + msg_len)
-        .ok_or(error!(UnforgivenV2Error::InvalidEd25519Instruction))?;
+    #[account(
+        init,
+        payer = holder,
+        token::mint = ticket_mint,
+        token::authority = recipient,
+        seeds = [TICKET_TOKEN_SEED, ticket_mint.key().as_ref(), recipient.key().as_ref()],
+        bump,
+    )]
+    pub recipient_ticket_token: Account<'info, TokenAccount>,
 
-    require!(pk == oracle_pubkey, UnforgivenV2Error::Ed25519PubkeyMismatch);
-    require!(sig == oracle_signature, UnforgivenV2Error::Ed25519SignatureMismatch);
+    #[account(
+        seeds = [SALE_CONFIG_SEED, ticket_receipt.event_key.as_ref()],
+        bump = sale_config.bump,
+    )]
+    pub sale_config: Box<Account<'info, SaleConfig>>,
 
-    require!(
-        msg == payload_bytes,
-        UnforgivenV2Error::Ed25519MessageMismatch
-    );
+    #[account(
+        seeds = [b"admin_config_v2"],
+        bump = admin_config.bump,
+    )]
+    pub admin_config: Box<Account<'info, AdminConfig>>,
 
-    Ok(())
-}
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
 
-pub fn deserialize_shield_payload_v0(data: &[u8]) -> Result<ShieldPayloadV0> {
-    require!(
-        data.len() == SHIELD_PAYLOAD_V0_LEN,
-        UnforgivenV2Error::InvalidEd25519Instruction
-    );
+    #[account(address = instructions::ID)]
+    /// CHECK: Address constraint guarantees this is the instructions sysvar.
+    pub instructions: UncheckedAccount<'info>,
 
-    Ok(ShieldPayloadV0 {
-        policy_version: data[0],
-        user_pubkey: data[1..33]
-            .try_into()
-            .map_err(|_| error!(UnforgivenV2Error::InvalidEd25519Instruction))?,
-        initial_price: u64::from_le_bytes(
-            data[33..41]
-                .try_into()
-                .map_err(|_| error!(UnforgivenV2Error::InvalidEd25519Instruction))?,
-        ),
-        sales_velocity_bps: i64::from_le_bytes(
-            data[41..49]
-                .try_into()
-                .map_err(|_| error!(UnforgivenV2Error::InvalidEd25519Instruction))?,
-        ),
-        time_elapsed: u64::from_le_bytes(
-            data[49..57]
-                .try_into()
-                .map_err(|_| error!(UnforgivenV2Error::InvalidEd25519Instruction))?,
-        ),
-        dignity_score: data[57],
-        adapter_mask: data[58],
-        user_mode: data[59],
-        zk_provider: data[60],
-        zk_proof_hash: data[61..93]
-            .try_into()
-            .map_err(|_| error!(UnforgivenV2Error::InvalidEd25519Instruction))?,
-        scoring_model_hash: data[93..125]
-            .try_into()
-            .map_err(|_| error!(UnforgivenV2Error::InvalidEd25519Instruction))?,
-        attestation_expiry: i64::from_le_bytes(
-            data[125..133]
-                .try_into()
-                .map_err(|_| error!(UnforgivenV2Error::InvalidEd25519Instruction))?,
-        ),
-        nonce: u64::from_le_bytes(
-            data[133..141]
-                .try_into()
-                .map_err(|_| error!(UnforgivenV2Error::InvalidEd25519Instruction))?,
-        ),
-    })
+    #[account(mut)]
+    /// CHECK: Required with a recipient shield; derived and created inside the handler.
+    pub recipient_proof_use: Option<UncheckedAccount<'info>>,
 }
 
-pub fn quote_from_payload(payload: &ShieldPayloadV0) -> Result<VrgdaQuote> {
-    let input = VrgdaInput {
-        initial_price: payload.initial_price,
-        sales_velocity_bps: payload.sales_velocity_bps,
-        time_elapsed: payload.time_elapsed,
-        dignity_score: payload.dignity_score,
-    };
+#[derive(Accounts)]
+pub struct RotateEntrySecret<'info> {
+    pub holder: Signer<'info>,
 
-    calculate_vrgda_quote(input).map_err(|err| match err {
-        VrgdaMathError::InvalidDignityScore => error!(UnforgivenV2Error::InvalidDignityScore),
-        VrgdaMathError::InvalidSalesVelocity => error!(UnforgivenV2Error::InvalidSalesVelocity),
-        VrgdaMathError::InvalidTimeElapsed => error!(UnforgivenV2Error::InvalidTimeElapsed),
-    })
+    #[account(
+        mut,
+        seeds = [TICKET_RECEIPT_SEED, ticket_receipt.mint.as_ref()],
+        bump = ticket_receipt.bump,
+        constraint = ticket_receipt.current_holder == holder.key() @ UnforgivenV2Error::TicketOwnerMismatch,
+    )]
+    pub ticket_receipt: Account<'info, TicketReceipt>,
 }
 
-pub fn preview_event_from_payload(
-    payload: &ShieldPayloadV0,
-    oracle_signature: &[u8; 64],
-    admin_config: &AdminConfig,
-    user_key: &Pubkey,
-    now: i64,
-) -> Result<PreviewPriceEvent> {
-    validate_preview_request_fields(payload, admin_config, user_key, now)?;
-    verify_oracle_signature(payload, oracle_signature, &admin_config.oracle_pubkey)?;
+#[derive(Accounts)]
+#[instruction(session_id: u64, payload: ShieldPayloadV0)]
+pub struct StartHideSisSession<'info> {
+    #[account(mut)]
+    pub player: Signer<'info>,
 
-    let quote = quote_from_payload(payload)?;
-    Ok(PreviewPriceEvent {
-        final_price: quote.final_price,
-        is_infinite: quote.is_infinite,
-        blocked: quote.blocked,
-        effective_velocity_bps: quote.effective_velocity_bps,
-        dignity_score: payload.dignity_score,
-        adapter_mask: payload.adapter_mask,
-        dignity_bucket: dignity_bucket(payload.dignity_score),
-        user_mode: payload.user_mode,
-    })
-}
+    #[account(
+        seeds = [b"admin_config_v2"],
+        bump = admin_config.bump,
+    )]
+    pub admin_config: Box<Account<'info, AdminConfig>>,
 
-pub fn execution_event_from_payload(
-    payload: &ShieldPayloadV0,
-    oracle_signature: &[u8; 64],
-    admin_config: &AdminConfig,
-    user_key: &Pubkey,
-    now: i64,
-) -> Result<ShieldExecutionEvent> {
-    validate_preview_request_fields(payload, admin_config, user_key, now)?;
-    verify_oracle_signature(payload, oracle_signature, &admin_config.oracle_pubkey)?;
+    #[account(
+        init,
+        payer = player,
+        space = 8 + HideSisSession::INIT_SPACE,
+        seeds = [HIDE_SIS_SESSION_SEED, player.key().as_ref(), &session_id.to_le_bytes()],
+        bump,
+    )]
+    pub session: Account<'info, HideSisSession>,
 
-    let quote = quote_from_payload(payload)?;
-    require!(!quote.blocked, UnforgivenV2Error::ShieldBlocked);
+    pub system_program: Program<'info, System>,
 
-    Ok(ShieldExecutionEvent {
-        final_price: quote.final_price,
-        blocked: quote.blocked,
-        effective_velocity_bps: quote.effective_velocity_bps,
-        dignity_score: payload.dignity_score,
-        adapter_mask: payload.adapter_mask,
-        user_mode: payload.user_mode,
-        nonce: payload.nonce,
-        zk_proof_hash: payload.zk_proof_hash,
-    })
+    #[account(address = instructions::ID)]
+    /// CHECK: Address constraint guarantees this is the instructions sysvar.
+    pub instructions: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = player,
+        space = 8 + ProofUse::INIT_SPACE,
+        seeds = [
+            b"proof_use",
+            player.key().as_ref(),
+            payload.zk_proof_hash.as_ref(),
+            payload.nonce.to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub proof_use: Box<Account<'info, ProofUse>>,
 }
 
-fn dignity_bucket(score: u8) -> u8 {
-    match score {
-        0..=20 => 0,
-        21..=40 => 1,
-        41..=60 => 2,
-        61..=80 => 3,
-        _ => 4,
-    }
+#[derive(Accounts)]
+pub struct HideSisSessionTurn<'info> {
+    pub player: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = player,
+        seeds = [HIDE_SIS_SESSION_SEED, player.key().as_ref(), &session.session_id.to_le_bytes()],
+        bump = session.bump,
+    )]
+    pub session: Account<'info, HideSisSession>,
 }
 
 #[derive(Accounts)]
-pub struct InitializeV2<'info> {
+pub struct ClaimEndingBadge<'info> {
     #[account(mut)]
-    pub authority: Signer<'info>,
+    pub player: Signer<'info>,
+
+    #[account(
+        seeds = [b"admin_config_v2"],
+        bump = admin_config.bump,
+    )]
+    pub admin_config: Box<Account<'info, AdminConfig>>,
+
+    #[account(
+        has_one = player,
+        seeds = [HIDE_SIS_SESSION_SEED, player.key().as_ref(), &session.session_id.to_le_bytes()],
+        bump = session.bump,
+    )]
+    pub session: Box<Account<'info, HideSisSession>>,
 
-    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
-    pub program: Program<'info, crate::program::UnforgivenV2>,
-    #[account(constraint = program_data.upgrade_authority_address == Some(authority.key()))]
-    pub program_data: Account<'info, ProgramData>,
+    #[account(
+        init,
+        payer = player,
+        space = 8 + EndingBadge::INIT_SPACE,
+        seeds = [ENDING_BADGE_SEED, player.key().as_ref(), &[session.ending_code]],
+        bump,
+    )]
+    pub badge: Box<Account<'info, EndingBadge>>,
 
     #[account(
         init,
-        payer = authority,
-        space = 8 + GlobalConfigV2::INIT_SPACE,
-        seeds = [b"global_v2"],
-        bump
+        payer = player,
+        mint::decimals = TICKET_DECIMALS,
+        mint::authority = ticket_mint_authority,
+        mint::freeze_authority = ticket_mint_authority,
+        seeds = [ENDING_BADGE_MINT_SEED, player.key().as_ref(), &[session.ending_code]],
+        bump,
     )]
-    pub global_config_v2: Account<'info, GlobalConfigV2>,
+    pub badge_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = player,
+        token::mint = badge_mint,
+        token::authority = player,
+        seeds = [ENDING_BADGE_TOKEN_SEED, badge_mint.key().as_ref()],
+        bump,
+    )]
+    pub badge_token: Box<Account<'info, TokenAccount>>,
 
+    #[account(seeds = [TICKET_MINT_AUTHORITY_SEED], bump)]
+    /// CHECK: PDA signer used only as mint and freeze authority.
+    pub ticket_mint_authority: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+
+    #[account(address = instructions::ID)]
+    /// CHECK: Address constraint guarantees this is the instructions sysvar.
+    pub instructions: UncheckedAccount<'info>,
 }
 
 #[derive(Accounts)]
-pub struct InitializeAdminConfig<'info> {
+pub struct InitializeGraphRegistry<'info> {
     #[account(mut)]
     pub authority: Signer<'info>,
 
-    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
-    pub program: Program<'info, crate::program::UnforgivenV2>,
-    #[account(constraint = program_data.upgrade_authority_address == Some(authority.key()))]
-    pub program_data: Account<'info, ProgramData>,
+    #[account(
+        has_one = authority,
+        seeds = [b"admin_config_v2"],
+        bump = admin_config.bump,
+    )]
+    pub admin_config: Account<'info, AdminConfig>,
 
     #[account(
         init,
         payer = authority,
-        space = 8 + AdminConfig::INIT_SPACE,
-        seeds = [b"admin_config_v2"],
-        bump
+        space = 8 + GraphRegistry::INIT_SPACE,
+        seeds = [GRAPH_REGISTRY_SEED],
+        bump,
     )]
-    pub admin_config: Account<'info, AdminConfig>,
+    pub graph_registry: Account<'info, GraphRegistry>,
 
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct AdminOnly<'info> {
+pub struct GraphRegistryAdmin<'info> {
     pub authority: Signer<'info>,
 
     #[account(
-        mut,
         has_one = authority,
         seeds = [b"admin_config_v2"],
         bump = admin_config.bump,
     )]
     pub admin_config: Account<'info, AdminConfig>,
-}
-
-#[derive(Accounts)]
-pub struct PreviewPrice<'info> {
-    pub user: Signer<'info>,
 
     #[account(
-        seeds = [b"admin_config_v2"],
-        bump = admin_config.bump,
+        mut,
+        seeds = [GRAPH_REGISTRY_SEED],
+        bump = graph_registry.bump,
     )]
-    pub admin_config: Account<'info, AdminConfig>,
+    pub graph_registry: Account<'info, GraphRegistry>,
 
-    #[account(address = instructions::ID)]
-    /// CHECK: Address constraint guarantees this is the instructions sysvar.
-    pub instructions: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED],
+        bump = audit_log.bump,
+    )]
+    pub audit_log: Box<Account<'info, AuditLog>>,
 }
 
 #[derive(Accounts)]
-pub struct ExecuteShield<'info> {
+#[instruction(version: u64)]
+pub struct AnchorGraphSnapshot<'info> {
     #[account(mut)]
-    pub user: Signer<'info>,
+    pub publisher: Signer<'info>,
 
     #[account(
-        seeds = [b"global_v2"],
-        bump = global_config_v2.bump,
+        mut,
+        has_one = publisher @ UnforgivenV2Error::GraphPublisherMismatch,
+        seeds = [GRAPH_REGISTRY_SEED],
+        bump = graph_registry.bump,
     )]
-    pub global_config_v2: Box<Account<'info, GlobalConfigV2>>,
-
-    #[account(mut, address = global_config_v2.authority)]
-    pub treasury: SystemAccount<'info>,
+    pub graph_registry: Account<'info, GraphRegistry>,
 
     #[account(
-        seeds = [b"admin_config_v2"],
-        bump = admin_config.bump,
+        init,
+        payer = publisher,
+        space = 8 + GraphSnapshot::INIT_SPACE,
+        seeds = [GRAPH_SNAPSHOT_SEED, &version.to_le_bytes()],
+        bump,
     )]
-    pub admin_config: Box<Account<'info, AdminConfig>>,
+    pub graph_snapshot: Account<'info, GraphSnapshot>,
 
-    #[account(address = instructions::ID)]
-    /// CHECK: Address constraint guarantees this is the instructions sysvar.
-    pub instructions: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
 
+#[derive(Accounts)]
+pub struct RedeemTicket<'info> {
     #[account(mut)]
-    /// CHECK: PDA is derived and created inside the handler to avoid payload-heavy pre-handler work.
-    pub proof_use: UncheckedAccount<'info>,
+    pub scanner: Signer<'info>,
 
-    #[account(mut)]
-    /// CHECK: PDA is derived and created inside the handler to avoid payload-heavy pre-handler work.
-    pub ticket_mint: UncheckedAccount<'info>,
+    #[account(
+        seeds = [SALE_CONFIG_SEED, ticket_receipt.event_key.as_ref()],
+        bump = sale_config.bump,
+        constraint = sale_config.scanner == scanner.key() @ UnforgivenV2Error::ScannerMismatch,
+    )]
+    pub sale_config: Account<'info, SaleConfig>,
 
-    #[account(seeds = [TICKET_MINT_AUTHORITY_SEED], bump)]
-    /// CHECK: PDA signer used only as mint authority.
-    pub ticket_mint_authority: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [TICKET_RECEIPT_SEED, ticket_receipt.mint.as_ref()],
+        bump = ticket_receipt.bump,
+        constraint = ticket_receipt.redeemed_at == 0 @ UnforgivenV2Error::TicketAlreadyRedeemed,
+    )]
+    pub ticket_receipt: Account<'info, TicketReceipt>,
 
     #[account(mut)]
-    /// CHECK: PDA is derived and created inside the handler to avoid payload-heavy pre-handler work.
-    pub user_ticket_token: UncheckedAccount<'info>,
+    /// CHECK: PDA is derived and created inside the handler if the holder has none yet.
+    pub holder_reputation: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
 
+#[derive(Accounts)]
+pub struct InitializeReputation<'info> {
     #[account(mut)]
-    /// CHECK: PDA is derived and created inside the handler to avoid payload-heavy pre-handler work.
-    pub ticket_receipt: UncheckedAccount<'info>,
+    pub payer: Signer<'info>,
+
+    pub wallet: SystemAccount<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Reputation::INIT_SPACE,
+        seeds = [REPUTATION_SEED, wallet.key().as_ref()],
+        bump,
+    )]
+    pub reputation: Account<'info, Reputation>,
 
-    pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
-    pub rent: Sysvar<'info, Rent>,
 }
 
 #[derive(Accounts)]
-pub struct ListTicket<'info> {
+#[instruction(payload: ShieldPayloadV0)]
+pub struct RecordBlockedAttempt<'info> {
     #[account(mut)]
-    pub seller: Signer<'info>,
+    pub reporter: Signer<'info>,
 
-    pub ticket_mint: Account<'info, Mint>,
+    #[account(
+        seeds = [b"admin_config_v2"],
+        bump = admin_config.bump,
+    )]
+    pub admin_config: Box<Account<'info, AdminConfig>>,
 
     #[account(
-        mut,
-        seeds = [TICKET_RECEIPT_SEED, ticket_mint.key().as_ref()],
-        bump = ticket_receipt.bump,
-        constraint = ticket_receipt.mint == ticket_mint.key() @ UnforgivenV2Error::TicketMintMismatch,
-        constraint = ticket_receipt.current_holder == seller.key() @ UnforgivenV2Error::TicketOwnerMismatch,
+        seeds = [SALE_CONFIG_SEED, sale_config.event_key.as_ref()],
+        bump = sale_config.bump,
     )]
-    pub ticket_receipt: Account<'info, TicketReceipt>,
+    pub sale_config: Box<Account<'info, SaleConfig>>,
+
+    #[account(
+        seeds = [TICKET_CLASS_SEED, sale_config.event_key.as_ref(), &[ticket_class.class_id]],
+        bump = ticket_class.bump,
+    )]
+    pub ticket_class: Option<Box<Account<'info, TicketClass>>>,
 
     #[account(
         mut,
-        seeds = [TICKET_TOKEN_SEED, ticket_mint.key().as_ref(), seller.key().as_ref()],
-        bump,
-        token::mint = ticket_mint,
-        token::authority = seller,
+        seeds = [REPUTATION_SEED, reputation.wallet.as_ref()],
+        bump = reputation.bump,
     )]
-    pub seller_ticket_token: Account<'info, TokenAccount>,
+    pub reputation: Account<'info, Reputation>,
 
     #[account(
         init,
-        payer = seller,
-        space = 8 + TicketListing::INIT_SPACE,
-        seeds = [TICKET_LISTING_SEED, ticket_mint.key().as_ref()],
+        payer = reporter,
+        space = 8 + ProofUse::INIT_SPACE,
+        seeds = [
+            b"proof_use",
+            reputation.wallet.as_ref(),
+            payload.zk_proof_hash.as_ref(),
+            payload.nonce.to_le_bytes().as_ref(),
+        ],
         bump,
     )]
-    pub listing: Account<'info, TicketListing>,
+    pub proof_use: Box<Account<'info, ProofUse>>,
+
+    #[account(address = instructions::ID)]
+    /// CHECK: Address constraint guarantees this is the instructions sysvar.
+    pub instructions: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeMembershipConfig<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        has_one = authority,
+        seeds = [b"admin_config_v2"],
+        bump = admin_config.bump,
+    )]
+    pub admin_config: Account<'info, AdminConfig>,
 
     #[account(
         init,
-        payer = seller,
-        token::mint = ticket_mint,
-        token::authority = listing,
-        seeds = [TICKET_ESCROW_SEED, ticket_mint.key().as_ref()],
+        payer = authority,
+        space = 8 + MembershipConfig::INIT_SPACE,
+        seeds = [MEMBERSHIP_CONFIG_SEED],
         bump,
     )]
-    pub listing_escrow_token: Account<'info, TokenAccount>,
+    pub membership_config: Account<'info, MembershipConfig>,
 
-    pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
-    pub rent: Sysvar<'info, Rent>,
+
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED],
+        bump = audit_log.bump,
+    )]
+    pub audit_log: Box<Account<'info, AuditLog>>,
 }
 
 #[derive(Accounts)]
-pub struct CancelTicketListing<'info> {
-    #[account(mut)]
-    pub seller: Signer<'info>,
+pub struct UpdateMembershipConfig<'info> {
+    pub authority: Signer<'info>,
 
-    pub ticket_mint: Account<'info, Mint>,
+    #[account(
+        has_one = authority,
+        seeds = [b"admin_config_v2"],
+        bump = admin_config.bump,
+    )]
+    pub admin_config: Account<'info, AdminConfig>,
 
     #[account(
         mut,
-        seeds = [TICKET_RECEIPT_SEED, ticket_mint.key().as_ref()],
-        bump = ticket_receipt.bump,
-        constraint = ticket_receipt.mint == ticket_mint.key() @ UnforgivenV2Error::TicketMintMismatch,
-        constraint = ticket_receipt.current_holder == seller.key() @ UnforgivenV2Error::TicketOwnerMismatch,
+        seeds = [MEMBERSHIP_CONFIG_SEED],
+        bump = membership_config.bump,
     )]
-    pub ticket_receipt: Account<'info, TicketReceipt>,
+    pub membership_config: Account<'info, MembershipConfig>,
 
     #[account(
         mut,
-        close = seller,
-        has_one = seller,
-        constraint = listing.mint == ticket_mint.key() @ UnforgivenV2Error::TicketMintMismatch,
-        seeds = [TICKET_LISTING_SEED, ticket_mint.key().as_ref()],
-        bump = listing.bump,
+        seeds = [AUDIT_LOG_SEED],
+        bump = audit_log.bump,
     )]
-    pub listing: Account<'info, TicketListing>,
+    pub audit_log: Box<Account<'info, AuditLog>>,
+}
+
+#[derive(Accounts)]
+#[instruction(tier: u8, payload: ShieldPayloadV0)]
+pub struct PurchaseMembership<'info> {
+    #[account(mut)]
+    pub wallet: Signer<'info>,
 
     #[account(
-        init,
-        payer = seller,
-        token::mint = ticket_mint,
-        token::authority = seller,
-        seeds = [TICKET_TOKEN_SEED, ticket_mint.key().as_ref(), seller.key().as_ref()],
-        bump,
+        seeds = [b"global_v2"],
+        bump = global_config_v2.bump,
     )]
-    pub seller_ticket_token: Account<'info, TokenAccount>,
+    pub global_config_v2: Box<Account<'info, GlobalConfigV2>>,
+
+    #[account(mut, address = global_config_v2.authority @ UnforgivenV2Error::TreasuryMismatch)]
+    pub treasury: SystemAccount<'info>,
 
     #[account(
-        mut,
-        seeds = [TICKET_ESCROW_SEED, ticket_mint.key().as_ref()],
+        seeds = [b"admin_config_v2"],
+        bump = admin_config.bump,
+    )]
+    pub admin_config: Box<Account<'info, AdminConfig>>,
+
+    #[account(
+        seeds = [MEMBERSHIP_CONFIG_SEED],
+        bump = membership_config.bump,
+    )]
+    pub membership_config: Account<'info, MembershipConfig>,
+
+    #[account(
+        init,
+        payer = wallet,
+        space = 8 + Membership::INIT_SPACE,
+        seeds = [MEMBERSHIP_SEED, wallet.key().as_ref()],
         bump,
-        token::mint = ticket_mint,
-        token::authority = listing,
     )]
-    pub listing_escrow_token: Account<'info, TokenAccount>,
+    pub membership: Account<'info, Membership>,
 
-    pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
-    pub rent: Sysvar<'info, Rent>,
+
+    #[account(address = instructions::ID)]
+    /// CHECK: Address constraint guarantees this is the instructions sysvar.
+    pub instructions: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = wallet,
+        space = 8 + ProofUse::INIT_SPACE,
+        seeds = [
+            b"proof_use",
+            wallet.key().as_ref(),
+            payload.zk_proof_hash.as_ref(),
+            payload.nonce.to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub proof_use: Box<Account<'info, ProofUse>>,
 }
 
 #[derive(Accounts)]
-pub struct FillTicketListing<'info> {
+#[instruction(tier: u8, payload: ShieldPayloadV0)]
+pub struct UpgradeMembership<'info> {
     #[account(mut)]
-    pub buyer: Signer<'info>,
-
-    #[account(mut)]
-    pub seller: SystemAccount<'info>,
+    pub wallet: Signer<'info>,
 
     #[account(
         seeds = [b"global_v2"],
         bump = global_config_v2.bump,
     )]
-    pub global_config_v2: Account<'info, GlobalConfigV2>,
-
-    #[account(mut, address = global_config_v2.authority)]
-    pub fee_recipient: SystemAccount<'info>,
+    pub global_config_v2: Box<Account<'info, GlobalConfigV2>>,
 
-    pub ticket_mint: Account<'info, Mint>,
+    #[account(mut, address = global_config_v2.authority @ UnforgivenV2Error::TreasuryMismatch)]
+    pub treasury: SystemAccount<'info>,
 
     #[account(
-        mut,
-        seeds = [TICKET_RECEIPT_SEED, ticket_mint.key().as_ref()],
-        bump = ticket_receipt.bump,
-        constraint = ticket_receipt.mint == ticket_mint.key() @ UnforgivenV2Error::TicketMintMismatch,
-        constraint = ticket_receipt.current_holder == seller.key() @ UnforgivenV2Error::TicketOwnerMismatch,
+        seeds = [b"admin_config_v2"],
+        bump = admin_config.bump,
+    )]
+    pub admin_config: Box<Account<'info, AdminConfig>>,
+
+    #[account(
+        seeds = [MEMBERSHIP_CONFIG_SEED],
+        bump = membership_config.bump,
     )]
-    pub ticket_receipt: Account<'info, TicketReceipt>,
+    pub membership_config: Account<'info, MembershipConfig>,
 
     #[account(
         mut,
-        close = seller,
-        has_one = seller,
-        constraint = listing.mint == ticket_mint.key() @ UnforgivenV2Error::TicketMintMismatch,
-        seeds = [TICKET_LISTING_SEED, ticket_mint.key().as_ref()],
-        bump = listing.bump,
+        has_one = wallet,
+        seeds = [MEMBERSHIP_SEED, wallet.key().as_ref()],
+        bump = membership.bump,
     )]
-    pub listing: Account<'info, TicketListing>,
+    pub membership: Account<'info, Membership>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(address = instructions::ID)]
+    /// CHECK: Address constraint guarantees this is the instructions sysvar.
+    pub instructions: UncheckedAccount<'info>,
 
     #[account(
         init,
-        payer = buyer,
-        token::mint = ticket_mint,
-        token::authority = buyer,
-        seeds = [TICKET_TOKEN_SEED, ticket_mint.key().as_ref(), buyer.key().as_ref()],
+        payer = wallet,
+        space = 8 + ProofUse::INIT_SPACE,
+        seeds = [
+            b"proof_use",
+            wallet.key().as_ref(),
+            payload.zk_proof_hash.as_ref(),
+            payload.nonce.to_le_bytes().as_ref(),
+        ],
         bump,
     )]
-    pub buyer_ticket_token: Account<'info, TokenAccount>,
+    pub proof_use: Box<Account<'info, ProofUse>>,
+}
 
-    #[account(
-        mut,
-        seeds = [TICKET_ESCROW_SEED, ticket_mint.key().as_ref()],
-        bump,
-        token::mint = ticket_mint,
-        token::authority = listing,
-    )]
-    pub listing_escrow_token: Account<'info, TokenAccount>,
+// Typed wrappers for integrators depending on this crate with `features = ["cpi"]`.
+#[cfg(feature = "cpi")]
+pub mod gate {
+    use super::*;
+    use anchor_lang::solana_program::program::get_return_data;
 
-    pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, System>,
-    pub rent: Sysvar<'info, Rent>,
+    // Calls `shield_gate` and decodes its return data. The Ed25519 instruction at
+    // `sig_instruction_index` must be in the outer transaction.
+    pub fn shield_gate<'info>(
+        ctx: CpiContext<'_, '_, '_, 'info, crate::cpi::accounts::ShieldGate<'info>>,
+        payload: ShieldPayloadV0,
+        oracle_signature: [u8; 64],
+        sig_instruction_index: u16,
+    ) -> Result<QuoteReturnV0> {
+        crate::cpi::shield_gate(ctx, payload, oracle_signature, sig_instruction_index)?;
+        read_quote_return()
+    }
+
+    // Reads a `QuoteReturnV0` left by this program in the current transaction.
+    pub fn read_quote_return() -> Result<QuoteReturnV0> {
+        let (program_id, data) =
+            get_return_data().ok_or(error!(UnforgivenV2Error::InvalidQuoteReturnData))?;
+        require_keys_eq!(
+            program_id,
+            crate::ID,
+            UnforgivenV2Error::InvalidQuoteReturnData
+        );
+        decode_quote_return_v0(&data)
+    }
 }
 
 #[cfg(test)]
@@ -1484,6 +7708,7 @@ mod tests {
 
     const ONE_SOL_LAMPORTS: u64 = 1_000_000_000;
     const NOW: i64 = 1_700_000_000;
+    const TEST_CLUSTER_ID: [u8; 32] = [42u8; 32];
 
     fn test_oracle_keypair() -> DalekKeypair {
         let secret = SecretKey::from_bytes(&[7u8; 32]).unwrap();
@@ -1497,6 +7722,35 @@ mod tests {
             oracle_pubkey,
             active_scoring_model_hash: model_hash,
             bump: 255,
+            zk_provider_allowlist: [u8::MAX; ZK_PROVIDER_ALLOWLIST_LEN],
+            cluster_id: TEST_CLUSTER_ID,
+            layout_version: account_layout::admin_config::VERSION,
+        }
+    }
+
+    fn sample_sale_config() -> SaleConfig {
+        SaleConfig {
+            event_key: Pubkey::new_unique(),
+            max_uses_per_proof: 1,
+            mode_policies: [UserModePolicy::PERMISSIVE; USER_MODE_COUNT],
+            required_adapter_mask: 0,
+            forbidden_adapter_mask: 0,
+            bump: 255,
+            allocation_mode: ALLOCATION_MODE_OPEN,
+            commit_deposit_lamports: 0,
+            commit_min_delay_slots: 0,
+            commit_reveal_window_slots: 0,
+            commit_forfeit_bps: 0,
+            pricing_strategy: PRICING_STRATEGY_VRGDA,
+            dutch_floor_price: 0,
+            dutch_step_amount: 0,
+            dutch_step_interval_secs: 0,
+            max_transfers_per_ticket: 0,
+            transfer_requires_shield: false,
+            min_membership_tier: MEMBERSHIP_TIER_NONE,
+            dutch_start_price: 0,
+            ticket_class_count: 0,
+            scanner: Pubkey::new_unique(),
         }
     }
 
@@ -1523,8 +7777,74 @@ mod tests {
         }
     }
 
+    fn sign_for(payload: &ShieldPayloadV0, kp: &DalekKeypair, purpose: u8) -> [u8; 64] {
+        let domain = SigningDomain {
+            program_id: crate::id(),
+            cluster_id: TEST_CLUSTER_ID,
+            purpose,
+            ticket_class: TICKET_CLASS_NONE,
+        };
+        kp.sign(&serialize_shield_message_v0(payload, &domain))
+            .to_bytes()
+    }
+
     fn sign_payload(payload: &ShieldPayloadV0, kp: &DalekKeypair) -> [u8; 64] {
-        kp.sign(&serialize_shield_payload_v0(payload)).to_bytes()
+        sign_for(payload, kp, SIGNING_PURPOSE_PREVIEW)
+    }
+
+    fn ed25519_ix_data(entries: &[([u8; 32], [u8; 64], Vec<u8>)], ix_index: u16) -> Vec<u8> {
+        let mut offsets = Vec::new();
+        let mut body = Vec::new();
+        let body_start = 2 + entries.len() * 14;
+        for (pk, sig, msg) in entries {
+            let pk_offset = (body_start + body.len()) as u16;
+            body.extend_from_slice(pk);
+            let sig_offset = (body_start + body.len()) as u16;
+            body.extend_from_slice(sig);
+            let msg_offset = (body_start + body.len()) as u16;
+            body.extend_from_slice(msg);
+            for value in [
+                sig_offset,
+                ix_index,
+                pk_offset,
+                ix_index,
+                msg_offset,
+                msg.len() as u16,
+                ix_index,
+            ] {
+                offsets.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+
+        let mut data = vec![entries.len() as u8, 0];
+        data.extend_from_slice(&offsets);
+        data.extend_from_slice(&body);
+        data
+    }
+
+    #[test]
+    fn ed25519_verify_matches_oracle_entry_in_multi_signature_ix() {
+        let oracle_pk = [5u8; 32];
+        let oracle_sig = [6u8; 64];
+        let message = b"shield message".to_vec();
+        let entries = [
+            ([1u8; 32], [2u8; 64], b"other signer".to_vec()),
+            (oracle_pk, oracle_sig, message.clone()),
+        ];
+
+        let data = ed25519_ix_data(&entries, u16::MAX);
+        assert!(verify_ed25519_ix_data(&data, 3, &message, &oracle_sig, &oracle_pk).is_ok());
+        assert!(verify_ed25519_ix_data(&data, 3, b"tampered", &oracle_sig, &oracle_pk).is_err());
+        assert!(verify_ed25519_ix_data(&data, 3, &message, &[9u8; 64], &oracle_pk).is_err());
+        assert!(verify_ed25519_ix_data(&data, 3, &message, &oracle_sig, &[7u8; 32]).is_err());
+
+        let self_indexed = ed25519_ix_data(&entries, 3);
+        assert!(
+            verify_ed25519_ix_data(&self_indexed, 3, &message, &oracle_sig, &oracle_pk).is_ok()
+        );
+        assert!(
+            verify_ed25519_ix_data(&self_indexed, 4, &message, &oracle_sig, &oracle_pk).is_err()
+        );
     }
 
     #[test]
@@ -1563,12 +7883,83 @@ mod tests {
         assert_eq!(u64::from_le_bytes(bytes[133..141].try_into().unwrap()), 12);
     }
 
+    #[test]
+    fn signatures_are_bound_to_program_cluster_and_purpose() {
+        let oracle = test_oracle_keypair();
+        let model_hash = [11u8; 32];
+        let user = Pubkey::new_unique();
+        let mut admin = sample_admin(oracle.public.to_bytes(), model_hash);
+        let sale = sample_sale_config();
+        let payload = sample_payload(50, USER_MODE_VERIFIED, model_hash, user);
+
+        let preview_sig = sign_for(&payload, &oracle, SIGNING_PURPOSE_PREVIEW);
+        let execute_sig = sign_for(&payload, &oracle, SIGNING_PURPOSE_EXECUTE);
+        assert!(
+            preview_event_from_payload(&payload, &preview_sig, &admin, &sale, &user, NOW).is_ok()
+        );
+        assert!(
+            execution_event_from_payload(&payload, &execute_sig, &admin, &sale, &user, NOW).is_ok()
+        );
+        assert!(
+            execution_event_from_payload(&payload, &preview_sig, &admin, &sale, &user, NOW)
+                .is_err()
+        );
+        assert!(
+            preview_event_from_payload(&payload, &execute_sig, &admin, &sale, &user, NOW).is_err()
+        );
+
+        let raw_sig = oracle
+            .sign(&serialize_shield_payload_v0(&payload))
+            .to_bytes();
+        assert!(preview_event_from_payload(&payload, &raw_sig, &admin, &sale, &user, NOW).is_err());
+
+        let other_program = SigningDomain {
+            program_id: Pubkey::new_unique(),
+            cluster_id: TEST_CLUSTER_ID,
+            purpose: SIGNING_PURPOSE_PREVIEW,
+            ticket_class: TICKET_CLASS_NONE,
+        };
+        let foreign_sig = oracle
+            .sign(&serialize_shield_message_v0(&payload, &other_program))
+            .to_bytes();
+        assert!(
+            preview_event_from_payload(&payload, &foreign_sig, &admin, &sale, &user, NOW).is_err()
+        );
+
+        admin.cluster_id = [43u8; 32];
+        assert!(
+            preview_event_from_payload(&payload, &preview_sig, &admin, &sale, &user, NOW).is_err()
+        );
+    }
+
+    #[test]
+    fn signed_message_prefixes_domain_before_payload() {
+        let payload = sample_payload(50, USER_MODE_VERIFIED, [11u8; 32], Pubkey::new_unique());
+        let domain = SigningDomain {
+            program_id: crate::id(),
+            cluster_id: TEST_CLUSTER_ID,
+            purpose: SIGNING_PURPOSE_EXECUTE,
+            ticket_class: TICKET_CLASS_NONE,
+        };
+
+        let message = serialize_shield_message_v0(&payload, &domain);
+        assert_eq!(SHIELD_DOMAIN_LEN, 86);
+        assert_eq!(message.len(), SHIELD_SIGNED_MESSAGE_V0_LEN);
+        assert_eq!(&message[..20], SHIELD_DOMAIN_TAG);
+        assert_eq!(&message[20..52], crate::id().as_ref());
+        assert_eq!(&message[52..84], &TEST_CLUSTER_ID);
+        assert_eq!(message[84], SIGNING_PURPOSE_EXECUTE);
+        assert_eq!(message[85], TICKET_CLASS_NONE);
+        assert_eq!(&message[86..], &serialize_shield_payload_v0(&payload));
+    }
+
     #[test]
     fn same_heat_higher_dignity_yields_lower_price() {
         let oracle = test_oracle_keypair();
         let model_hash = [11u8; 32];
         let user = Pubkey::new_unique();
         let admin = sample_admin(oracle.public.to_bytes(), model_hash);
+        let sale = sample_sale_config();
 
         let bot = sample_payload(0, USER_MODE_BOT_SUSPECTED, model_hash, user);
         let human = sample_payload(90, USER_MODE_VERIFIED, model_hash, user);
@@ -1577,6 +7968,7 @@ mod tests {
             &bot,
             &sign_payload(&bot, &oracle),
             &admin,
+            &sale,
             &user,
             NOW,
         )
@@ -1585,6 +7977,7 @@ mod tests {
             &human,
             &sign_payload(&human, &oracle),
             &admin,
+            &sale,
             &user,
             NOW,
         )
@@ -1599,21 +7992,31 @@ mod tests {
         let model_hash = [11u8; 32];
         let user = Pubkey::new_unique();
         let admin = sample_admin(oracle.public.to_bytes(), model_hash);
+        let sale = sample_sale_config();
 
         let mut payload = sample_payload(50, USER_MODE_VERIFIED, model_hash, user);
         let mut bad_sig = [0u8; 64];
         bad_sig.copy_from_slice(&sign_payload(&payload, &oracle));
         bad_sig[0] ^= 0xFF;
-        assert!(preview_event_from_payload(&payload, &bad_sig, &admin, &user, NOW).is_err());
+        assert!(preview_event_from_payload(&payload, &bad_sig, &admin, &sale, &user, NOW).is_err());
 
         payload.attestation_expiry = NOW - 1;
         let expired_sig = sign_payload(&payload, &oracle);
-        assert!(preview_event_from_payload(&payload, &expired_sig, &admin, &user, NOW).is_err());
+        assert!(
+            preview_event_from_payload(&payload, &expired_sig, &admin, &sale, &user, NOW).is_err()
+        );
 
         let payload = sample_payload(50, USER_MODE_VERIFIED, model_hash, user);
         let sig = sign_payload(&payload, &oracle);
-        assert!(preview_event_from_payload(&payload, &sig, &admin, &Pubkey::new_unique(), NOW)
-            .is_err());
+        assert!(preview_event_from_payload(
+            &payload,
+            &sig,
+            &admin,
+            &sale,
+            &Pubkey::new_unique(),
+            NOW
+        )
+        .is_err());
     }
 
     #[test]
@@ -1622,10 +8025,11 @@ mod tests {
         let model_hash = [11u8; 32];
         let user = Pubkey::new_unique();
         let admin = sample_admin(oracle.public.to_bytes(), [99u8; 32]);
+        let sale = sample_sale_config();
 
         let payload = sample_payload(50, USER_MODE_VERIFIED, model_hash, user);
         let sig = sign_payload(&payload, &oracle);
-        assert!(preview_event_from_payload(&payload, &sig, &admin, &user, NOW).is_err());
+        assert!(preview_event_from_payload(&payload, &sig, &admin, &sale, &user, NOW).is_err());
     }
 
     #[test]
@@ -1634,14 +8038,785 @@ mod tests {
         let model_hash = [11u8; 32];
         let user = Pubkey::new_unique();
         let admin = sample_admin(oracle.public.to_bytes(), model_hash);
+        let sale = sample_sale_config();
 
         let mut payload = sample_payload(0, USER_MODE_BOT_SUSPECTED, model_hash, user);
         payload.initial_price = u64::MAX;
         payload.sales_velocity_bps = 9_000;
         payload.time_elapsed = 1_000;
+        let sig = sign_for(&payload, &oracle, SIGNING_PURPOSE_EXECUTE);
+
+        assert!(execution_event_from_payload(&payload, &sig, &admin, &sale, &user, NOW).is_err());
+        // execute_shield and record_blocked_attempt count it as a blocked attempt instead.
+        assert!(
+            shield_purchase_quote(&payload, &sale, None)
+                .unwrap()
+                .blocked
+        );
+    }
+
+    #[test]
+    fn user_mode_policy_gates_actions_floor_and_price() {
+        let oracle = test_oracle_keypair();
+        let model_hash = [11u8; 32];
+        let user = Pubkey::new_unique();
+        let admin = sample_admin(oracle.public.to_bytes(), model_hash);
+        let mut sale = sample_sale_config();
+
+        let payload = sample_payload(50, USER_MODE_GUEST, model_hash, user);
         let sig = sign_payload(&payload, &oracle);
+        let base = preview_event_from_payload(&payload, &sig, &admin, &sale, &user, NOW).unwrap();
+
+        sale.mode_policies[USER_MODE_GUEST as usize] = UserModePolicy {
+            allowed_actions: MODE_ACTION_PREVIEW,
+            price_multiplier_bps: 15_000,
+            dignity_floor: 40,
+        };
+        let boosted =
+            preview_event_from_payload(&payload, &sig, &admin, &sale, &user, NOW).unwrap();
+        assert_eq!(boosted.final_price, base.final_price * 3 / 2);
+        let execute_sig = sign_for(&payload, &oracle, SIGNING_PURPOSE_EXECUTE);
+        assert!(
+            execution_event_from_payload(&payload, &execute_sig, &admin, &sale, &user, NOW)
+                .is_err()
+        );
+        assert!(ensure_user_mode_action(&sale, USER_MODE_GUEST, MODE_ACTION_LIST).is_err());
+
+        sale.mode_policies[USER_MODE_GUEST as usize].dignity_floor = 60;
+        assert!(preview_event_from_payload(&payload, &sig, &admin, &sale, &user, NOW).is_err());
+
+        let at_cap = VrgdaQuote {
+            final_price: 120 * payload.initial_price,
+            is_infinite: false,
+            blocked: true,
+            effective_velocity_bps: 0,
+        };
+        let scaled = apply_price_multiplier(at_cap, 30_000, payload.initial_price);
+        assert_eq!(scaled.final_price, at_cap.final_price);
+    }
+
+    #[test]
+    fn adapter_requirements_are_enforced_during_validation() {
+        let model_hash = [11u8; 32];
+        let user = Pubkey::new_unique();
+        let admin = sample_admin([1u8; 32], model_hash);
+        let mut sale = sample_sale_config();
+        let mut payload = sample_payload(50, USER_MODE_VERIFIED, model_hash, user);
+
+        sale.required_adapter_mask = ADAPTER_SPOTIFY;
+        payload.adapter_mask = ADAPTER_GITHUB | ADAPTER_SPOTIFY;
+        assert!(validate_preview_request_fields(&payload, &admin, &sale, &user, NOW).is_ok());
+
+        payload.adapter_mask = ADAPTER_GITHUB;
+        assert!(validate_preview_request_fields(&payload, &admin, &sale, &user, NOW).is_err());
+
+        sale.required_adapter_mask = 0;
+        sale.forbidden_adapter_mask = ADAPTER_TWITTER;
+        payload.adapter_mask = ADAPTER_GITHUB | ADAPTER_TWITTER;
+        assert!(validate_preview_request_fields(&payload, &admin, &sale, &user, NOW).is_err());
+    }
+
+    #[test]
+    fn revoked_zk_provider_is_rejected() {
+        let model_hash = [11u8; 32];
+        let user = Pubkey::new_unique();
+        let mut admin = sample_admin([1u8; 32], model_hash);
+        let sale = sample_sale_config();
+        let mut payload = sample_payload(50, USER_MODE_VERIFIED, model_hash, user);
+        payload.zk_provider = 9;
+
+        assert!(validate_preview_request_fields(&payload, &admin, &sale, &user, NOW).is_ok());
+
+        set_zk_provider_bit(&mut admin.zk_provider_allowlist, 9, false);
+        assert!(!zk_provider_allowed(&admin.zk_provider_allowlist, 9));
+        assert!(zk_provider_allowed(&admin.zk_provider_allowlist, 8));
+        assert!(validate_preview_request_fields(&payload, &admin, &sale, &user, NOW).is_err());
+
+        set_zk_provider_bit(&mut admin.zk_provider_allowlist, 9, true);
+        assert!(validate_preview_request_fields(&payload, &admin, &sale, &user, NOW).is_ok());
+    }
+
+    #[test]
+    fn proof_quota_caps_uses_per_proof() {
+        assert_eq!(next_proof_quota_use(0, 2).unwrap(), 1);
+        assert_eq!(next_proof_quota_use(1, 2).unwrap(), 2);
+        assert!(next_proof_quota_use(2, 2).is_err());
+        assert!(next_proof_quota_use(0, 0).is_err());
+    }
+
+    #[test]
+    fn entry_secret_verifies_only_for_current_holder_and_fresh_rotation() {
+        let mint = Pubkey::new_unique();
+        let holder = Pubkey::new_unique();
+        let secret = [5u8; 32];
+        let mut receipt = TicketReceipt {
+            mint,
+            event_key: Pubkey::new_unique(),
+            original_buyer: holder,
+            current_holder: holder,
+            purchase_price: ONE_SOL_LAMPORTS,
+            last_sale_price: ONE_SOL_LAMPORTS,
+            issued_at: NOW,
+            last_transfer_at: NOW,
+            nonce: 1,
+            zk_proof_hash: [3u8; 32],
+            listed: false,
+            resale_count: 0,
+            bump: 255,
+            holder_user_mode: USER_MODE_VERIFIED,
+            class_id: TICKET_CLASS_NONE,
+            transfer_count: 0,
+            entry_secret_commitment: [0u8; 32],
+            entry_secret_rotated_at: 0,
+            redeemed_at: 0,
+            layout_version: account_layout::ticket_receipt::VERSION,
+        };
+        assert!(!verify_entry_secret(&receipt, &secret, NOW, 30));
+
+        receipt.entry_secret_commitment = entry_secret_commitment(&mint, &holder, &secret);
+        receipt.entry_secret_rotated_at = NOW;
+        assert!(verify_entry_secret(&receipt, &secret, NOW + 30, 30));
+        assert!(!verify_entry_secret(&receipt, &secret, NOW + 31, 30));
+        assert!(!verify_entry_secret(&receipt, &[6u8; 32], NOW, 30));
+        receipt.redeemed_at = NOW;
+        assert!(!verify_entry_secret(&receipt, &secret, NOW, 30));
+        receipt.redeemed_at = 0;
+
+        // A screenshot of the old holder's secret is useless after a transfer.
+        receipt.current_holder = Pubkey::new_unique();
+        assert!(!verify_entry_secret(&receipt, &secret, NOW, 30));
+        receipt.reset_entry_secret();
+        assert_eq!(receipt.entry_secret_commitment, [0u8; 32]);
+        assert!(!verify_entry_secret(&receipt, &secret, NOW, 30));
+    }
+
+    #[test]
+    fn v1_tickets_decode_only_with_the_v1_discriminator() {
+        assert_eq!(
+            V1_PROGRAM_ID.to_string(),
+            "7cVF3X3PvNLTNHd9EqvWHsrtHkeJXwRzBcRuoHoTThVT"
+        );
+
+        let buyer = Pubkey::new_unique();
+        let mut data = hashv(&[b"account:Ticket"]).to_bytes()[..8].to_vec();
+        data.extend_from_slice(buyer.as_ref());
+        data.extend_from_slice(&NOW.to_le_bytes());
+        assert_eq!(parse_v1_ticket(&data).unwrap(), (buyer, NOW));
+        assert!(parse_v1_ticket(&data[..V1_TICKET_LEN - 1]).is_err());
+
+        data[..8].copy_from_slice(&TicketReceipt::DISCRIMINATOR);
+        assert!(parse_v1_ticket(&data).is_err());
+    }
+
+    #[test]
+    fn shared_layout_matches_anchor_serialization() {
+        use account_layout::{
+            admin_config, global_config_v2, proof_quota, proof_use, ticket_receipt,
+        };
+
+        let admin = sample_admin([1u8; 32], [2u8; 32]);
+        let mut bytes = Vec::new();
+        admin.try_serialize(&mut bytes).unwrap();
+        assert_eq!(bytes.len(), admin_config::LEN);
+        assert_eq!(admin_config::LEN, 8 + AdminConfig::INIT_SPACE);
+        assert_eq!(
+            bytes[admin_config::ACTIVE_SCORING_MODEL_HASH..admin_config::BUMP],
+            [2u8; 32]
+        );
+        assert_eq!(
+            bytes[admin_config::CLUSTER_ID..admin_config::LAYOUT_VERSION],
+            TEST_CLUSTER_ID
+        );
+        assert_eq!(
+            stored_layout_version(&bytes, admin_config::LAYOUT_VERSION),
+            admin_config::VERSION
+        );
+        // An account from before versioning is one byte short and reads as version 0.
+        assert_eq!(
+            stored_layout_version(
+                &bytes[..admin_config::LAYOUT_VERSION],
+                admin_config::LAYOUT_VERSION
+            ),
+            0
+        );
+
+        assert_eq!(global_config_v2::LEN, 8 + GlobalConfigV2::INIT_SPACE);
+        assert_eq!(proof_use::LEN, 8 + ProofUse::INIT_SPACE);
+        assert_eq!(proof_quota::LEN, 8 + ProofQuota::INIT_SPACE);
+        assert_eq!(ticket_receipt::LEN, 8 + TicketReceipt::INIT_SPACE);
+
+        let owner = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let receipt = TicketReceipt {
+            mint,
+            event_key: Pubkey::new_unique(),
+            original_buyer: owner,
+            current_holder: owner,
+            purchase_price: ONE_SOL_LAMPORTS,
+            last_sale_price: ONE_SOL_LAMPORTS,
+            issued_at: NOW,
+            last_transfer_at: NOW,
+            nonce: 9,
+            zk_proof_hash: [4u8; 32],
+            listed: false,
+            resale_count: 0,
+            bump: 254,
+            holder_user_mode: USER_MODE_VERIFIED,
+            class_id: 3,
+            transfer_count: 0,
+            entry_secret_commitment: [0u8; 32],
+            entry_secret_rotated_at: 0,
+            redeemed_at: 0,
+            layout_version: ticket_receipt::VERSION,
+        };
+        let mut expected = Vec::new();
+        receipt.try_serialize(&mut expected).unwrap();
+
+        let key = Pubkey::new_unique();
+        let mut lamports = 0u64;
+        let mut data = vec![0xffu8; ticket_receipt::LEN];
+        let info = AccountInfo::new(
+            &key,
+            false,
+            true,
+            &mut lamports,
+            &mut data,
+            &crate::ID,
+            false,
+            0,
+        );
+        write_ticket_receipt_account(
+            &info,
+            IssuedTicketReceipt {
+                mint: &mint,
+                event_key: &receipt.event_key,
+                owner: &owner,
+                price: ONE_SOL_LAMPORTS,
+                issued_at: NOW,
+                nonce: 9,
+                zk_proof_hash: &[4u8; 32],
+                holder_user_mode: USER_MODE_VERIFIED,
+                class_id: 3,
+                bump: 254,
+            },
+        )
+        .unwrap();
+        assert_eq!(info.try_borrow_data().unwrap()[..], expected[..]);
+    }
+
+    #[test]
+    fn quote_return_data_round_trips_and_rejects_other_versions() {
+        let payload = sample_payload(72, USER_MODE_VERIFIED, [7u8; 32], Pubkey::new_unique());
+        let quote = quote_from_payload(&payload).unwrap();
+        let returned = quote_return_v0(SIGNING_PURPOSE_SHIELD_GATE, &payload, &quote);
+        let bytes = encode_quote_return_v0(&returned);
+
+        assert_eq!(decode_quote_return_v0(&bytes).unwrap(), returned);
+        assert_eq!(returned.final_price, quote.final_price);
+        assert_eq!(returned.nonce, payload.nonce);
+
+        let mut future = bytes;
+        future[0] = QUOTE_RETURN_VERSION_V0 + 1;
+        assert!(decode_quote_return_v0(&future).is_err());
+        assert!(decode_quote_return_v0(&bytes[..QUOTE_RETURN_V0_LEN - 1]).is_err());
+    }
+
+    #[test]
+    fn reputation_adjusts_dignity_within_bounds() {
+        let mut reputation = Reputation {
+            wallet: Pubkey::new_unique(),
+            fair_purchases: 0,
+            redeemed_tickets: 0,
+            flips: 0,
+            blocked_attempts: 0,
+            updated_at: NOW,
+            bump: 255,
+        };
+        assert_eq!(reputation_adjusted_dignity(60, &reputation), 60);
+
+        reputation.fair_purchases = 3;
+        reputation.redeemed_tickets = 2;
+        assert_eq!(reputation_adjusted_dignity(60, &reputation), 76);
+        assert_eq!(reputation_adjusted_dignity(95, &reputation), 100);
+
+        // Attendance bonus is capped; a flip still costs the full penalty.
+        reputation.redeemed_tickets = 40;
+        reputation.flips = 1;
+        assert_eq!(
+            reputation_adjusted_dignity(60, &reputation),
+            (60 + REPUTATION_BONUS_CAP - REPUTATION_FLIP_PENALTY) as u8
+        );
+
+        reputation.blocked_attempts = u32::MAX;
+        assert_eq!(reputation_adjusted_dignity(100, &reputation), 0);
+    }
+
+    #[test]
+    fn audit_log_keeps_the_latest_entries_in_order() {
+        let mut log = AuditLog {
+            total_entries: 0,
+            next_index: 0,
+            entries: [AuditEntry::default(); AUDIT_LOG_CAPACITY],
+            bump: 255,
+        };
+        let signer = Pubkey::new_unique();
+        assert!(audit_log_entries(&log).is_empty());
+
+        for step in 0..(AUDIT_LOG_CAPACITY as u8 + 3) {
+            push_audit_entry(
+                &mut log,
+                AUDIT_ACTION_ORACLE_ROTATED,
+                signer,
+                NOW + i64::from(step),
+                [step; 32],
+                [step + 1; 32],
+            );
+        }
+
+        let entries = audit_log_entries(&log);
+        assert_eq!(log.total_entries, AUDIT_LOG_CAPACITY as u64 + 3);
+        assert_eq!(entries.len(), AUDIT_LOG_CAPACITY);
+        assert_eq!(entries[0].sequence, 3);
+        assert_eq!(entries[0].old_value, [3u8; 32]);
+        assert_eq!(
+            entries[AUDIT_LOG_CAPACITY - 1].sequence,
+            log.total_entries - 1
+        );
+        assert!(entries
+            .windows(2)
+            .all(|pair| pair[0].sequence + 1 == pair[1].sequence));
+
+        let event_key = Pubkey::new_unique();
+        let quota = sale_audit_value(&event_key, &1u16).unwrap();
+        assert_eq!(quota, sale_audit_value(&event_key, &1u16).unwrap());
+        assert_ne!(quota, sale_audit_value(&event_key, &2u16).unwrap());
+        assert_ne!(
+            quota,
+            sale_audit_value(&Pubkey::new_unique(), &1u16).unwrap()
+        );
+    }
+
+    fn sample_hide_sis_session(dignity_score: u8) -> HideSisSession {
+        HideSisSession {
+            player: Pubkey::new_unique(),
+            session_id: 1,
+            user_mode: USER_MODE_VERIFIED,
+            dignity_score,
+            relation_score: 0,
+            pollution_score: 0,
+            pollution_flag: false,
+            c2_n3_passed: false,
+            truth_unlocked: false,
+            current_node: C1_N1_OPENING_PROBE,
+            chapter_budget_bps: [CHAPTER_BUDGET_BPS; CHAPTER_COUNT],
+            final_decision_code: DECISION_NONE,
+            ending_code: ENDING_NONE,
+            humanity_score: 0,
+            finalized: false,
+            started_at: NOW,
+            updated_at: NOW,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn hide_sis_session_enforces_transitions_and_reaches_true_ending() {
+        use hide_sis_types::*;
+
+        let mut session = sample_hide_sis_session(72);
+        assert!(apply_interrogation_turn(&mut session, C1_N2_PRIVATE_PROOF, 1).is_err());
+        assert!(apply_chapter_decision(&mut session, C1_N1_OPENING_PROBE, PACT_COMMIT).is_err());
+        assert!(finalize_hide_sis_session(&mut session).is_err());
+
+        for (node, choice) in [
+            (C1_N1_OPENING_PROBE, 2),
+            (C1_N2_PRIVATE_PROOF, 1),
+            (C1_N3_LAST_CALL_PRESSURE, 3),
+            (C2_N1_TERMS_EXCHANGE, 1),
+            (C2_N2_FOOTPRINT_SWAP, 2),
+            (C2_N3_KILLER_REVEAL_GATE, 1),
+        ] {
+            let event = apply_interrogation_turn(&mut session, node, choice).unwrap();
+            assert_eq!(event.node_id, node);
+            assert!(!event.pollution_flag);
+        }
+        assert!(session.c2_n3_passed);
+        assert!(apply_interrogation_turn(&mut session, C2_N4_SECRET_PACT, 1).is_err());
+
+        apply_chapter_decision(&mut session, C2_N4_SECRET_PACT, PACT_COMMIT).unwrap();
+        apply_interrogation_turn(&mut session, C3_N1_SYSTEM_BREAKDOWN, 1).unwrap();
+        assert!(finalize_hide_sis_session(&mut session).is_err());
+        let decision =
+            apply_chapter_decision(&mut session, C3_N2_FINAL_CHOICE, END_BURY_TRUTH).unwrap();
+        assert_eq!(decision.truth_progress, 100);
+        assert_eq!(session.current_node, C3_N3_ENDING_RESOLVE);
+
+        let finalized = finalize_hide_sis_session(&mut session).unwrap();
+        assert_eq!(finalized.ending_code, SILK_BURIAL_TRUE);
+        assert!(finalized.truth_unlocked);
+        assert_eq!(session.dignity_score, 80);
+        assert_eq!(session.relation_score, RELATION_MAX);
+        assert!(finalize_hide_sis_session(&mut session).is_err());
+        assert!(apply_interrogation_turn(&mut session, C3_N3_ENDING_RESOLVE, 1).is_err());
+    }
+
+    #[test]
+    fn hide_sis_risky_choices_raise_pollution() {
+        use hide_sis_types::*;
+
+        let mut session = sample_hide_sis_session(50);
+        apply_interrogation_turn(&mut session, C1_N1_OPENING_PROBE, 1).unwrap();
+        let turn = apply_interrogation_turn(&mut session, C1_N2_PRIVATE_PROOF, 2).unwrap();
+        assert!(turn.pollution_flag);
+        assert!(session.pollution_flag);
+        assert_eq!(session.dignity_score, 47);
+    }
+
+    #[test]
+    fn graph_snapshot_versions_are_strictly_monotonic() {
+        assert!(ensure_graph_version_advances(0, 1).is_ok());
+        assert!(ensure_graph_version_advances(7, 9).is_ok());
+        assert!(ensure_graph_version_advances(7, 7).is_err());
+        assert!(ensure_graph_version_advances(7, 3).is_err());
+        assert!(ensure_graph_version_advances(0, 0).is_err());
+    }
+
+    #[test]
+    fn membership_tiers_gate_sales_and_price_upgrades() {
+        let config = MembershipConfig {
+            tier_prices: [700_000_000, 1_300_000_000],
+            period_secs: 30 * 86_400,
+            bump: 255,
+        };
+        assert_eq!(
+            membership_charge_base(&config, MEMBERSHIP_TIER_NONE, MEMBERSHIP_TIER_CORE).unwrap(),
+            700_000_000
+        );
+        assert_eq!(
+            membership_charge_base(&config, MEMBERSHIP_TIER_CORE, MEMBERSHIP_TIER_INNER_CIRCLE)
+                .unwrap(),
+            600_000_000
+        );
+        assert!(
+            membership_charge_base(&config, MEMBERSHIP_TIER_CORE, MEMBERSHIP_TIER_CORE).is_err()
+        );
+        assert!(membership_charge_base(&config, MEMBERSHIP_TIER_NONE, 3).is_err());
+
+        let mut sale = sample_sale_config();
+        let membership = Membership {
+            wallet: Pubkey::new_unique(),
+            tier: MEMBERSHIP_TIER_CORE,
+            started_at: NOW,
+            expires_at: NOW + config.period_secs,
+            total_paid: 700_000_000,
+            bump: 255,
+        };
+        assert!(ensure_membership_tier(&sale, None, NOW).is_ok());
 
-        assert!(execution_event_from_payload(&payload, &sig, &admin, &user, NOW).is_err());
+        sale.min_membership_tier = MEMBERSHIP_TIER_CORE;
+        assert!(ensure_membership_tier(&sale, None, NOW).is_err());
+        assert!(ensure_membership_tier(&sale, Some(&membership), NOW).is_ok());
+        assert!(ensure_membership_tier(&sale, Some(&membership), membership.expires_at).is_err());
+
+        sale.min_membership_tier = MEMBERSHIP_TIER_INNER_CIRCLE;
+        assert!(ensure_membership_tier(&sale, Some(&membership), NOW).is_err());
+    }
+
+    #[test]
+    fn ticket_transfers_respect_the_per_ticket_limit() {
+        assert_eq!(next_ticket_transfer_count(0, 2).unwrap(), 1);
+        assert_eq!(next_ticket_transfer_count(1, 2).unwrap(), 2);
+        assert!(next_ticket_transfer_count(2, 2).is_err());
+        assert_eq!(next_ticket_transfer_count(40, 0).unwrap(), 41);
+        assert!(next_ticket_transfer_count(u16::MAX, 0).is_err());
+
+        assert_eq!(
+            transferred_user_mode(USER_MODE_BOT_SUSPECTED, USER_MODE_VERIFIED),
+            USER_MODE_BOT_SUSPECTED
+        );
+        assert_eq!(
+            transferred_user_mode(USER_MODE_VERIFIED, USER_MODE_GUEST),
+            USER_MODE_GUEST
+        );
+        assert_eq!(
+            transferred_user_mode(USER_MODE_VERIFIED, USER_MODE_VERIFIED),
+            USER_MODE_VERIFIED
+        );
+    }
+
+    #[test]
+    fn lottery_takes_weighted_top_k_independent_of_ranking_order() {
+        let lottery_key = Pubkey::new_unique();
+        let randomness = lottery_randomness(&lottery_key, &[7u8; 32], &[9u8; 32], 400, 1_200);
+        assert_ne!(
+            randomness,
+            lottery_randomness(&lottery_key, &[8u8; 32], &[9u8; 32], 400, 1_200)
+        );
+        assert_ne!(
+            randomness,
+            lottery_randomness(&lottery_key, &[7u8; 32], &[10u8; 32], 400, 1_200)
+        );
+        let entries: Vec<LotteryRank> = (0..400u32)
+            .map(|index| {
+                let mut proof_hash = [0u8; 32];
+                proof_hash[..4].copy_from_slice(&index.to_le_bytes());
+                let dignity = if index % 2 == 0 { 95 } else { 10 };
+                LotteryRank {
+                    zk_proof_hash: proof_hash,
+                    score: lottery_entry_score(&randomness, &proof_hash, lottery_weight(dignity)),
+                }
+            })
+            .collect();
+        let rank_all = |order: &mut dyn Iterator<Item = &LotteryRank>| {
+            let mut board = [LotteryRank::default(); MAX_LOTTERY_WINNERS as usize];
+            let mut len = 0;
+            for entry in order {
+                len = insert_lottery_rank(&mut board[..20], len, *entry);
+            }
+            (board, len)
+        };
+
+        let (forward, forward_len) = rank_all(&mut entries.iter());
+        let (backward, backward_len) = rank_all(&mut entries.iter().rev());
+        assert_eq!(forward_len, 20);
+        assert_eq!((forward, forward_len), (backward, backward_len));
+        assert!(forward[..20]
+            .windows(2)
+            .all(|pair| pair[0].score >= pair[1].score));
+
+        let mut expected: Vec<LotteryRank> = entries.clone();
+        expected.sort_by(|a, b| (b.score, b.zk_proof_hash).cmp(&(a.score, a.zk_proof_hash)));
+        assert_eq!(&forward[..20], &expected[..20]);
+
+        // Even indices carry weight 5, odd ones weight 1.
+        let high_wins = forward[..20]
+            .iter()
+            .filter(|rank| u32::from_le_bytes(rank.zk_proof_hash[..4].try_into().unwrap()) % 2 == 0)
+            .count();
+        assert_eq!(lottery_weight(10), 1);
+        assert_eq!(lottery_weight(95), 5);
+        assert!(high_wins >= 17, "high wins {high_wins}");
+
+        let mut lottery = Lottery {
+            event_key: Pubkey::new_unique(),
+            registration_start: 0,
+            registration_end: 1,
+            winner_slots: 20,
+            entry_count: 400,
+            total_weight: 1_200,
+            draw_slot: 0,
+            randomness,
+            drawn: true,
+            claimed: 0,
+            bump: 255,
+            ranked_count: 400,
+            board: forward,
+            board_len: forward_len,
+            seed_commitment: lottery_seed_commitment(&[7u8; 32]),
+            seed: [7u8; 32],
+            seed_revealed: true,
+        };
+        assert!(lottery_is_winner(&lottery, &expected[0].zk_proof_hash));
+        assert!(lottery_is_winner(&lottery, &expected[19].zk_proof_hash));
+        assert!(!lottery_is_winner(&lottery, &expected[20].zk_proof_hash));
+        lottery.winner_slots = 5;
+        assert!(!lottery_is_winner(&lottery, &expected[5].zk_proof_hash));
+    }
+
+    #[test]
+    fn slot_hash_lookup_uses_first_block_at_or_after_draw_slot() {
+        let mut data = 3u64.to_le_bytes().to_vec();
+        for (slot, byte) in [(105u64, 5u8), (103, 3), (100, 0)] {
+            data.extend_from_slice(&slot.to_le_bytes());
+            data.extend_from_slice(&[byte; 32]);
+        }
+
+        assert_eq!(slot_hash_at_or_after(&data, 103), Some([3u8; 32]));
+        assert_eq!(slot_hash_at_or_after(&data, 101), Some([3u8; 32]));
+        assert_eq!(slot_hash_at_or_after(&data, 100), Some([0u8; 32]));
+        assert_eq!(slot_hash_at_or_after(&data, 106), None);
+        assert_eq!(slot_hash_at_or_after(&data, 99), None);
+    }
+
+    #[test]
+    fn purchase_commitment_binds_payload_salt_and_window() {
+        let model_hash = [11u8; 32];
+        let payload = sample_payload(70, USER_MODE_VERIFIED, model_hash, Pubkey::new_unique());
+        let commitment = purchase_commitment_hash(&payload, &[1u8; 32]);
+
+        assert_eq!(purchase_commitment_hash(&payload, &[1u8; 32]), commitment);
+        assert_ne!(purchase_commitment_hash(&payload, &[2u8; 32]), commitment);
+        let mut other = payload.clone();
+        other.nonce += 1;
+        assert_ne!(purchase_commitment_hash(&other, &[1u8; 32]), commitment);
+
+        assert!(ensure_reveal_window(9, 10, 20).is_err());
+        assert!(ensure_reveal_window(10, 10, 20).is_ok());
+        assert!(ensure_reveal_window(20, 10, 20).is_ok());
+        assert!(ensure_reveal_window(21, 10, 20).is_err());
+
+        assert_eq!(
+            split_commit_deposit(1_000_000, 2_500).unwrap(),
+            (250_000, 750_000)
+        );
+        assert_eq!(
+            split_commit_deposit(1_000_000, 10_000).unwrap(),
+            (1_000_000, 0)
+        );
+        assert!(split_commit_deposit(1_000_000, 10_001).is_err());
+    }
+
+    #[test]
+    fn sealed_bid_board_keeps_top_k_and_clears_uniformly() {
+        let mut auction = SealedBidAuction {
+            event_key: Pubkey::new_unique(),
+            commit_end: NOW,
+            reveal_end: NOW + 60,
+            winner_slots: 2,
+            reserve_price: 100,
+            board: [SealedBidRank::default(); SEALED_BID_BOARD_LEN],
+            board_len: 0,
+            bump: 255,
+            ticket_class: 2,
+            claim_end: NOW + 120,
+        };
+        let bids: Vec<(Pubkey, u64)> = [150, 400, 150, 300, 120]
+            .iter()
+            .map(|amount| (Pubkey::new_unique(), *amount))
+            .collect();
+
+        assert!(!sealed_bid_is_winner(&auction, &bids[0].0));
+        assert_eq!(sealed_bid_clearing_price(&auction), 100);
+
+        for (bid, amount) in &bids {
+            let capacity = auction.winner_slots as usize + 1;
+            let board_len = auction.board_len;
+            auction.board_len = insert_sealed_bid_rank(
+                &mut auction.board[..capacity],
+                board_len,
+                SealedBidRank {
+                    bid: *bid,
+                    amount: *amount,
+                },
+            );
+        }
+
+        assert_eq!(auction.board_len, 3);
+        assert!(sealed_bid_is_winner(&auction, &bids[1].0));
+        assert!(sealed_bid_is_winner(&auction, &bids[3].0));
+        assert!(!sealed_bid_is_winner(&auction, &bids[0].0));
+        assert!(!sealed_bid_is_winner(&auction, &bids[2].0));
+        assert_eq!(auction.board[2].bid, bids[0].0);
+        assert_eq!(sealed_bid_clearing_price(&auction), 150);
+
+        assert_eq!(
+            sealed_bid_forfeit(&auction, &bids[0].0, NOW + 60).unwrap(),
+            0
+        );
+        assert!(sealed_bid_forfeit(&auction, &bids[1].0, NOW + 119).is_err());
+        assert_eq!(
+            sealed_bid_forfeit(&auction, &bids[1].0, NOW + 120).unwrap(),
+            150
+        );
+
+        let bidder = Pubkey::new_unique();
+        let commitment = sealed_bid_commitment(&bidder, 300, &[4u8; 32]);
+        assert_ne!(sealed_bid_commitment(&bidder, 301, &[4u8; 32]), commitment);
+        assert_ne!(
+            sealed_bid_commitment(&Pubkey::new_unique(), 300, &[4u8; 32]),
+            commitment
+        );
+    }
+
+    #[test]
+    fn ticket_class_binds_signature_price_and_supply() {
+        let oracle = test_oracle_keypair();
+        let model_hash = [11u8; 32];
+        let user = Pubkey::new_unique();
+        let admin = sample_admin(oracle.public.to_bytes(), model_hash);
+        let sale = sample_sale_config();
+        let vip = TicketClass {
+            event_key: sale.event_key,
+            class_id: 2,
+            base_price: ONE_SOL_LAMPORTS,
+            velocity_multiplier_bps: 20_000,
+            supply: 1,
+            sold: 0,
+            bump: 255,
+            allocation_mode: ALLOCATION_MODE_OPEN,
+        };
+        let payload = sample_payload(60, USER_MODE_VERIFIED, model_hash, user);
+        let vip_domain = shield_signing_domain(&admin, SIGNING_PURPOSE_EXECUTE, vip.class_id);
+        let vip_sig = oracle
+            .sign(&serialize_shield_message_v0(&payload, &vip_domain))
+            .to_bytes();
+        let ga_sig = sign_for(&payload, &oracle, SIGNING_PURPOSE_EXECUTE);
+
+        let vip_event =
+            execution_event_for_class(&payload, &vip_sig, &admin, &sale, Some(&vip), &user, NOW)
+                .unwrap();
+        let ga_event =
+            execution_event_from_payload(&payload, &ga_sig, &admin, &sale, &user, NOW).unwrap();
+        assert!(vip_event.final_price > ga_event.final_price);
+        assert!(execution_event_for_class(
+            &payload,
+            &ga_sig,
+            &admin,
+            &sale,
+            Some(&vip),
+            &user,
+            NOW
+        )
+        .is_err());
+        assert!(
+            execution_event_from_payload(&payload, &vip_sig, &admin, &sale, &user, NOW).is_err()
+        );
+
+        let mut cheap = payload.clone();
+        cheap.initial_price = ONE_SOL_LAMPORTS / 2;
+        assert!(quote_for_class(&cheap, &sale, Some(&vip)).is_err());
+
+        let classed_sale = SaleConfig {
+            ticket_class_count: 1,
+            ..sale
+        };
+        assert!(quote_for_class(&payload, &classed_sale, None).is_err());
+        assert!(quote_for_class(&payload, &classed_sale, Some(&vip)).is_ok());
+
+        assert_eq!(next_ticket_class_sale(0, vip.supply).unwrap(), 1);
+        assert!(next_ticket_class_sale(1, vip.supply).is_err());
+    }
+
+    #[test]
+    fn dutch_pricing_needs_a_descending_schedule_from_the_start_price() {
+        let start = ONE_SOL_LAMPORTS;
+        assert!(
+            validate_pricing_strategy(PRICING_STRATEGY_DUTCH, start, start / 2, 1_000, 60).is_ok()
+        );
+        assert!(
+            validate_pricing_strategy(PRICING_STRATEGY_DUTCH, start, start / 2, 0, 60).is_err()
+        );
+        assert!(
+            validate_pricing_strategy(PRICING_STRATEGY_DUTCH, start, start / 2, 1_000, 0).is_err()
+        );
+        assert!(
+            validate_pricing_strategy(PRICING_STRATEGY_DUTCH, start, start + 1, 1_000, 60).is_err()
+        );
+        assert!(validate_pricing_strategy(PRICING_STRATEGY_VRGDA, 0, 0, 0, 0).is_ok());
+        assert!(validate_pricing_strategy(2, start, 0, 1_000, 60).is_err());
+
+        let mut sale = sample_sale_config();
+        sale.pricing_strategy = PRICING_STRATEGY_DUTCH;
+        sale.dutch_start_price = start;
+        sale.dutch_floor_price = start / 2;
+        sale.dutch_step_amount = start / 10;
+        sale.dutch_step_interval_secs = 1;
+        let mut payload = sample_payload(60, USER_MODE_VERIFIED, [11u8; 32], Pubkey::new_unique());
+        payload.initial_price = start;
+        payload.time_elapsed = 100;
+        assert!(quote_for_sale(&payload, &sale).is_ok());
+        payload.initial_price = start * 10;
+        assert!(quote_for_sale(&payload, &sale).is_err());
     }
 
     #[test]
@@ -15,6 +15,17 @@ pub struct VrgdaInput {
     pub dignity_score: u8,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DutchAuctionInput {
+    pub start_price: u64,
+    pub floor_price: u64,
+    pub step_amount: u64,
+    pub step_interval_secs: u64,
+    pub sales_velocity_bps: i64,
+    pub time_elapsed: u64,
+    pub dignity_score: u8,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct VrgdaQuote {
     pub final_price: u64,
@@ -28,6 +39,7 @@ pub enum VrgdaMathError {
     InvalidSalesVelocity,
     InvalidDignityScore,
     InvalidTimeElapsed,
+    InvalidDutchSchedule,
 }
 
 pub fn calculate_vrgda_quote(input: VrgdaInput) -> Result<VrgdaQuote, VrgdaMathError> {
@@ -42,57 +54,109 @@ pub fn calculate_vrgda_quote(input: VrgdaInput) -> Result<VrgdaQuote, VrgdaMathE
     }
 
     let base_price = input.initial_price.max(1);
-    let score_distance = i64::from(100u8.saturating_sub(input.dignity_score));
+    Ok(shielded_quote(
+        base_price,
+        base_price,
+        input.sales_velocity_bps,
+        input.time_elapsed,
+        input.dignity_score,
+    ))
+}
+
+pub fn calculate_dutch_quote(input: DutchAuctionInput) -> Result<VrgdaQuote, VrgdaMathError> {
+    if input.dignity_score > 100 {
+        return Err(VrgdaMathError::InvalidDignityScore);
+    }
+    if input.sales_velocity_bps <= -BPS_SCALE {
+        return Err(VrgdaMathError::InvalidSalesVelocity);
+    }
+    if input.time_elapsed > MAX_TIME_ELAPSED_SECS {
+        return Err(VrgdaMathError::InvalidTimeElapsed);
+    }
+    if input.step_interval_secs == 0 || input.floor_price > input.start_price {
+        return Err(VrgdaMathError::InvalidDutchSchedule);
+    }
+
+    let start_price = input.start_price.max(1);
+    let steps = input.time_elapsed / input.step_interval_secs;
+    let schedule_price = start_price
+        .saturating_sub(steps.saturating_mul(input.step_amount))
+        .max(input.floor_price)
+        .max(1);
+
+    Ok(shielded_quote(
+        schedule_price,
+        start_price,
+        input.sales_velocity_bps,
+        input.time_elapsed,
+        input.dignity_score,
+    ))
+}
+
+// Heat growth, loyalty discount and bot cap shared by every curve. `curve_price` is the
+// curve's price before shielding; caps and the block threshold scale off `reference_price`.
+fn shielded_quote(
+    curve_price: u64,
+    reference_price: u64,
+    sales_velocity_bps: i64,
+    time_elapsed: u64,
+    dignity_score: u8,
+) -> VrgdaQuote {
+    let score_distance = i64::from(100u8.saturating_sub(dignity_score));
     let heat_weight_bps = score_distance * score_distance;
 
-    let effective_velocity_bps = match (input.sales_velocity_bps as i128)
+    let effective_velocity_bps = match (sales_velocity_bps as i128)
         .checked_mul(heat_weight_bps as i128)
         .and_then(|v| v.checked_div(BPS_SCALE as i128))
     {
         Some(v) => match i64::try_from(v) {
             Ok(velocity) => velocity,
-            Err(_) => return Ok(infinity_quote(input.sales_velocity_bps)),
+            Err(_) => return infinity_quote(sales_velocity_bps),
         },
-        None => return Ok(infinity_quote(input.sales_velocity_bps)),
+        None => return infinity_quote(sales_velocity_bps),
     };
 
-    let exp_price = match compute_exponential_price(base_price, effective_velocity_bps, input.time_elapsed) {
-        Some(price) => price,
-        None => return Ok(infinity_quote(effective_velocity_bps)),
-    };
+    let exp_price =
+        match compute_exponential_price(curve_price, effective_velocity_bps, time_elapsed) {
+            Some(price) => price,
+            None => return infinity_quote(effective_velocity_bps),
+        };
 
-    let loyalty_discount_bps = LOYALTY_BASE_BPS
-        .checked_sub(
-            u128::from(input.dignity_score.saturating_sub(LOYALTY_THRESHOLD))
-                .checked_mul(LOYALTY_POINT_DISCOUNT_BPS)
-                .unwrap_or(LOYALTY_BASE_BPS),
-        )
-        .unwrap_or(0);
+    let loyalty_discount_bps = LOYALTY_BASE_BPS.saturating_sub(
+        u128::from(dignity_score.saturating_sub(LOYALTY_THRESHOLD))
+            .checked_mul(LOYALTY_POINT_DISCOUNT_BPS)
+            .unwrap_or(LOYALTY_BASE_BPS),
+    );
 
     let final_price_u128 = match exp_price
         .checked_mul(loyalty_discount_bps)
         .and_then(|v| v.checked_div(LOYALTY_BASE_BPS))
     {
         Some(value) => value.max(1),
-        None => return Ok(infinity_quote(effective_velocity_bps)),
+        None => return infinity_quote(effective_velocity_bps),
     };
 
-    let capped_price_u128 = final_price_u128.min(u128::from(base_price).saturating_mul(BOT_PRICE_CAP_MULTIPLIER));
+    let capped_price_u128 = final_price_u128.min(bot_price_cap(reference_price));
 
     let final_price = match u64::try_from(capped_price_u128) {
         Ok(price) => price.max(1),
-        Err(_) => return Ok(infinity_quote(effective_velocity_bps)),
+        Err(_) => return infinity_quote(effective_velocity_bps),
     };
 
-    let blocked_threshold = u128::from(base_price).saturating_mul(BLOCK_MULTIPLIER);
+    let blocked_threshold = u128::from(reference_price).saturating_mul(BLOCK_MULTIPLIER);
     let blocked = u128::from(final_price) >= blocked_threshold;
 
-    Ok(VrgdaQuote {
+    VrgdaQuote {
         final_price,
         is_infinite: false,
         blocked,
         effective_velocity_bps,
-    })
+    }
+}
+
+// Ceiling for any quote derived from `reference_price`, including mode-scaled ones.
+pub fn bot_price_cap(reference_price: u64) -> u128 {
+    u128::from(reference_price.max(1)).saturating_mul(BOT_PRICE_CAP_MULTIPLIER)
 }
 
 fn infinity_quote(effective_velocity_bps: i64) -> VrgdaQuote {
@@ -242,6 +306,61 @@ mod tests {
         assert_eq!(v90.final_price, 997_977_140);
     }
 
+    #[test]
+    fn dutch_schedule_steps_down_to_floor_with_dignity_shield() {
+        let human = DutchAuctionInput {
+            start_price: 10 * ONE_SOL_LAMPORTS,
+            floor_price: 4 * ONE_SOL_LAMPORTS,
+            step_amount: ONE_SOL_LAMPORTS,
+            step_interval_secs: 60,
+            sales_velocity_bps: 0,
+            time_elapsed: 0,
+            dignity_score: 70,
+        };
+
+        assert_eq!(
+            calculate_dutch_quote(human).unwrap().final_price,
+            10 * ONE_SOL_LAMPORTS
+        );
+        let after_two_steps = calculate_dutch_quote(DutchAuctionInput {
+            time_elapsed: 179,
+            ..human
+        })
+        .unwrap();
+        assert_eq!(after_two_steps.final_price, 8 * ONE_SOL_LAMPORTS);
+        let at_floor = calculate_dutch_quote(DutchAuctionInput {
+            time_elapsed: 3_600,
+            ..human
+        })
+        .unwrap();
+        assert_eq!(at_floor.final_price, 4 * ONE_SOL_LAMPORTS);
+
+        let loyal = calculate_dutch_quote(DutchAuctionInput {
+            time_elapsed: 3_600,
+            dignity_score: 90,
+            ..human
+        })
+        .unwrap();
+        assert!(loyal.final_price < at_floor.final_price);
+
+        let bot = calculate_dutch_quote(DutchAuctionInput {
+            time_elapsed: 12,
+            sales_velocity_bps: 5_000,
+            dignity_score: 0,
+            ..human
+        })
+        .unwrap();
+        assert!(bot.blocked);
+        assert_eq!(bot.final_price, 120 * 10 * ONE_SOL_LAMPORTS);
+
+        let err = calculate_dutch_quote(DutchAuctionInput {
+            floor_price: 11 * ONE_SOL_LAMPORTS,
+            ..human
+        })
+        .unwrap_err();
+        assert_eq!(err, VrgdaMathError::InvalidDutchSchedule);
+    }
+
     #[test]
     fn rejects_invalid_input_ranges() {
         let err = calculate_vrgda_quote(VrgdaInput {
@@ -0,0 +1,47 @@
+use anchor_lang::{
+    prelude::{AccountInfo, Pubkey},
+    solana_program::entrypoint::ProgramResult,
+    AccountSerialize,
+};
+use solana_sdk::account::Account;
+use unforgiven_v2::ID;
+
+pub const ONE_SOL_LAMPORTS: u64 = 1_000_000_000;
+
+// Anchor's entrypoint wants the account slice to outlive the call.
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    unforgiven_v2::entry(program_id, accounts, data)
+}
+
+// A funded program-owned account holding `state`, zero-padded to `space`.
+pub fn program_account<T: AccountSerialize>(state: &T, space: usize) -> Account {
+    let mut data = Vec::with_capacity(space);
+    state.try_serialize(&mut data).unwrap();
+    data.resize(space, 0);
+    Account {
+        lamports: ONE_SOL_LAMPORTS,
+        data,
+        owner: ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+// Serializes the current layout and cuts it back to the length of an older account.
+pub fn baseline_account<T: AccountSerialize>(state: &T, len: usize) -> Account {
+    let mut data = Vec::new();
+    state.try_serialize(&mut data).unwrap();
+    data.truncate(len);
+    Account {
+        lamports: ONE_SOL_LAMPORTS / 100,
+        data,
+        owner: ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
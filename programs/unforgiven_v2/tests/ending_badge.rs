@@ -0,0 +1,242 @@
+pub mod common;
+
+use anchor_lang::{
+    prelude::Pubkey,
+    solana_program::{program_option::COption, program_pack::Pack, sysvar},
+    InstructionData, Space, ToAccountMetas,
+};
+use anchor_spl::token::spl_token::{
+    self,
+    state::{Account as SplTokenAccount, AccountState, Mint as SplMint},
+};
+use common::{process_instruction, program_account, ONE_SOL_LAMPORTS};
+use ed25519_dalek::{Keypair as DalekKeypair, PublicKey as DalekPublicKey, SecretKey, Signer as _};
+use solana_program_test::{processor, ProgramTest};
+use solana_sdk::{
+    account::Account,
+    clock::Clock,
+    ed25519_instruction::new_ed25519_instruction,
+    instruction::Instruction,
+    signature::{Keypair, Signer},
+    system_program,
+    transaction::Transaction,
+};
+use unforgiven_v2::{
+    account_layout,
+    hide_sis_types::{CHAPTER_BUDGET_BPS, CHAPTER_COUNT, DECISION_NONE, FRAMED_AND_JAILED},
+    serialize_shield_message_v0, AdminConfig, HideSisSession, ShieldPayloadV0, SigningDomain, ID,
+    POLICY_VERSION_V0, SIGNING_PURPOSE_ENDING_BADGE, TICKET_CLASS_NONE, USER_MODE_VERIFIED,
+    ZK_PROVIDER_ALLOWLIST_LEN,
+};
+
+const SESSION_ID: u64 = 7;
+
+// The badge must end up frozen in the player's account with no mint authority left, so it
+// can be neither moved, burned nor minted again.
+#[tokio::test]
+async fn ending_badge_is_frozen_and_mint_authority_revoked() {
+    let mut program_test = ProgramTest::new("unforgiven_v2", ID, processor!(process_instruction));
+    program_test.prefer_bpf(false);
+    let mut context = program_test.start_with_context().await;
+
+    let secret = SecretKey::from_bytes(&[7u8; 32]).unwrap();
+    let oracle = DalekKeypair {
+        public: DalekPublicKey::from(&secret),
+        secret,
+    };
+    let player = Keypair::new();
+    let player_key = player.pubkey();
+    let model_hash = [11u8; 32];
+    let cluster_id = context.genesis_config().hash().to_bytes();
+    let (admin_config, admin_bump) = Pubkey::find_program_address(&[b"admin_config_v2"], &ID);
+    let (session, session_bump) = Pubkey::find_program_address(
+        &[
+            b"hide_sis_session_v2",
+            player_key.as_ref(),
+            &SESSION_ID.to_le_bytes(),
+        ],
+        &ID,
+    );
+
+    context.set_account(
+        &player_key,
+        &Account::new(10 * ONE_SOL_LAMPORTS, 0, &system_program::id()).into(),
+    );
+    context.set_account(
+        &admin_config,
+        &program_account(
+            &AdminConfig {
+                authority: Pubkey::new_unique(),
+                oracle_pubkey: oracle.public.to_bytes(),
+                active_scoring_model_hash: model_hash,
+                bump: admin_bump,
+                zk_provider_allowlist: [u8::MAX; ZK_PROVIDER_ALLOWLIST_LEN],
+                cluster_id,
+                layout_version: account_layout::admin_config::VERSION,
+            },
+            account_layout::admin_config::LEN,
+        )
+        .into(),
+    );
+    context.set_account(
+        &session,
+        &program_account(
+            &HideSisSession {
+                player: player_key,
+                session_id: SESSION_ID,
+                user_mode: USER_MODE_VERIFIED,
+                dignity_score: 80,
+                relation_score: 0,
+                pollution_score: 0,
+                pollution_flag: false,
+                c2_n3_passed: true,
+                truth_unlocked: false,
+                current_node: 0,
+                chapter_budget_bps: [CHAPTER_BUDGET_BPS; CHAPTER_COUNT],
+                final_decision_code: DECISION_NONE,
+                ending_code: FRAMED_AND_JAILED,
+                humanity_score: 60,
+                finalized: true,
+                started_at: 0,
+                updated_at: 0,
+                bump: session_bump,
+            },
+            8 + HideSisSession::INIT_SPACE,
+        )
+        .into(),
+    );
+
+    let clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    let payload = ShieldPayloadV0 {
+        policy_version: POLICY_VERSION_V0,
+        user_pubkey: player_key.to_bytes(),
+        initial_price: 0,
+        sales_velocity_bps: 0,
+        time_elapsed: 0,
+        dignity_score: 80,
+        adapter_mask: 0,
+        user_mode: USER_MODE_VERIFIED,
+        zk_provider: 1,
+        zk_proof_hash: [5u8; 32],
+        scoring_model_hash: model_hash,
+        attestation_expiry: clock.unix_timestamp + 300,
+        nonce: 1,
+    };
+    let message = serialize_shield_message_v0(
+        &payload,
+        &SigningDomain {
+            program_id: ID,
+            cluster_id,
+            purpose: SIGNING_PURPOSE_ENDING_BADGE,
+            ticket_class: TICKET_CLASS_NONE,
+        },
+    );
+    let oracle_signature = oracle.sign(&message).to_bytes();
+
+    let ending = [FRAMED_AND_JAILED];
+    let (badge, _) =
+        Pubkey::find_program_address(&[b"ending_badge_v2", player_key.as_ref(), &ending], &ID);
+    let (badge_mint, _) = Pubkey::find_program_address(
+        &[b"ending_badge_mint_v2", player_key.as_ref(), &ending],
+        &ID,
+    );
+    let (badge_token, _) =
+        Pubkey::find_program_address(&[b"ending_badge_token_v2", badge_mint.as_ref()], &ID);
+    let (ticket_mint_authority, _) =
+        Pubkey::find_program_address(&[b"ticket_mint_authority_v2"], &ID);
+
+    let claim_ix = Instruction {
+        program_id: ID,
+        accounts: unforgiven_v2::accounts::ClaimEndingBadge {
+            player: player_key,
+            admin_config,
+            session,
+            badge,
+            badge_mint,
+            badge_token,
+            ticket_mint_authority,
+            token_program: spl_token::ID,
+            system_program: system_program::id(),
+            rent: sysvar::rent::ID,
+            instructions: sysvar::instructions::ID,
+        }
+        .to_account_metas(None),
+        data: unforgiven_v2::instruction::ClaimEndingBadge {
+            payload,
+            oracle_signature,
+            sig_instruction_index: 0,
+        }
+        .data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[new_ed25519_instruction(&oracle, &message), claim_ix],
+        Some(&player_key),
+        &[&player],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    let mint_account = context
+        .banks_client
+        .get_account(badge_mint)
+        .await
+        .unwrap()
+        .unwrap();
+    let mint = SplMint::unpack(&mint_account.data).unwrap();
+    assert_eq!(mint.supply, 1);
+    assert_eq!(mint.mint_authority, COption::None);
+    assert_eq!(mint.freeze_authority, COption::Some(ticket_mint_authority));
+
+    let token_account = context
+        .banks_client
+        .get_account(badge_token)
+        .await
+        .unwrap()
+        .unwrap();
+    let token = SplTokenAccount::unpack(&token_account.data).unwrap();
+    assert_eq!(token.owner, player_key);
+    assert_eq!(token.amount, 1);
+    assert_eq!(token.state, AccountState::Frozen);
+
+    // The player still owns the token account, but the frozen state stops every move.
+    let recipient = Pubkey::new_unique();
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let burn_tx = Transaction::new_signed_with_payer(
+        &[spl_token::instruction::burn(
+            &spl_token::ID,
+            &badge_token,
+            &badge_mint,
+            &player_key,
+            &[],
+            1,
+        )
+        .unwrap()],
+        Some(&player_key),
+        &[&player],
+        blockhash,
+    );
+    assert!(context
+        .banks_client
+        .process_transaction(burn_tx)
+        .await
+        .is_err());
+    let transfer_tx = Transaction::new_signed_with_payer(
+        &[spl_token::instruction::transfer(
+            &spl_token::ID,
+            &badge_token,
+            &recipient,
+            &player_key,
+            &[],
+            1,
+        )
+        .unwrap()],
+        Some(&player_key),
+        &[&player],
+        blockhash,
+    );
+    assert!(context
+        .banks_client
+        .process_transaction(transfer_tx)
+        .await
+        .is_err());
+}
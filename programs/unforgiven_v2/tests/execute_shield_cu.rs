@@ -0,0 +1,416 @@
+pub mod common;
+
+use anchor_lang::{
+    prelude::Pubkey, solana_program::sysvar, AnchorSerialize, Discriminator, InstructionData,
+    Space, ToAccountMetas,
+};
+use common::{program_account, ONE_SOL_LAMPORTS};
+use ed25519_dalek::{Keypair as DalekKeypair, PublicKey as DalekPublicKey, SecretKey, Signer as _};
+use solana_program_test::{ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    clock::Clock,
+    compute_budget::ComputeBudgetInstruction,
+    ed25519_instruction::new_ed25519_instruction,
+    instruction::Instruction,
+    signature::{Keypair, Signer},
+    system_program,
+    transaction::Transaction,
+};
+use unforgiven_v2::{
+    account_layout, require_pda_address, serialize_shield_message_v0, AdminConfig, GlobalConfigV2,
+    ProofQuota, Reputation, SaleConfig, ShieldAccountBumps, ShieldPayloadV0, SigningDomain,
+    UserModePolicy, ALLOCATION_MODE_OPEN, ID, POLICY_VERSION_V0, PRICING_STRATEGY_VRGDA,
+    SHIELD_DOMAIN_TICKET_CLASS_OFFSET, SIGNING_PURPOSE_EXECUTE, TICKET_CLASS_NONE, USER_MODE_COUNT,
+    USER_MODE_VERIFIED, ZK_PROVIDER_ALLOWLIST_LEN,
+};
+
+// Default per-instruction limit: execute_shield must fit without a compute budget request.
+const EXECUTE_SHIELD_CU_BUDGET: u64 = 200_000;
+// The program as it was before caller-supplied bumps, built from the parent of
+// "Trim execute_shield compute with caller-supplied bumps and fewer CPIs".
+const BASELINE_PROGRAM: &str = "unforgiven_v2_baseline";
+const ZK_PROOF_HASH: [u8; 32] = [5u8; 32];
+const MEASURE_CU_LIMIT: u32 = 1_400_000;
+
+fn test_oracle_keypair() -> DalekKeypair {
+    let secret = SecretKey::from_bytes(&[7u8; 32]).unwrap();
+    let public = DalekPublicKey::from(&secret);
+    DalekKeypair { secret, public }
+}
+
+struct Fixture {
+    context: ProgramTestContext,
+    user: Keypair,
+    oracle: DalekKeypair,
+    treasury: Pubkey,
+    global_config_v2: Pubkey,
+    admin_config: Pubkey,
+    sale_config: Pubkey,
+    cluster_id: [u8; 32],
+    model_hash: [u8; 32],
+}
+
+// The baseline binary reads the same accounts: every layout change since only appended fields.
+async fn setup(program_name: &str) -> Fixture {
+    let mut program_test = ProgramTest::new(program_name, ID, None);
+    program_test.prefer_bpf(true);
+    let mut context = program_test.start_with_context().await;
+
+    let user = Keypair::new();
+    let oracle = test_oracle_keypair();
+    let treasury = Pubkey::new_unique();
+    let model_hash = [11u8; 32];
+    let cluster_id = context.genesis_config().hash().to_bytes();
+    let (global_config_v2, global_bump) = Pubkey::find_program_address(&[b"global_v2"], &ID);
+    let (admin_config, admin_bump) = Pubkey::find_program_address(&[b"admin_config_v2"], &ID);
+    let (sale_config, sale_bump) =
+        Pubkey::find_program_address(&[b"sale_config_v2", global_config_v2.as_ref()], &ID);
+
+    context.set_account(
+        &treasury,
+        &Account::new(ONE_SOL_LAMPORTS, 0, &system_program::id()).into(),
+    );
+    context.set_account(
+        &global_config_v2,
+        &program_account(
+            &GlobalConfigV2 {
+                authority: treasury,
+                bump: global_bump,
+                layout_version: account_layout::global_config_v2::VERSION,
+            },
+            account_layout::global_config_v2::LEN,
+        )
+        .into(),
+    );
+    context.set_account(
+        &admin_config,
+        &program_account(
+            &AdminConfig {
+                authority: Pubkey::new_unique(),
+                oracle_pubkey: oracle.public.to_bytes(),
+                active_scoring_model_hash: model_hash,
+                bump: admin_bump,
+                zk_provider_allowlist: [u8::MAX; ZK_PROVIDER_ALLOWLIST_LEN],
+                cluster_id,
+                layout_version: account_layout::admin_config::VERSION,
+            },
+            account_layout::admin_config::LEN,
+        )
+        .into(),
+    );
+    let sale = SaleConfig {
+        event_key: global_config_v2,
+        max_uses_per_proof: 1,
+        mode_policies: [UserModePolicy::PERMISSIVE; USER_MODE_COUNT],
+        required_adapter_mask: 0,
+        forbidden_adapter_mask: 0,
+        bump: sale_bump,
+        allocation_mode: ALLOCATION_MODE_OPEN,
+        commit_deposit_lamports: 0,
+        commit_min_delay_slots: 0,
+        commit_reveal_window_slots: 0,
+        commit_forfeit_bps: 0,
+        pricing_strategy: PRICING_STRATEGY_VRGDA,
+        dutch_floor_price: 0,
+        dutch_step_amount: 0,
+        dutch_step_interval_secs: 0,
+        max_transfers_per_ticket: 0,
+        transfer_requires_shield: false,
+        min_membership_tier: 0,
+        dutch_start_price: 0,
+        ticket_class_count: 0,
+        scanner: Pubkey::new_unique(),
+    };
+    context.set_account(
+        &sale_config,
+        &program_account(&sale, 8 + SaleConfig::INIT_SPACE).into(),
+    );
+
+    // Purchases require the proof's quota and the buyer's reputation to exist already.
+    let user_key = user.pubkey();
+    let (proof_quota, proof_quota_bump) = Pubkey::find_program_address(
+        &[b"proof_quota_v2", global_config_v2.as_ref(), &ZK_PROOF_HASH],
+        &ID,
+    );
+    let (reputation, reputation_bump) =
+        Pubkey::find_program_address(&[b"reputation_v2", user_key.as_ref()], &ID);
+    context.set_account(
+        &user_key,
+        &Account::new(10 * ONE_SOL_LAMPORTS, 0, &system_program::id()).into(),
+    );
+    context.set_account(
+        &proof_quota,
+        &program_account(
+            &ProofQuota {
+                event_key: global_config_v2,
+                zk_proof_hash: ZK_PROOF_HASH,
+                uses: 0,
+                bump: proof_quota_bump,
+            },
+            8 + ProofQuota::INIT_SPACE,
+        )
+        .into(),
+    );
+    context.set_account(
+        &reputation,
+        &program_account(
+            &Reputation {
+                wallet: user_key,
+                fair_purchases: 0,
+                redeemed_tickets: 0,
+                flips: 0,
+                blocked_attempts: 0,
+                updated_at: 0,
+                bump: reputation_bump,
+            },
+            8 + Reputation::INIT_SPACE,
+        )
+        .into(),
+    );
+
+    Fixture {
+        context,
+        user,
+        oracle,
+        treasury,
+        global_config_v2,
+        admin_config,
+        sale_config,
+        cluster_id,
+        model_hash,
+    }
+}
+
+// The baseline signed a domain without the ticket class byte and took neither `bumps`
+// nor the accounts after `proof_quota`.
+fn execute_shield_transaction(fixture: &Fixture, now: i64, baseline: bool) -> Transaction {
+    let user = &fixture.user;
+    let payload = ShieldPayloadV0 {
+        policy_version: POLICY_VERSION_V0,
+        user_pubkey: user.pubkey().to_bytes(),
+        initial_price: ONE_SOL_LAMPORTS / 10,
+        sales_velocity_bps: 2_500,
+        time_elapsed: 12,
+        dignity_score: 92,
+        adapter_mask: 0b111,
+        user_mode: USER_MODE_VERIFIED,
+        zk_provider: 1,
+        zk_proof_hash: ZK_PROOF_HASH,
+        scoring_model_hash: fixture.model_hash,
+        attestation_expiry: now + 300,
+        nonce: 42,
+    };
+    let mut message = serialize_shield_message_v0(
+        &payload,
+        &SigningDomain {
+            program_id: ID,
+            cluster_id: fixture.cluster_id,
+            purpose: SIGNING_PURPOSE_EXECUTE,
+            ticket_class: TICKET_CLASS_NONE,
+        },
+    )
+    .to_vec();
+    if baseline {
+        message.remove(SHIELD_DOMAIN_TICKET_CLASS_OFFSET);
+    }
+    let oracle_signature = fixture.oracle.sign(&message).to_bytes();
+
+    let nonce = payload.nonce.to_le_bytes();
+    let user_key = user.pubkey();
+    let (proof_use, proof_use_bump) = Pubkey::find_program_address(
+        &[
+            b"proof_use",
+            user_key.as_ref(),
+            &payload.zk_proof_hash,
+            &nonce,
+        ],
+        &ID,
+    );
+    let (proof_quota, proof_quota_bump) = Pubkey::find_program_address(
+        &[
+            b"proof_quota_v2",
+            fixture.global_config_v2.as_ref(),
+            &payload.zk_proof_hash,
+        ],
+        &ID,
+    );
+    let (ticket_mint, ticket_mint_bump) = Pubkey::find_program_address(
+        &[
+            b"ticket_mint_v2",
+            user_key.as_ref(),
+            &payload.zk_proof_hash,
+            &nonce,
+        ],
+        &ID,
+    );
+    let (user_ticket_token, user_ticket_token_bump) = Pubkey::find_program_address(
+        &[b"ticket_token_v2", ticket_mint.as_ref(), user_key.as_ref()],
+        &ID,
+    );
+    let (ticket_receipt, ticket_receipt_bump) =
+        Pubkey::find_program_address(&[b"ticket_receipt_v2", ticket_mint.as_ref()], &ID);
+    let (ticket_mint_authority, ticket_mint_authority_bump) =
+        Pubkey::find_program_address(&[b"ticket_mint_authority_v2"], &ID);
+    let (reputation, reputation_bump) =
+        Pubkey::find_program_address(&[b"reputation_v2", user_key.as_ref()], &ID);
+
+    let mut execute_ix = Instruction {
+        program_id: ID,
+        accounts: unforgiven_v2::accounts::ExecuteShield {
+            user: user_key,
+            global_config_v2: fixture.global_config_v2,
+            treasury: fixture.treasury,
+            admin_config: fixture.admin_config,
+            instructions: sysvar::instructions::ID,
+            proof_use,
+            ticket_mint,
+            ticket_mint_authority,
+            user_ticket_token,
+            ticket_receipt,
+            token_program: anchor_spl::token::ID,
+            system_program: system_program::id(),
+            rent: sysvar::rent::ID,
+            sale_config: fixture.sale_config,
+            proof_quota,
+            ticket_class: None,
+            membership: None,
+            reputation,
+        }
+        .to_account_metas(None),
+        data: unforgiven_v2::instruction::ExecuteShield {
+            seed_payload: payload.clone(),
+            oracle_signature,
+            sig_instruction_index: 1,
+            bumps: ShieldAccountBumps {
+                proof_use: proof_use_bump,
+                ticket_mint: ticket_mint_bump,
+                ticket_mint_authority: ticket_mint_authority_bump,
+                user_ticket_token: user_ticket_token_bump,
+                ticket_receipt: ticket_receipt_bump,
+                proof_quota: proof_quota_bump,
+                reputation: reputation_bump,
+            },
+        }
+        .data(),
+    };
+    if baseline {
+        let proof_quota_index = execute_ix
+            .accounts
+            .iter()
+            .position(|meta| meta.pubkey == proof_quota)
+            .unwrap();
+        execute_ix.accounts.truncate(proof_quota_index + 1);
+        execute_ix.data = unforgiven_v2::instruction::ExecuteShield::DISCRIMINATOR.to_vec();
+        payload.serialize(&mut execute_ix.data).unwrap();
+        oracle_signature.serialize(&mut execute_ix.data).unwrap();
+        1u16.serialize(&mut execute_ix.data).unwrap();
+    }
+
+    Transaction::new_signed_with_payer(
+        &[
+            ComputeBudgetInstruction::set_compute_unit_limit(MEASURE_CU_LIMIT),
+            new_ed25519_instruction(&fixture.oracle, &message),
+            execute_ix,
+        ],
+        Some(&user_key),
+        &[user],
+        fixture.context.last_blockhash,
+    )
+}
+
+#[test]
+fn caller_supplied_bumps_must_be_canonical() {
+    let mint = Pubkey::new_unique();
+    // Find a seed set that also has a viable bump below the canonical one.
+    let (user, receipt, canonical, lower) = (0..)
+        .find_map(|_| {
+            let user = Pubkey::new_unique();
+            let seeds: [&[u8]; 3] = [b"ticket_token_v2", mint.as_ref(), user.as_ref()];
+            let (receipt, canonical) = Pubkey::find_program_address(&seeds, &ID);
+            (0..canonical).rev().find_map(|bump| {
+                Pubkey::create_program_address(&[seeds[0], seeds[1], seeds[2], &[bump]], &ID)
+                    .ok()
+                    .map(|lower| (user, receipt, canonical, (bump, lower)))
+            })
+        })
+        .unwrap();
+
+    require_pda_address(
+        &receipt,
+        &[
+            b"ticket_token_v2",
+            mint.as_ref(),
+            user.as_ref(),
+            &[canonical],
+        ],
+        &ID,
+    )
+    .unwrap();
+    assert!(require_pda_address(
+        &lower.1,
+        &[b"ticket_token_v2", mint.as_ref(), user.as_ref(), &[lower.0]],
+        &ID,
+    )
+    .is_err());
+    assert!(require_pda_address(
+        &Pubkey::new_unique(),
+        &[
+            b"ticket_token_v2",
+            mint.as_ref(),
+            user.as_ref(),
+            &[canonical]
+        ],
+        &ID,
+    )
+    .is_err());
+}
+
+async fn execute_shield_units(program_name: &str, baseline: bool) -> u64 {
+    let mut fixture = setup(program_name).await;
+    let clock: Clock = fixture.context.banks_client.get_sysvar().await.unwrap();
+
+    let tx = execute_shield_transaction(&fixture, clock.unix_timestamp, baseline);
+    let simulation = fixture
+        .context
+        .banks_client
+        .simulate_transaction(tx.clone())
+        .await
+        .unwrap();
+    let details = simulation.simulation_details.unwrap();
+    assert!(
+        simulation.result.as_ref().unwrap().is_ok(),
+        "{program_name} execute_shield failed: {:?}\n{}",
+        simulation.result,
+        details.logs.join("\n")
+    );
+
+    fixture
+        .context
+        .banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap();
+    // The compute budget and ed25519 precompile instructions meter nothing, so this is
+    // execute_shield alone.
+    details.units_consumed
+}
+
+// Needs both SBF builds in `BPF_OUT_DIR`: `unforgiven_v2.so` from this tree and
+// `unforgiven_v2_baseline.so` from the baseline commit. Compute units are only metered
+// for on-chain binaries. The `program-cu` job in `.github/workflows/ci-gate.yml` builds
+// both and runs this test.
+#[tokio::test]
+#[ignore = "requires the unforgiven_v2 and unforgiven_v2_baseline SBF builds"]
+async fn execute_shield_uses_less_compute_than_baseline() {
+    let baseline = execute_shield_units(BASELINE_PROGRAM, true).await;
+    let consumed = execute_shield_units("unforgiven_v2", false).await;
+    assert!(
+        consumed < baseline,
+        "execute_shield consumed {consumed} CU, baseline {baseline}"
+    );
+    assert!(
+        consumed <= EXECUTE_SHIELD_CU_BUDGET,
+        "execute_shield consumed {consumed} CU, budget {EXECUTE_SHIELD_CU_BUDGET}"
+    );
+}
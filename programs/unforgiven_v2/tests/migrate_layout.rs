@@ -0,0 +1,189 @@
+pub mod common;
+
+use anchor_lang::{prelude::Pubkey, AccountDeserialize, InstructionData, Space, ToAccountMetas};
+use common::{baseline_account, process_instruction, ONE_SOL_LAMPORTS};
+use solana_program_test::{processor, ProgramTest};
+use solana_sdk::{
+    account::Account,
+    instruction::Instruction,
+    signature::{Keypair, Signer},
+    system_program,
+    transaction::Transaction,
+};
+use unforgiven_v2::{
+    account_layout, audit_log_entries, AdminConfig, AuditEntry, AuditLog, TicketReceipt,
+    AUDIT_ACTION_ADMIN_CONFIG_MIGRATED, AUDIT_LOG_CAPACITY, ID, TICKET_CLASS_NONE, USER_MODE_GUEST,
+    USER_MODE_VERIFIED, ZK_PROVIDER_ALLOWLIST_LEN,
+};
+
+const CLUSTER_ID: [u8; 32] = [42u8; 32];
+
+// Accounts created by the baseline program stop right before the first appended field.
+// Migrating them must leave the old fields alone and give the new ones their real defaults.
+#[tokio::test]
+async fn migrations_fill_defaults_for_baseline_accounts() {
+    let mut program_test = ProgramTest::new("unforgiven_v2", ID, processor!(process_instruction));
+    program_test.prefer_bpf(false);
+    let mut context = program_test.start_with_context().await;
+
+    let authority = Keypair::new();
+    let authority_key = authority.pubkey();
+    let (admin_config, admin_bump) = Pubkey::find_program_address(&[b"admin_config_v2"], &ID);
+    let (audit_log, audit_log_bump) = Pubkey::find_program_address(&[b"audit_log_v2"], &ID);
+    let ticket_receipt = Pubkey::new_unique();
+    let holder = Pubkey::new_unique();
+
+    context.set_account(
+        &authority_key,
+        &Account::new(10 * ONE_SOL_LAMPORTS, 0, &system_program::id()).into(),
+    );
+    context.set_account(
+        &admin_config,
+        &baseline_account(
+            &AdminConfig {
+                authority: authority_key,
+                oracle_pubkey: [1u8; 32],
+                active_scoring_model_hash: [2u8; 32],
+                bump: admin_bump,
+                zk_provider_allowlist: [0u8; ZK_PROVIDER_ALLOWLIST_LEN],
+                cluster_id: [0u8; 32],
+                layout_version: 0,
+            },
+            account_layout::admin_config::ZK_PROVIDER_ALLOWLIST,
+        )
+        .into(),
+    );
+    context.set_account(
+        &audit_log,
+        &baseline_account(
+            &AuditLog {
+                total_entries: 0,
+                next_index: 0,
+                entries: [AuditEntry::default(); AUDIT_LOG_CAPACITY],
+                bump: audit_log_bump,
+            },
+            8 + AuditLog::INIT_SPACE,
+        )
+        .into(),
+    );
+    context.set_account(
+        &ticket_receipt,
+        &baseline_account(
+            &TicketReceipt {
+                mint: Pubkey::new_unique(),
+                event_key: Pubkey::new_unique(),
+                original_buyer: holder,
+                current_holder: holder,
+                purchase_price: ONE_SOL_LAMPORTS,
+                last_sale_price: ONE_SOL_LAMPORTS,
+                issued_at: 1,
+                last_transfer_at: 1,
+                nonce: 9,
+                zk_proof_hash: [4u8; 32],
+                listed: false,
+                resale_count: 2,
+                bump: 254,
+                holder_user_mode: USER_MODE_VERIFIED,
+                class_id: 3,
+                transfer_count: 0,
+                entry_secret_commitment: [0u8; 32],
+                entry_secret_rotated_at: 0,
+                redeemed_at: 0,
+                layout_version: 0,
+            },
+            account_layout::ticket_receipt::HOLDER_USER_MODE,
+        )
+        .into(),
+    );
+
+    let migrate_admin_ix = Instruction {
+        program_id: ID,
+        accounts: unforgiven_v2::accounts::MigrateAdminConfig {
+            authority: authority_key,
+            admin_config,
+            system_program: system_program::id(),
+            audit_log,
+        }
+        .to_account_metas(None),
+        data: unforgiven_v2::instruction::MigrateAdminConfig {
+            cluster_id: CLUSTER_ID,
+        }
+        .data(),
+    };
+    let migrate_receipt_ix = Instruction {
+        program_id: ID,
+        accounts: unforgiven_v2::accounts::MigrateTicketReceipt {
+            payer: authority_key,
+            ticket_receipt,
+            system_program: system_program::id(),
+        }
+        .to_account_metas(None),
+        data: unforgiven_v2::instruction::MigrateTicketReceipt {}.data(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[migrate_admin_ix, migrate_receipt_ix],
+        Some(&authority_key),
+        &[&authority],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(tx).await.unwrap();
+
+    let admin_account = context
+        .banks_client
+        .get_account(admin_config)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(admin_account.data.len(), account_layout::admin_config::LEN);
+    let admin = AdminConfig::try_deserialize(&mut admin_account.data.as_slice()).unwrap();
+    assert_eq!(admin.authority, authority_key);
+    assert_eq!(admin.oracle_pubkey, [1u8; 32]);
+    assert_eq!(admin.active_scoring_model_hash, [2u8; 32]);
+    assert_eq!(admin.bump, admin_bump);
+    assert_eq!(
+        admin.zk_provider_allowlist,
+        [u8::MAX; ZK_PROVIDER_ALLOWLIST_LEN]
+    );
+    assert_eq!(admin.cluster_id, CLUSTER_ID);
+    assert_eq!(admin.layout_version, account_layout::admin_config::VERSION);
+
+    let log_account = context
+        .banks_client
+        .get_account(audit_log)
+        .await
+        .unwrap()
+        .unwrap();
+    let entries =
+        audit_log_entries(&AuditLog::try_deserialize(&mut log_account.data.as_slice()).unwrap());
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].action, AUDIT_ACTION_ADMIN_CONFIG_MIGRATED);
+    assert_eq!(entries[0].signer, authority_key);
+    assert_eq!(entries[0].old_value[0], 0);
+    assert_eq!(
+        entries[0].new_value[0],
+        account_layout::admin_config::VERSION
+    );
+
+    let receipt_account = context
+        .banks_client
+        .get_account(ticket_receipt)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        receipt_account.data.len(),
+        account_layout::ticket_receipt::LEN
+    );
+    let receipt = TicketReceipt::try_deserialize(&mut receipt_account.data.as_slice()).unwrap();
+    assert_eq!(receipt.current_holder, holder);
+    assert_eq!(receipt.resale_count, 2);
+    assert_eq!(receipt.bump, 254);
+    assert_eq!(receipt.holder_user_mode, USER_MODE_GUEST);
+    assert_eq!(receipt.class_id, TICKET_CLASS_NONE);
+    assert_eq!(receipt.transfer_count, 0);
+    assert_eq!(receipt.redeemed_at, 0);
+    assert_eq!(
+        receipt.layout_version,
+        account_layout::ticket_receipt::VERSION
+    );
+}
@@ -1,12 +1,16 @@
 use ed25519_dalek::{Keypair as DalekKeypair, PublicKey as DalekPublicKey, SecretKey, Signer};
 use unforgiven_v2::{
-    execution_event_from_payload, preview_event_from_payload, quote_from_payload,
-    serialize_shield_payload_v0, AdminConfig, ShieldPayloadV0, POLICY_VERSION_V0,
-    USER_MODE_BOT_SUSPECTED, USER_MODE_VERIFIED,
+    decode_quote_return_v0, encode_quote_return_v0, execution_event_from_payload,
+    preview_event_for_class, preview_event_from_payload, quote_from_payload,
+    serialize_shield_message_v0, AdminConfig, SaleConfig, ShieldPayloadV0, SigningDomain,
+    UserModePolicy, ALLOCATION_MODE_OPEN, POLICY_VERSION_V0, PRICING_STRATEGY_VRGDA,
+    QUOTE_RETURN_VERSION_V0, SIGNING_PURPOSE_EXECUTE, SIGNING_PURPOSE_PREVIEW, TICKET_CLASS_NONE,
+    USER_MODE_BOT_SUSPECTED, USER_MODE_COUNT, USER_MODE_VERIFIED, ZK_PROVIDER_ALLOWLIST_LEN,
 };
 
 const ONE_SOL_LAMPORTS: u64 = 1_000_000_000;
 const NOW: i64 = 1_700_000_000;
+const CLUSTER_ID: [u8; 32] = [42u8; 32];
 
 fn test_oracle_keypair() -> DalekKeypair {
     let secret = SecretKey::from_bytes(&[7u8; 32]).unwrap();
@@ -20,6 +24,35 @@ fn admin(oracle_pubkey: [u8; 32], model_hash: [u8; 32]) -> AdminConfig {
         oracle_pubkey,
         active_scoring_model_hash: model_hash,
         bump: 255,
+        zk_provider_allowlist: [u8::MAX; ZK_PROVIDER_ALLOWLIST_LEN],
+        cluster_id: CLUSTER_ID,
+        layout_version: 1,
+    }
+}
+
+fn sale() -> SaleConfig {
+    SaleConfig {
+        event_key: anchor_lang::prelude::Pubkey::new_unique(),
+        max_uses_per_proof: 1,
+        mode_policies: [UserModePolicy::PERMISSIVE; USER_MODE_COUNT],
+        required_adapter_mask: 0,
+        forbidden_adapter_mask: 0,
+        bump: 255,
+        allocation_mode: ALLOCATION_MODE_OPEN,
+        commit_deposit_lamports: 0,
+        commit_min_delay_slots: 0,
+        commit_reveal_window_slots: 0,
+        commit_forfeit_bps: 0,
+        pricing_strategy: PRICING_STRATEGY_VRGDA,
+        dutch_floor_price: 0,
+        dutch_step_amount: 0,
+        dutch_step_interval_secs: 0,
+        max_transfers_per_ticket: 0,
+        transfer_requires_shield: false,
+        min_membership_tier: 0,
+        dutch_start_price: 0,
+        ticket_class_count: 0,
+        scanner: anchor_lang::prelude::Pubkey::new_unique(),
     }
 }
 
@@ -48,8 +81,19 @@ fn payload(
     }
 }
 
+fn sign_for(kp: &DalekKeypair, payload: &ShieldPayloadV0, purpose: u8) -> [u8; 64] {
+    let domain = SigningDomain {
+        program_id: unforgiven_v2::id(),
+        cluster_id: CLUSTER_ID,
+        purpose,
+        ticket_class: TICKET_CLASS_NONE,
+    };
+    kp.sign(&serialize_shield_message_v0(payload, &domain))
+        .to_bytes()
+}
+
 fn sign_payload(kp: &DalekKeypair, payload: &ShieldPayloadV0) -> [u8; 64] {
-    kp.sign(&serialize_shield_payload_v0(payload)).to_bytes()
+    sign_for(kp, payload, SIGNING_PURPOSE_PREVIEW)
 }
 
 #[test]
@@ -58,6 +102,7 @@ fn same_market_heat_has_clear_price_split_by_dignity() {
     let model_hash = [11u8; 32];
     let user = anchor_lang::prelude::Pubkey::new_unique();
     let admin_cfg = admin(oracle.public.to_bytes(), model_hash);
+    let sale_cfg = sale();
 
     let bot_payload = payload(
         user.to_bytes(),
@@ -80,6 +125,7 @@ fn same_market_heat_has_clear_price_split_by_dignity() {
         &bot_payload,
         &sign_payload(&oracle, &bot_payload),
         &admin_cfg,
+        &sale_cfg,
         &user,
         NOW,
     )
@@ -88,6 +134,7 @@ fn same_market_heat_has_clear_price_split_by_dignity() {
         &user_payload,
         &sign_payload(&oracle, &user_payload),
         &admin_cfg,
+        &sale_cfg,
         &user,
         NOW,
     )
@@ -102,6 +149,7 @@ fn invalid_payload_values_are_rejected() {
     let model_hash = [11u8; 32];
     let user = anchor_lang::prelude::Pubkey::new_unique();
     let admin_cfg = admin(oracle.public.to_bytes(), model_hash);
+    let sale_cfg = sale();
 
     let bad_score = payload(
         user.to_bytes(),
@@ -133,13 +181,22 @@ fn invalid_payload_values_are_rejected() {
     );
     let mut bad_sig = sign_payload(&oracle, &bad_sig_payload);
     bad_sig[0] ^= 1;
-    assert!(
-        preview_event_from_payload(&bad_sig_payload, &bad_sig, &admin_cfg, &user, NOW).is_err()
-    );
+    assert!(preview_event_from_payload(
+        &bad_sig_payload,
+        &bad_sig,
+        &admin_cfg,
+        &sale_cfg,
+        &user,
+        NOW
+    )
+    .is_err());
 
     bad_sig_payload.attestation_expiry = NOW - 1;
     let sig = sign_payload(&oracle, &bad_sig_payload);
-    assert!(preview_event_from_payload(&bad_sig_payload, &sig, &admin_cfg, &user, NOW).is_err());
+    assert!(
+        preview_event_from_payload(&bad_sig_payload, &sig, &admin_cfg, &sale_cfg, &user, NOW)
+            .is_err()
+    );
 }
 
 #[test]
@@ -148,6 +205,7 @@ fn blocked_state_is_visible_on_infinity_path() {
     let model_hash = [11u8; 32];
     let user = anchor_lang::prelude::Pubkey::new_unique();
     let admin_cfg = admin(oracle.public.to_bytes(), model_hash);
+    let sale_cfg = sale();
 
     let mut p = payload(
         user.to_bytes(),
@@ -160,7 +218,7 @@ fn blocked_state_is_visible_on_infinity_path() {
     p.initial_price = u64::MAX;
 
     let sig = sign_payload(&oracle, &p);
-    let event = preview_event_from_payload(&p, &sig, &admin_cfg, &user, NOW).unwrap();
+    let event = preview_event_from_payload(&p, &sig, &admin_cfg, &sale_cfg, &user, NOW).unwrap();
     assert!(event.is_infinite);
     assert!(event.blocked);
 }
@@ -171,6 +229,7 @@ fn blocked_payload_cannot_enter_execution_path() {
     let model_hash = [11u8; 32];
     let user = anchor_lang::prelude::Pubkey::new_unique();
     let admin_cfg = admin(oracle.public.to_bytes(), model_hash);
+    let sale_cfg = sale();
 
     let mut p = payload(
         user.to_bytes(),
@@ -182,6 +241,53 @@ fn blocked_payload_cannot_enter_execution_path() {
     );
     p.initial_price = u64::MAX;
 
-    let sig = sign_payload(&oracle, &p);
-    assert!(execution_event_from_payload(&p, &sig, &admin_cfg, &user, NOW).is_err());
+    let sig = sign_for(&oracle, &p, SIGNING_PURPOSE_EXECUTE);
+    assert!(execution_event_from_payload(&p, &sig, &admin_cfg, &sale_cfg, &user, NOW).is_err());
+}
+
+#[test]
+fn preview_return_data_matches_preview_event() {
+    let oracle = test_oracle_keypair();
+    let model_hash = [11u8; 32];
+    let user = anchor_lang::prelude::Pubkey::new_unique();
+    let admin_cfg = admin(oracle.public.to_bytes(), model_hash);
+    // A mode multiplier, so a return value built from the raw quote would not match.
+    let mut sale_cfg = sale();
+    sale_cfg.mode_policies[usize::from(USER_MODE_VERIFIED)].price_multiplier_bps = 15_000;
+
+    let p = payload(
+        user.to_bytes(),
+        64,
+        5_000,
+        12,
+        USER_MODE_VERIFIED,
+        model_hash,
+    );
+    let (event, returned) = preview_event_for_class(
+        &p,
+        &sign_payload(&oracle, &p),
+        &admin_cfg,
+        &sale_cfg,
+        None,
+        &user,
+        NOW,
+    )
+    .unwrap();
+    assert_ne!(
+        returned.final_price,
+        quote_from_payload(&p).unwrap().final_price
+    );
+    let bytes = encode_quote_return_v0(&returned);
+    assert_eq!(bytes[0], QUOTE_RETURN_VERSION_V0);
+
+    let decoded = decode_quote_return_v0(&bytes).unwrap();
+    assert_eq!(decoded, returned);
+    assert_eq!(decoded.purpose, SIGNING_PURPOSE_PREVIEW);
+    assert_eq!(decoded.final_price, event.final_price);
+    assert_eq!(decoded.is_infinite, event.is_infinite);
+    assert_eq!(decoded.blocked, event.blocked);
+    assert_eq!(decoded.effective_velocity_bps, event.effective_velocity_bps);
+    assert_eq!(decoded.dignity_bucket, event.dignity_bucket);
+    assert_eq!(decoded.user_mode, event.user_mode);
+    assert_eq!(decoded.nonce, p.nonce);
 }
@@ -17,6 +17,8 @@ const {
 } = require('@solana/web3.js');
 const {
   findAdminConfigPda,
+  findAuditLogPda,
+  findSaleConfigPda,
   buildPreviewTxInstructions,
   discriminator,
 } = require('./tx_builder_v2');
@@ -124,10 +126,19 @@ function parseAdminConfigAccount(data) {
     oraclePubkey: Buffer.from(data.subarray(40, 72)),
     activeScoringModelHash: Buffer.from(data.subarray(72, 104)),
     bump: data[104],
+    clusterId: data.length >= 169 ? Buffer.from(data.subarray(137, 169)) : Buffer.alloc(32),
   };
 }
 
-async function ensureAdminConfig(connection, wallet, programId, adminConfigPda, oraclePubkeyBytes, modelHashBytes) {
+async function ensureAdminConfig(
+  connection,
+  wallet,
+  programId,
+  adminConfigPda,
+  oraclePubkeyBytes,
+  modelHashBytes,
+  clusterIdBytes,
+) {
   const adminInfo = await connection.getAccountInfo(adminConfigPda, 'confirmed');
   if (!adminInfo) {
     const programData = deriveProgramDataAddress(programId);
@@ -135,6 +146,7 @@ async function ensureAdminConfig(connection, wallet, programId, adminConfigPda,
       discriminator('global', 'initialize_admin_config'),
       oraclePubkeyBytes,
       modelHashBytes,
+      clusterIdBytes,
     ]);
 
     const initIx = buildIx(
@@ -145,6 +157,7 @@ async function ensureAdminConfig(connection, wallet, programId, adminConfigPda,
         { pubkey: programData, isSigner: false, isWritable: false },
         { pubkey: adminConfigPda, isSigner: false, isWritable: true },
         { pubkey: require('@solana/web3.js').SystemProgram.programId, isSigner: false, isWritable: false },
+        { pubkey: findAuditLogPda(programId), isSigner: false, isWritable: true },
       ],
       initData,
     );
@@ -169,6 +182,7 @@ async function ensureAdminConfig(connection, wallet, programId, adminConfigPda,
       [
         { pubkey: wallet.publicKey, isSigner: true, isWritable: false },
         { pubkey: adminConfigPda, isSigner: false, isWritable: true },
+        { pubkey: findAuditLogPda(programId), isSigner: false, isWritable: true },
       ],
       rotateData,
     );
@@ -189,6 +203,7 @@ async function ensureAdminConfig(connection, wallet, programId, adminConfigPda,
       [
         { pubkey: wallet.publicKey, isSigner: true, isWritable: false },
         { pubkey: adminConfigPda, isSigner: false, isWritable: true },
+        { pubkey: findAuditLogPda(programId), isSigner: false, isWritable: true },
       ],
       setData,
     );
@@ -198,6 +213,24 @@ async function ensureAdminConfig(connection, wallet, programId, adminConfigPda,
     });
     console.log(`setScoringModelHash tx: ${sig}`);
   }
+
+  if (!parsed.clusterId.equals(clusterIdBytes)) {
+    const setData = Buffer.concat([discriminator('global', 'set_cluster_id'), clusterIdBytes]);
+    const setIx = buildIx(
+      programId,
+      [
+        { pubkey: wallet.publicKey, isSigner: true, isWritable: false },
+        { pubkey: adminConfigPda, isSigner: false, isWritable: true },
+        { pubkey: findAuditLogPda(programId), isSigner: false, isWritable: true },
+      ],
+      setData,
+    );
+    const sig = await sendAndConfirmTransaction(connection, new Transaction().add(setIx), [wallet], {
+      commitment: 'confirmed',
+      preflightCommitment: 'confirmed',
+    });
+    console.log(`setClusterId tx: ${sig}`);
+  }
 }
 
 async function main() {
@@ -221,8 +254,19 @@ async function main() {
   const first = await fetchShieldPayload(wallet.publicKey.toBase58());
   const oraclePubkeyBytes = Buffer.from(new PublicKey(first.oracle_pubkey).toBytes());
   const modelHashBytes = Buffer.from(hexToBytes(first.scoring_model_hash_hex));
+  const clusterIdBytes = Buffer.from(new PublicKey(first.signing_domain.cluster_id).toBytes());
   const adminConfigPda = findAdminConfigPda(programId);
-  await ensureAdminConfig(connection, wallet, programId, adminConfigPda, oraclePubkeyBytes, modelHashBytes);
+  const globalConfigPda = PublicKey.findProgramAddressSync([Buffer.from('global_v2')], programId)[0];
+  const saleConfigPda = findSaleConfigPda(programId, globalConfigPda);
+  await ensureAdminConfig(
+    connection,
+    wallet,
+    programId,
+    adminConfigPda,
+    oraclePubkeyBytes,
+    modelHashBytes,
+    clusterIdBytes,
+  );
 
   let sent = 0;
   let ok = 0;
@@ -238,6 +282,10 @@ async function main() {
       programId,
       userPubkey: wallet.publicKey,
       adminConfigPda,
+      saleConfigPda,
+      clusterIdBytes,
+      // The compute budget instruction sits ahead of the ed25519 one.
+      sigInstructionIndex: 1,
       payloadBytes,
       oracleSignatureBytes: oracleSigBytes,
       oraclePubkeyBytes,
@@ -77,7 +77,7 @@ if [[ -z "${SHIELD_API_BASE:-}" && -z "${ORACLE_PUBLIC_KEY:-}" && -z "${ORACLE_K
   exit 1
 fi
 
-echo "[deploy_v2_devnet] initializing global/admin config"
+echo "[deploy_v2_devnet] initializing global/admin/sale config"
 RPC_URL="$RPC_URL" KEYPAIR_PATH="$KEYPAIR_PATH" node ./scripts/init_admin_v2.js
 
 echo
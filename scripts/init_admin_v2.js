@@ -17,7 +17,7 @@ const {
   sendAndConfirmTransaction,
 } = require('@solana/web3.js');
 const { deriveProgramDataAddress } = require('./solana_program_ids');
-const { discriminator, findAdminConfigPda } = require('./tx_builder_v2');
+const { discriminator, findAdminConfigPda, findAuditLogPda } = require('./tx_builder_v2');
 
 const SCORING_MODEL_V0 =
   'github>50:+40|spotify(hours>10):+30|twitter(age>365&&activity>=50):+20|guest=25|bot=0|cap=100|v0';
@@ -63,6 +63,8 @@ function parseAdminConfigAccount(data) {
     oraclePubkey: Buffer.from(data.subarray(40, 72)),
     activeScoringModelHash: Buffer.from(data.subarray(72, 104)),
     bump: data[104],
+    // Accounts that predate the cluster id read as unset until migrated.
+    clusterId: data.length >= 169 ? Buffer.from(data.subarray(137, 169)) : Buffer.alloc(32),
   };
 }
 
@@ -81,9 +83,21 @@ async function fetchRuntimeConfigFromApi(apiBase) {
   return {
     oraclePubkey: body.oraclePubkey,
     scoringModelHashHex: body.scoringModelHashHex,
+    clusterId: body.clusterId || null,
   };
 }
 
+// The cluster id is the genesis hash the oracle binds into every signature.
+async function resolveClusterIdBytes(connection, runtimeConfig) {
+  const clusterId =
+    process.env.SHIELD_CLUSTER_ID || runtimeConfig.clusterId || (await connection.getGenesisHash());
+  const bytes = Buffer.from(new PublicKey(clusterId).toBytes());
+  if (bytes.equals(Buffer.alloc(32))) {
+    throw new Error('cluster id must not be all zeroes');
+  }
+  return bytes;
+}
+
 async function resolveRuntimeConfig() {
   const apiBase = process.env.SHIELD_API_BASE;
   if (apiBase) {
@@ -106,6 +120,7 @@ async function resolveRuntimeConfig() {
 
   return {
     oraclePubkey,
+    clusterId: null,
     scoringModelHashHex:
       process.env.SCORING_MODEL_HASH_HEX ||
       process.env.BASELINE_SCORING_MODEL_HASH_HEX ||
@@ -136,22 +151,35 @@ async function main() {
     programId,
   )[0];
   const adminConfig = findAdminConfigPda(programId);
+  const saleConfig = PublicKey.findProgramAddressSync(
+    [Buffer.from('sale_config_v2'), globalConfigV2.toBuffer()],
+    programId,
+  )[0];
+  const maxUsesPerProof = Number(process.env.MAX_USES_PER_PROOF || '1');
+  if (!Number.isInteger(maxUsesPerProof) || maxUsesPerProof < 1 || maxUsesPerProof > 0xffff) {
+    throw new Error('MAX_USES_PER_PROOF must be an integer in [1, 65535]');
+  }
 
   const runtimeConfig = await resolveRuntimeConfig();
   const oraclePubkeyBytes = normalizeOracleBytes(runtimeConfig.oraclePubkey);
   const scoringModelHashBytes = hexToBytes(runtimeConfig.scoringModelHashHex);
+  const clusterIdBytes = await resolveClusterIdBytes(connection, runtimeConfig);
+  const auditLog = findAuditLogPda(programId);
 
   console.log(`wallet: ${wallet.publicKey.toBase58()}`);
   console.log(`program: ${programId.toBase58()}`);
   console.log(`rpc: ${rpcUrl}`);
   console.log(`global_config_v2: ${globalConfigV2.toBase58()}`);
   console.log(`admin_config: ${adminConfig.toBase58()}`);
+  console.log(`sale_config: ${saleConfig.toBase58()}`);
   console.log(`oracle_pubkey: ${runtimeConfig.oraclePubkey}`);
   console.log(`scoring_model_hash_hex: ${runtimeConfig.scoringModelHashHex}`);
+  console.log(`cluster_id: ${new PublicKey(clusterIdBytes).toBase58()}`);
 
-  const [globalInfo, adminInfo] = await Promise.all([
+  const [globalInfo, adminInfo, saleInfo] = await Promise.all([
     connection.getAccountInfo(globalConfigV2, 'confirmed'),
     connection.getAccountInfo(adminConfig, 'confirmed'),
+    connection.getAccountInfo(saleConfig, 'confirmed'),
   ]);
 
   if (!globalInfo) {
@@ -163,6 +191,7 @@ async function main() {
         { pubkey: programData, isSigner: false, isWritable: false },
         { pubkey: globalConfigV2, isSigner: false, isWritable: true },
         { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        { pubkey: auditLog, isSigner: false, isWritable: true },
       ],
       Buffer.from(discriminator('global', 'initialize_v2')),
     );
@@ -179,11 +208,13 @@ async function main() {
         { pubkey: programData, isSigner: false, isWritable: false },
         { pubkey: adminConfig, isSigner: false, isWritable: true },
         { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        { pubkey: auditLog, isSigner: false, isWritable: true },
       ],
       Buffer.concat([
         Buffer.from(discriminator('global', 'initialize_admin_config')),
         oraclePubkeyBytes,
         scoringModelHashBytes,
+        clusterIdBytes,
       ]),
     );
     const sig = await sendInstructions(connection, wallet, [initAdminIx]);
@@ -206,6 +237,7 @@ async function main() {
         [
           { pubkey: wallet.publicKey, isSigner: true, isWritable: false },
           { pubkey: adminConfig, isSigner: false, isWritable: true },
+          { pubkey: auditLog, isSigner: false, isWritable: true },
         ],
         Buffer.concat([
           Buffer.from(discriminator('global', 'rotate_oracle')),
@@ -222,6 +254,7 @@ async function main() {
         [
           { pubkey: wallet.publicKey, isSigner: true, isWritable: false },
           { pubkey: adminConfig, isSigner: false, isWritable: true },
+          { pubkey: auditLog, isSigner: false, isWritable: true },
         ],
         Buffer.concat([
           Buffer.from(discriminator('global', 'set_scoring_model_hash')),
@@ -231,6 +264,46 @@ async function main() {
       const sig = await sendInstructions(connection, wallet, [setHashIx]);
       console.log(`setScoringModelHash tx: ${sig}`);
     }
+
+    if (!parsed.clusterId.equals(clusterIdBytes)) {
+      const setClusterIx = buildIx(
+        programId,
+        [
+          { pubkey: wallet.publicKey, isSigner: true, isWritable: false },
+          { pubkey: adminConfig, isSigner: false, isWritable: true },
+          { pubkey: auditLog, isSigner: false, isWritable: true },
+        ],
+        Buffer.concat([
+          Buffer.from(discriminator('global', 'set_cluster_id')),
+          clusterIdBytes,
+        ]),
+      );
+      const sig = await sendInstructions(connection, wallet, [setClusterIx]);
+      console.log(`setClusterId tx: ${sig}`);
+    }
+  }
+
+  // execute_shield requires the sale config, so it must exist before the sale opens.
+  if (!saleInfo) {
+    const maxUsesBytes = Buffer.alloc(2);
+    maxUsesBytes.writeUInt16LE(maxUsesPerProof, 0);
+    const initSaleIx = buildIx(
+      programId,
+      [
+        { pubkey: wallet.publicKey, isSigner: true, isWritable: true },
+        { pubkey: adminConfig, isSigner: false, isWritable: false },
+        { pubkey: globalConfigV2, isSigner: false, isWritable: false },
+        { pubkey: saleConfig, isSigner: false, isWritable: true },
+        { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        { pubkey: auditLog, isSigner: false, isWritable: true },
+      ],
+      Buffer.concat([
+        Buffer.from(discriminator('global', 'initialize_sale_config')),
+        maxUsesBytes,
+      ]),
+    );
+    const sig = await sendInstructions(connection, wallet, [initSaleIx]);
+    console.log(`initializeSaleConfig tx: ${sig}`);
   }
 }
 
@@ -14,7 +14,7 @@ const {
   TransactionInstruction,
   sendAndConfirmTransaction,
 } = require('@solana/web3.js');
-const { discriminator, findAdminConfigPda } = require('./tx_builder_v2');
+const { discriminator, findAdminConfigPda, findAuditLogPda } = require('./tx_builder_v2');
 
 function loadKeypair(filePath) {
   const raw = JSON.parse(fs.readFileSync(filePath, 'utf8'));
@@ -76,6 +76,7 @@ async function main() {
     keys: [
       { pubkey: wallet.publicKey, isSigner: true, isWritable: false },
       { pubkey: adminConfigPda, isSigner: false, isWritable: true },
+      { pubkey: findAuditLogPda(programId), isSigner: false, isWritable: true },
     ],
     data,
   });
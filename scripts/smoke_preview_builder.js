@@ -5,19 +5,26 @@ const {
   PAYLOAD_LEN,
   ORACLE_SIGNATURE_LEN,
   buildPreviewInstructionData,
+  SHIELD_DOMAIN_LEN,
+  SIGNING_PURPOSE_PREVIEW,
   buildPreviewTxInstructions,
   findAdminConfigPda,
+  findSaleConfigPda,
+  serializeShieldMessageV0,
 } = require('./tx_builder_v2');
 
 const programId = new PublicKey('5VqDVHqeCJW1cWZgydjJLG68ShDGVZ45k6cE7hUY9uMW');
 const user = new PublicKey('92MXryYumqfooXdYAApXYBojQ9kqd5cX7kZJ1X3RgcVB');
 const admin = findAdminConfigPda(programId);
+const globalConfig = PublicKey.findProgramAddressSync([Buffer.from('global_v2')], programId)[0];
+const sale = findSaleConfigPda(programId, globalConfig);
+const clusterId = Buffer.alloc(32, 5);
 const payload = Buffer.alloc(PAYLOAD_LEN, 7);
 const sig = Buffer.alloc(ORACLE_SIGNATURE_LEN, 9);
 const oraclePk = Buffer.alloc(32, 3);
 
-const data = buildPreviewInstructionData(payload, sig);
-if (data.length !== 8 + PAYLOAD_LEN + ORACLE_SIGNATURE_LEN) {
+const data = buildPreviewInstructionData(payload, sig, 1);
+if (data.length !== 8 + PAYLOAD_LEN + ORACLE_SIGNATURE_LEN + 2) {
   throw new Error(`unexpected preview instruction data length: ${data.length}`);
 }
 
@@ -25,6 +32,9 @@ const { ed25519Ix, previewIx } = buildPreviewTxInstructions({
   programId,
   userPubkey: user,
   adminConfigPda: admin,
+  saleConfigPda: sale,
+  clusterIdBytes: clusterId,
+  sigInstructionIndex: 1,
   payloadBytes: payload,
   oracleSignatureBytes: sig,
   oraclePubkeyBytes: oraclePk,
@@ -33,11 +43,34 @@ const { ed25519Ix, previewIx } = buildPreviewTxInstructions({
 if (!ed25519Ix.programId.equals(new PublicKey('Ed25519SigVerify111111111111111111111111111'))) {
   throw new Error('ed25519 ix program id mismatch');
 }
-if (previewIx.keys.length !== 3) {
+if (previewIx.keys.length !== 5) {
   throw new Error('preview ix key count mismatch');
 }
 if (!previewIx.keys[2].pubkey.equals(SYSVAR_INSTRUCTIONS_PUBKEY)) {
   throw new Error('preview ix missing instructions sysvar key');
 }
+if (!previewIx.keys[3].pubkey.equals(sale)) {
+  throw new Error('preview ix missing sale config key');
+}
+
+const message = serializeShieldMessageV0(payload, {
+  programId,
+  clusterIdBytes: clusterId,
+  purpose: SIGNING_PURPOSE_PREVIEW,
+  ticketClass: 0,
+});
+if (message.length !== SHIELD_DOMAIN_LEN + PAYLOAD_LEN || SHIELD_DOMAIN_LEN !== 86) {
+  throw new Error(`unexpected signed message length: ${message.length}`);
+}
+if (message.toString('ascii', 0, 20) !== 'UNFORGIVEN_SHIELD_V0' || message[84] !== 1) {
+  throw new Error('signing domain header mismatch');
+}
+if (!message.subarray(52, 84).equals(clusterId) || !message.subarray(86).equals(payload)) {
+  throw new Error('signing domain body mismatch');
+}
+// The ed25519 instruction data ends with the signed message.
+if (!Buffer.from(ed25519Ix.data).subarray(-message.length).equals(message)) {
+  throw new Error('ed25519 ix does not sign the domain-prefixed message');
+}
 
 console.log('OK: tx_builder_v2 preview instruction smoke test passed');
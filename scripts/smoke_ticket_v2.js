@@ -17,7 +17,10 @@ const {
   Transaction,
   TransactionInstruction,
 } = require('@solana/web3.js');
+const { TICKET_CLASS_NONE, serializeShieldMessageV0 } = require('./tx_builder_v2');
 
+const SIGNING_PURPOSE_EXECUTE = 2;
+const SIGNING_PURPOSE_BUY_RESALE = 3;
 const TOKEN_PROGRAM_ID = new PublicKey('TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA');
 const PROGRAM_KEYPAIR_PATH =
   process.env.PROGRAM_KEYPAIR_PATH ||
@@ -49,6 +52,9 @@ const TICKET_TOKEN_SEED = Buffer.from('ticket_token_v2');
 const TICKET_RECEIPT_SEED = Buffer.from('ticket_receipt_v2');
 const TICKET_LISTING_SEED = Buffer.from('ticket_listing_v2');
 const TICKET_ESCROW_SEED = Buffer.from('ticket_escrow_v2');
+const SALE_CONFIG_SEED = Buffer.from('sale_config_v2');
+const PROOF_QUOTA_SEED = Buffer.from('proof_quota_v2');
+const REPUTATION_SEED = Buffer.from('reputation_v2');
 
 function loadKeypair(filePath) {
   const raw = JSON.parse(fs.readFileSync(filePath, 'utf8'));
@@ -73,6 +79,12 @@ function encodeU64LE(value) {
   return out;
 }
 
+function encodeU16LE(value) {
+  const out = Buffer.alloc(2);
+  out.writeUInt16LE(value);
+  return out;
+}
+
 function encodeI64LE(value) {
   const normalized = BigInt.asUintN(64, BigInt(value));
   return encodeU64LE(normalized);
@@ -96,12 +108,16 @@ function serializePayload(payload) {
   ]);
 }
 
-function signPayload(payloadBytes, oracle) {
-  return Buffer.from(nacl.sign.detached(payloadBytes, oracle.secretKey));
+function signPayload(message, oracle) {
+  return Buffer.from(nacl.sign.detached(message, oracle.secretKey));
+}
+
+function findPdaWithBump(seeds, programId) {
+  return PublicKey.findProgramAddressSync(seeds, programId);
 }
 
 function findPda(seeds, programId) {
-  return PublicKey.findProgramAddressSync(seeds, programId)[0];
+  return findPdaWithBump(seeds, programId)[0];
 }
 
 function findGlobalConfig(programId) {
@@ -112,40 +128,48 @@ function findAdminConfig(programId) {
   return findPda([Buffer.from('admin_config_v2')], programId);
 }
 
+// ProofUse and ticket mint seeds: user_pubkey, zk_proof_hash and nonce from the payload.
+function payloadSeeds(payloadBytes) {
+  return [
+    payloadBytes.subarray(1, 33),
+    payloadBytes.subarray(61, 93),
+    payloadBytes.subarray(133, 141),
+  ];
+}
+
 function findProofUse(programId, payloadBytes) {
-  return findPda(
-    [
-      Buffer.from('proof_use'),
-      payloadBytes.subarray(1, 33),
-      payloadBytes.subarray(61, 93),
-      payloadBytes.subarray(133, 141),
-    ],
-    programId,
-  );
+  return findPdaWithBump([Buffer.from('proof_use'), ...payloadSeeds(payloadBytes)], programId);
 }
 
 function findTicketMint(programId, payloadBytes) {
-  return findPda(
-    [
-      TICKET_MINT_SEED,
-      payloadBytes.subarray(1, 33),
-      payloadBytes.subarray(61, 93),
-      payloadBytes.subarray(133, 141),
-    ],
-    programId,
-  );
+  return findPdaWithBump([TICKET_MINT_SEED, ...payloadSeeds(payloadBytes)], programId);
 }
 
 function findTicketMintAuthority(programId) {
-  return findPda([TICKET_MINT_AUTHORITY_SEED], programId);
+  return findPdaWithBump([TICKET_MINT_AUTHORITY_SEED], programId);
 }
 
 function findTicketToken(programId, ticketMint, owner) {
-  return findPda([TICKET_TOKEN_SEED, ticketMint.toBuffer(), owner.toBuffer()], programId);
+  return findPdaWithBump([TICKET_TOKEN_SEED, ticketMint.toBuffer(), owner.toBuffer()], programId);
 }
 
 function findTicketReceipt(programId, ticketMint) {
-  return findPda([TICKET_RECEIPT_SEED, ticketMint.toBuffer()], programId);
+  return findPdaWithBump([TICKET_RECEIPT_SEED, ticketMint.toBuffer()], programId);
+}
+
+function findSaleConfig(programId) {
+  return findPda([SALE_CONFIG_SEED, findGlobalConfig(programId).toBuffer()], programId);
+}
+
+function findProofQuota(programId, zkProofHash) {
+  return findPdaWithBump(
+    [PROOF_QUOTA_SEED, findGlobalConfig(programId).toBuffer(), zkProofHash],
+    programId,
+  );
+}
+
+function findReputation(programId, wallet) {
+  return findPdaWithBump([REPUTATION_SEED, wallet.toBuffer()], programId);
 }
 
 function findListing(programId, ticketMint) {
@@ -199,6 +223,15 @@ async function sendTransaction(connection, signer, instructions) {
   throw lastError;
 }
 
+async function fetchClusterId(connection, programId) {
+  const admin = findAdminConfig(programId);
+  const info = await connection.getAccountInfo(admin, 'confirmed');
+  if (!info || info.data.length < 169) {
+    throw new Error(`admin config missing or not migrated at ${admin.toBase58()}`);
+  }
+  return Buffer.from(info.data.subarray(137, 169));
+}
+
 async function fetchGlobalAuthority(connection, programId) {
   const global = findGlobalConfig(programId);
   const info = await connection.getAccountInfo(global, 'confirmed');
@@ -208,11 +241,56 @@ async function fetchGlobalAuthority(connection, programId) {
   return new PublicKey(info.data.subarray(8, 40));
 }
 
-function buildExecuteInstruction(programId, buyer, treasury, payloadBytes, oracleSignature) {
-  const ticketMint = findTicketMint(programId, payloadBytes);
+// execute_shield requires the proof's ProofQuota and the buyer's Reputation to exist.
+function buildPurchaseSetupInstructions(programId, buyer, zkProofHash) {
+  return [
+    new TransactionInstruction({
+      programId,
+      keys: [
+        { pubkey: buyer.publicKey, isSigner: true, isWritable: true },
+        { pubkey: findSaleConfig(programId), isSigner: false, isWritable: false },
+        { pubkey: findProofQuota(programId, zkProofHash)[0], isSigner: false, isWritable: true },
+        { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+      ],
+      data: Buffer.concat([discriminator('initialize_proof_quota'), Buffer.from(zkProofHash)]),
+    }),
+    new TransactionInstruction({
+      programId,
+      keys: [
+        { pubkey: buyer.publicKey, isSigner: true, isWritable: true },
+        { pubkey: buyer.publicKey, isSigner: false, isWritable: false },
+        { pubkey: findReputation(programId, buyer.publicKey)[0], isSigner: false, isWritable: true },
+        { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+      ],
+      data: Buffer.from(discriminator('initialize_reputation')),
+    }),
+  ];
+}
+
+// The trailing bytes are ShieldAccountBumps, in field order. ticket_class and membership
+// are optional accounts; the program id stands in for None.
+function buildExecuteInstruction(
+  programId,
+  buyer,
+  treasury,
+  payloadBytes,
+  oracleSignature,
+  sigInstructionIndex,
+) {
+  const [proofUse, proofUseBump] = findProofUse(programId, payloadBytes);
+  const [ticketMint, ticketMintBump] = findTicketMint(programId, payloadBytes);
+  const [ticketMintAuthority, ticketMintAuthorityBump] = findTicketMintAuthority(programId);
+  const [userTicketToken, userTicketTokenBump] = findTicketToken(
+    programId,
+    ticketMint,
+    buyer.publicKey,
+  );
+  const [ticketReceipt, ticketReceiptBump] = findTicketReceipt(programId, ticketMint);
+  const [proofQuota, proofQuotaBump] = findProofQuota(programId, payloadBytes.subarray(61, 93));
+  const [reputation, reputationBump] = findReputation(programId, buyer.publicKey);
   return {
     ticketMint,
-    ticketReceipt: findTicketReceipt(programId, ticketMint),
+    ticketReceipt,
     ix: new TransactionInstruction({
       programId,
       keys: [
@@ -221,16 +299,35 @@ function buildExecuteInstruction(programId, buyer, treasury, payloadBytes, oracl
         { pubkey: treasury, isSigner: false, isWritable: true },
         { pubkey: findAdminConfig(programId), isSigner: false, isWritable: false },
         { pubkey: SYSVAR_INSTRUCTIONS_PUBKEY, isSigner: false, isWritable: false },
-        { pubkey: findProofUse(programId, payloadBytes), isSigner: false, isWritable: true },
+        { pubkey: proofUse, isSigner: false, isWritable: true },
         { pubkey: ticketMint, isSigner: false, isWritable: true },
-        { pubkey: findTicketMintAuthority(programId), isSigner: false, isWritable: false },
-        { pubkey: findTicketToken(programId, ticketMint, buyer.publicKey), isSigner: false, isWritable: true },
-        { pubkey: findTicketReceipt(programId, ticketMint), isSigner: false, isWritable: true },
+        { pubkey: ticketMintAuthority, isSigner: false, isWritable: false },
+        { pubkey: userTicketToken, isSigner: false, isWritable: true },
+        { pubkey: ticketReceipt, isSigner: false, isWritable: true },
         { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
         { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
         { pubkey: SYSVAR_RENT_PUBKEY, isSigner: false, isWritable: false },
+        { pubkey: findSaleConfig(programId), isSigner: false, isWritable: false },
+        { pubkey: proofQuota, isSigner: false, isWritable: true },
+        { pubkey: programId, isSigner: false, isWritable: false },
+        { pubkey: programId, isSigner: false, isWritable: false },
+        { pubkey: reputation, isSigner: false, isWritable: true },
       ],
-      data: Buffer.concat([discriminator('execute_shield'), payloadBytes, oracleSignature]),
+      data: Buffer.concat([
+        discriminator('execute_shield'),
+        payloadBytes,
+        oracleSignature,
+        encodeU16LE(sigInstructionIndex),
+        Buffer.from([
+          proofUseBump,
+          ticketMintBump,
+          ticketMintAuthorityBump,
+          userTicketTokenBump,
+          ticketReceiptBump,
+          proofQuotaBump,
+          reputationBump,
+        ]),
+      ]),
     }),
   };
 }
@@ -241,19 +338,30 @@ function buildListInstruction(programId, seller, ticketMint, askPriceLamports) {
     keys: [
       { pubkey: seller.publicKey, isSigner: true, isWritable: true },
       { pubkey: ticketMint, isSigner: false, isWritable: false },
-      { pubkey: findTicketReceipt(programId, ticketMint), isSigner: false, isWritable: true },
-      { pubkey: findTicketToken(programId, ticketMint, seller.publicKey), isSigner: false, isWritable: true },
+      { pubkey: findTicketReceipt(programId, ticketMint)[0], isSigner: false, isWritable: true },
+      { pubkey: findTicketToken(programId, ticketMint, seller.publicKey)[0], isSigner: false, isWritable: true },
       { pubkey: findListing(programId, ticketMint), isSigner: false, isWritable: true },
       { pubkey: findEscrow(programId, ticketMint), isSigner: false, isWritable: true },
       { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
       { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
       { pubkey: SYSVAR_RENT_PUBKEY, isSigner: false, isWritable: false },
+      { pubkey: findSaleConfig(programId), isSigner: false, isWritable: false },
+      { pubkey: findReputation(programId, seller.publicKey)[0], isSigner: false, isWritable: true },
     ],
     data: Buffer.concat([discriminator('list_ticket'), encodeU64LE(askPriceLamports)]),
   });
 }
 
-function buildFillInstruction(programId, buyer, sellerPubkey, feeRecipient, ticketMint) {
+function buildFillInstruction(
+  programId,
+  buyer,
+  sellerPubkey,
+  feeRecipient,
+  ticketMint,
+  payloadBytes,
+  oracleSignature,
+  sigInstructionIndex,
+) {
   return new TransactionInstruction({
     programId,
     keys: [
@@ -262,15 +370,25 @@ function buildFillInstruction(programId, buyer, sellerPubkey, feeRecipient, tick
       { pubkey: findGlobalConfig(programId), isSigner: false, isWritable: false },
       { pubkey: feeRecipient, isSigner: false, isWritable: true },
       { pubkey: ticketMint, isSigner: false, isWritable: false },
-      { pubkey: findTicketReceipt(programId, ticketMint), isSigner: false, isWritable: true },
+      { pubkey: findTicketReceipt(programId, ticketMint)[0], isSigner: false, isWritable: true },
       { pubkey: findListing(programId, ticketMint), isSigner: false, isWritable: true },
-      { pubkey: findTicketToken(programId, ticketMint, buyer.publicKey), isSigner: false, isWritable: true },
+      { pubkey: findTicketToken(programId, ticketMint, buyer.publicKey)[0], isSigner: false, isWritable: true },
       { pubkey: findEscrow(programId, ticketMint), isSigner: false, isWritable: true },
       { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
       { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
       { pubkey: SYSVAR_RENT_PUBKEY, isSigner: false, isWritable: false },
+      { pubkey: findAdminConfig(programId), isSigner: false, isWritable: false },
+      { pubkey: findSaleConfig(programId), isSigner: false, isWritable: false },
+      { pubkey: SYSVAR_INSTRUCTIONS_PUBKEY, isSigner: false, isWritable: false },
+      { pubkey: findReputation(programId, sellerPubkey)[0], isSigner: false, isWritable: true },
+      { pubkey: findProofUse(programId, payloadBytes)[0], isSigner: false, isWritable: true },
     ],
-    data: Buffer.from(discriminator('fill_ticket_listing')),
+    data: Buffer.concat([
+      discriminator('fill_ticket_listing'),
+      payloadBytes,
+      oracleSignature,
+      encodeU16LE(sigInstructionIndex),
+    ]),
   });
 }
 
@@ -323,37 +441,59 @@ async function main() {
     console.warn(`[smoke_ticket_v2] treasury authority mismatch: expected ${treasuryAuthority.publicKey.toBase58()} got ${treasury.toBase58()}`);
   }
 
-  const payload = {
-    policy_version: 0,
-    user_pubkey: buyerOne.publicKey.toBytes(),
-    initial_price: SMOKE_INITIAL_PRICE_LAMPORTS,
-    sales_velocity_bps: SMOKE_SALES_VELOCITY_BPS,
-    time_elapsed: SMOKE_TIME_ELAPSED,
-    dignity_score: SMOKE_DIGNITY_SCORE,
-    adapter_mask: 0b0000_0111,
-    user_mode: 2,
-    zk_provider: 1,
-    zk_proof_hash: crypto.randomBytes(32),
-    scoring_model_hash: computeScoringModelHash(),
-    attestation_expiry: BigInt(Math.floor(Date.now() / 1000) + 300),
-    nonce: BigInt(Date.now()),
+  const clusterIdBytes = await fetchClusterId(connection, programId);
+  const signedPayload = (buyer, purpose) => {
+    const payloadBytes = serializePayload({
+      policy_version: 0,
+      user_pubkey: buyer.publicKey.toBytes(),
+      initial_price: SMOKE_INITIAL_PRICE_LAMPORTS,
+      sales_velocity_bps: SMOKE_SALES_VELOCITY_BPS,
+      time_elapsed: SMOKE_TIME_ELAPSED,
+      dignity_score: SMOKE_DIGNITY_SCORE,
+      adapter_mask: 0b0000_0111,
+      user_mode: 2,
+      zk_provider: 1,
+      zk_proof_hash: crypto.randomBytes(32),
+      scoring_model_hash: computeScoringModelHash(),
+      attestation_expiry: BigInt(Math.floor(Date.now() / 1000) + 300),
+      nonce: BigInt(Date.now()),
+    });
+    const message = serializeShieldMessageV0(payloadBytes, {
+      programId,
+      clusterIdBytes,
+      purpose,
+      ticketClass: TICKET_CLASS_NONE,
+    });
+    const oracleSignature = signPayload(message, oracle);
+    return {
+      payloadBytes,
+      oracleSignature,
+      ed25519Ix: Ed25519Program.createInstructionWithPublicKey({
+        publicKey: oracle.publicKey.toBytes(),
+        message,
+        signature: oracleSignature,
+      }),
+    };
   };
-  const payloadBytes = serializePayload(payload);
-  const oracleSignature = signPayload(payloadBytes, oracle);
+
+  const purchase = signedPayload(buyerOne, SIGNING_PURPOSE_EXECUTE);
+  const setupIxs = buildPurchaseSetupInstructions(
+    programId,
+    buyerOne,
+    purchase.payloadBytes.subarray(61, 93),
+  );
   const execute = buildExecuteInstruction(
     programId,
     buyerOne,
     treasury,
-    payloadBytes,
-    oracleSignature,
+    purchase.payloadBytes,
+    purchase.oracleSignature,
+    setupIxs.length,
   );
 
   const executeSig = await sendTransaction(connection, buyerOne, [
-    Ed25519Program.createInstructionWithPublicKey({
-      publicKey: oracle.publicKey.toBytes(),
-      message: payloadBytes,
-      signature: oracleSignature,
-    }),
+    ...setupIxs,
+    purchase.ed25519Ix,
     execute.ix,
   ]);
 
@@ -362,13 +502,18 @@ async function main() {
     buildListInstruction(programId, buyerOne, execute.ticketMint, askPriceLamports),
   ]);
 
+  const resale = signedPayload(buyerTwo, SIGNING_PURPOSE_BUY_RESALE);
   const fillSig = await sendTransaction(connection, buyerTwo, [
+    resale.ed25519Ix,
     buildFillInstruction(
       programId,
       buyerTwo,
       buyerOne.publicKey,
       treasury,
       execute.ticketMint,
+      resale.payloadBytes,
+      resale.oracleSignature,
+      0,
     ),
   ]);
 
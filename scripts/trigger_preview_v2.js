@@ -19,6 +19,8 @@ const {
 const {
   discriminator,
   findAdminConfigPda,
+  findAuditLogPda,
+  findSaleConfigPda,
   buildPreviewTxInstructions,
 } = require('./tx_builder_v2');
 const { deriveProgramDataAddress } = require('./solana_program_ids');
@@ -123,10 +125,19 @@ function parseAdminConfigAccount(data) {
     oraclePubkey: Buffer.from(data.subarray(40, 72)),
     activeScoringModelHash: Buffer.from(data.subarray(72, 104)),
     bump: data[104],
+    clusterId: data.length >= 169 ? Buffer.from(data.subarray(137, 169)) : Buffer.alloc(32),
   };
 }
 
-async function ensureAdminConfig(connection, wallet, programId, adminConfigPda, oraclePubkeyBytes, modelHashBytes) {
+async function ensureAdminConfig(
+  connection,
+  wallet,
+  programId,
+  adminConfigPda,
+  oraclePubkeyBytes,
+  modelHashBytes,
+  clusterIdBytes,
+) {
   const adminInfo = await connection.getAccountInfo(adminConfigPda, 'confirmed');
   if (!adminInfo) {
     const programData = deriveProgramDataAddress(programId);
@@ -134,6 +145,7 @@ async function ensureAdminConfig(connection, wallet, programId, adminConfigPda,
       discriminator('global', 'initialize_admin_config'),
       oraclePubkeyBytes,
       modelHashBytes,
+      clusterIdBytes,
     ]);
 
     const initIx = buildIx(
@@ -144,6 +156,7 @@ async function ensureAdminConfig(connection, wallet, programId, adminConfigPda,
         { pubkey: programData, isSigner: false, isWritable: false },
         { pubkey: adminConfigPda, isSigner: false, isWritable: true },
         { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        { pubkey: findAuditLogPda(programId), isSigner: false, isWritable: true },
       ],
       initData,
     );
@@ -168,6 +181,7 @@ async function ensureAdminConfig(connection, wallet, programId, adminConfigPda,
       [
         { pubkey: wallet.publicKey, isSigner: true, isWritable: false },
         { pubkey: adminConfigPda, isSigner: false, isWritable: true },
+        { pubkey: findAuditLogPda(programId), isSigner: false, isWritable: true },
       ],
       rotateData,
     );
@@ -185,12 +199,28 @@ async function ensureAdminConfig(connection, wallet, programId, adminConfigPda,
       [
         { pubkey: wallet.publicKey, isSigner: true, isWritable: false },
         { pubkey: adminConfigPda, isSigner: false, isWritable: true },
+        { pubkey: findAuditLogPda(programId), isSigner: false, isWritable: true },
       ],
       setData,
     );
     const sig = await sendIxs(connection, wallet, [setIx]);
     console.log(`setScoringModelHash tx: ${sig}`);
   }
+
+  if (!parsed.clusterId.equals(clusterIdBytes)) {
+    const setData = Buffer.concat([discriminator('global', 'set_cluster_id'), clusterIdBytes]);
+    const setIx = buildIx(
+      programId,
+      [
+        { pubkey: wallet.publicKey, isSigner: true, isWritable: false },
+        { pubkey: adminConfigPda, isSigner: false, isWritable: true },
+        { pubkey: findAuditLogPda(programId), isSigner: false, isWritable: true },
+      ],
+      setData,
+    );
+    const sig = await sendIxs(connection, wallet, [setIx]);
+    console.log(`setClusterId tx: ${sig}`);
+  }
 }
 
 async function main() {
@@ -212,14 +242,28 @@ async function main() {
   const oracleSigBytes = Buffer.from(hexToBytes(api.oracle_signature_hex));
   const oraclePubkeyBytes = Buffer.from(new PublicKey(api.oracle_pubkey).toBytes());
   const modelHashBytes = Buffer.from(hexToBytes(api.scoring_model_hash_hex));
+  const clusterIdBytes = Buffer.from(new PublicKey(api.signing_domain.cluster_id).toBytes());
 
   const adminConfigPda = findAdminConfigPda(programId);
-  await ensureAdminConfig(connection, wallet, programId, adminConfigPda, oraclePubkeyBytes, modelHashBytes);
+  const globalConfigPda = PublicKey.findProgramAddressSync([Buffer.from('global_v2')], programId)[0];
+  await ensureAdminConfig(
+    connection,
+    wallet,
+    programId,
+    adminConfigPda,
+    oraclePubkeyBytes,
+    modelHashBytes,
+    clusterIdBytes,
+  );
 
   const { ed25519Ix, previewIx } = buildPreviewTxInstructions({
     programId,
     userPubkey: wallet.publicKey,
     adminConfigPda,
+    saleConfigPda: findSaleConfigPda(programId, globalConfigPda),
+    clusterIdBytes,
+    // sendIxs puts the compute budget instruction ahead of the ed25519 one.
+    sigInstructionIndex: previewCuLimit ? 1 : 0,
     payloadBytes,
     oracleSignatureBytes: oracleSigBytes,
     oraclePubkeyBytes,
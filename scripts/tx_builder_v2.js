@@ -11,6 +11,16 @@ const {
 const PAYLOAD_LEN = 141;
 const ORACLE_SIGNATURE_LEN = 64;
 
+// Mirrors `serialize_shield_message_v0`: an 86-byte signing domain followed by the payload.
+const SHIELD_DOMAIN_TAG = Buffer.from('UNFORGIVEN_SHIELD_V0', 'ascii');
+const SHIELD_DOMAIN_PROGRAM_ID_OFFSET = SHIELD_DOMAIN_TAG.length;
+const SHIELD_DOMAIN_CLUSTER_ID_OFFSET = SHIELD_DOMAIN_PROGRAM_ID_OFFSET + 32;
+const SHIELD_DOMAIN_PURPOSE_OFFSET = SHIELD_DOMAIN_CLUSTER_ID_OFFSET + 32;
+const SHIELD_DOMAIN_TICKET_CLASS_OFFSET = SHIELD_DOMAIN_PURPOSE_OFFSET + 1;
+const SHIELD_DOMAIN_LEN = SHIELD_DOMAIN_TICKET_CLASS_OFFSET + 1;
+const SIGNING_PURPOSE_PREVIEW = 1;
+const TICKET_CLASS_NONE = 0;
+
 function discriminator(namespace, name) {
   return crypto.createHash('sha256').update(`${namespace}:${name}`).digest().subarray(0, 8);
 }
@@ -26,7 +36,40 @@ function findAdminConfigPda(programId) {
   return PublicKey.findProgramAddressSync([Buffer.from('admin_config_v2')], pid)[0];
 }
 
-function buildPreviewInstructionData(payloadBytes, oracleSignatureBytes) {
+function findAuditLogPda(programId) {
+  const pid = new PublicKey(programId);
+  return PublicKey.findProgramAddressSync([Buffer.from('audit_log_v2')], pid)[0];
+}
+
+function findSaleConfigPda(programId, eventKey) {
+  const pid = new PublicKey(programId);
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from('sale_config_v2'), new PublicKey(eventKey).toBuffer()],
+    pid
+  )[0];
+}
+
+function serializeShieldMessageV0(payloadBytes, { programId, clusterIdBytes, purpose, ticketClass }) {
+  const payload = toBuffer(payloadBytes, 'payloadBytes');
+  const clusterId = toBuffer(clusterIdBytes, 'clusterIdBytes');
+  if (payload.length !== PAYLOAD_LEN) {
+    throw new Error(`payloadBytes length mismatch: expected ${PAYLOAD_LEN}, got ${payload.length}`);
+  }
+  if (clusterId.length !== 32) {
+    throw new Error(`clusterIdBytes length mismatch: expected 32, got ${clusterId.length}`);
+  }
+
+  const out = Buffer.alloc(SHIELD_DOMAIN_LEN + PAYLOAD_LEN);
+  SHIELD_DOMAIN_TAG.copy(out, 0);
+  new PublicKey(programId).toBuffer().copy(out, SHIELD_DOMAIN_PROGRAM_ID_OFFSET);
+  clusterId.copy(out, SHIELD_DOMAIN_CLUSTER_ID_OFFSET);
+  out[SHIELD_DOMAIN_PURPOSE_OFFSET] = purpose & 0xff;
+  out[SHIELD_DOMAIN_TICKET_CLASS_OFFSET] = (ticketClass ?? TICKET_CLASS_NONE) & 0xff;
+  payload.copy(out, SHIELD_DOMAIN_LEN);
+  return out;
+}
+
+function buildPreviewInstructionData(payloadBytes, oracleSignatureBytes, sigInstructionIndex = 0) {
   const payload = toBuffer(payloadBytes, 'payloadBytes');
   const sig = toBuffer(oracleSignatureBytes, 'oracleSignatureBytes');
 
@@ -39,13 +82,18 @@ function buildPreviewInstructionData(payloadBytes, oracleSignatureBytes) {
     );
   }
 
-  return Buffer.concat([discriminator('global', 'preview_price'), payload, sig]);
+  const sigIndex = Buffer.alloc(2);
+  sigIndex.writeUInt16LE(sigInstructionIndex, 0);
+  return Buffer.concat([discriminator('global', 'preview_price'), payload, sig, sigIndex]);
 }
 
 function buildPreviewTxInstructions({
   programId,
   userPubkey,
   adminConfigPda,
+  saleConfigPda,
+  clusterIdBytes,
+  sigInstructionIndex = 0,
   payloadBytes,
   oracleSignatureBytes,
   oraclePubkeyBytes,
@@ -53,6 +101,7 @@ function buildPreviewTxInstructions({
   const pid = new PublicKey(programId);
   const user = new PublicKey(userPubkey);
   const admin = new PublicKey(adminConfigPda);
+  const sale = new PublicKey(saleConfigPda);
   const payload = toBuffer(payloadBytes, 'payloadBytes');
   const sig = toBuffer(oracleSignatureBytes, 'oracleSignatureBytes');
   const oraclePk = toBuffer(oraclePubkeyBytes, 'oraclePubkeyBytes');
@@ -63,7 +112,14 @@ function buildPreviewTxInstructions({
 
   const ed25519Ix = Ed25519Program.createInstructionWithPublicKey({
     publicKey: Uint8Array.from(oraclePk),
-    message: Uint8Array.from(payload),
+    message: Uint8Array.from(
+      serializeShieldMessageV0(payload, {
+        programId: pid,
+        clusterIdBytes,
+        purpose: SIGNING_PURPOSE_PREVIEW,
+        ticketClass: TICKET_CLASS_NONE,
+      })
+    ),
     signature: Uint8Array.from(sig),
   });
 
@@ -73,8 +129,11 @@ function buildPreviewTxInstructions({
       { pubkey: user, isSigner: true, isWritable: false },
       { pubkey: admin, isSigner: false, isWritable: false },
       { pubkey: SYSVAR_INSTRUCTIONS_PUBKEY, isSigner: false, isWritable: false },
+      { pubkey: sale, isSigner: false, isWritable: false },
+      // Optional ticket_class left unset: Anchor reads the program id as `None`.
+      { pubkey: pid, isSigner: false, isWritable: false },
     ],
-    data: buildPreviewInstructionData(payload, sig),
+    data: buildPreviewInstructionData(payload, sig, sigInstructionIndex),
   });
 
   return { ed25519Ix, previewIx };
@@ -83,8 +142,14 @@ function buildPreviewTxInstructions({
 module.exports = {
   PAYLOAD_LEN,
   ORACLE_SIGNATURE_LEN,
+  SHIELD_DOMAIN_LEN,
+  SIGNING_PURPOSE_PREVIEW,
+  TICKET_CLASS_NONE,
   discriminator,
   findAdminConfigPda,
+  findAuditLogPda,
+  findSaleConfigPda,
+  serializeShieldMessageV0,
   buildPreviewInstructionData,
   buildPreviewTxInstructions,
 };
@@ -1,5 +1,4 @@
 import { PublicKey } from '@solana/web3.js';
-import nacl from 'tweetnacl';
 
 import {
   applyUserModeToDignityScore,
@@ -19,6 +18,14 @@ import {
   type UserMode,
 } from './shield_score.ts';
 import { oracleKeypair, oraclePubkeyBase58 } from './oracle.ts';
+import {
+  parseSigningPurposes,
+  signingClusterIdBase58,
+  signingProgramId,
+  signShieldPayloadForPurposes,
+  TICKET_CLASS_NONE,
+  type SigningPurpose,
+} from './signing_domain.ts';
 import { verifyReclaimProofBundle } from './reclaim_verify.ts';
 import { computeDignityScore as computeDignityFromAttestations } from '../../dignity-scoring/src/index.ts';
 
@@ -34,6 +41,8 @@ type ShieldScoreBody = {
   zk_provider?: number;
   policy_version?: number;
   proof_hash_hex?: string;
+  purposes?: SigningPurpose[];
+  ticket_class?: number;
 };
 
 type ShieldScoreResult = {
@@ -80,10 +89,14 @@ export function getOraclePubkeyPayload(): { oraclePubkey: string } {
 export function getShieldRuntimeConfigPayload(): {
   oraclePubkey: string;
   scoringModelHashHex: string;
+  programId: string;
+  clusterId: string;
 } {
   return {
     oraclePubkey: oraclePubkeyBase58(),
     scoringModelHashHex: toHex(Uint8Array.from(SCORING_MODEL_HASH)),
+    programId: signingProgramId().toBase58(),
+    clusterId: signingClusterIdBase58(),
   };
 }
 
@@ -98,6 +111,15 @@ export async function handleShieldScoreRequest(body: ShieldScoreBody): Promise<S
       return fail(400, 'invalid_mode', 'Invalid mode');
     }
 
+    const purposes = parseSigningPurposes(body.purposes);
+    if (!purposes) {
+      return fail(400, 'invalid_purposes', 'Invalid signing purposes');
+    }
+    const ticketClass = body.ticket_class ?? TICKET_CLASS_NONE;
+    if (!Number.isInteger(ticketClass) || ticketClass < 0 || ticketClass > 255) {
+      return fail(400, 'invalid_ticket_class', 'Invalid ticket class');
+    }
+
     const user = new PublicKey(body.wallet);
     const attestations = body.reclaim_attestations ?? [];
 
@@ -168,7 +190,8 @@ export async function handleShieldScoreRequest(body: ShieldScoreBody): Promise<S
 
     const payloadBytes = serializeShieldPayloadV0(payload);
     const oracle = oracleKeypair();
-    const signature = nacl.sign.detached(payloadBytes, oracle.secretKey);
+    const signed = signShieldPayloadForPurposes(payloadBytes, oracle.secretKey, purposes, ticketClass);
+    const signature = Buffer.from(signed.oracle_signatures[purposes[0]] as string, 'hex');
 
     if (mode === 'verified') {
       seenProofByUniq.set(uniq, Number(expiry));
@@ -210,7 +233,10 @@ export async function handleShieldScoreRequest(body: ShieldScoreBody): Promise<S
         },
         payload_hex: toHex(payloadBytes),
         oracle_signature_hex: toHex(signature),
-        oracle_signature_base64: Buffer.from(signature).toString('base64'),
+        oracle_signature_base64: signature.toString('base64'),
+        signing_purpose: purposes[0],
+        signing_domain: signed.signing_domain,
+        oracle_signatures: signed.oracle_signatures,
         oracle_pubkey: oracle.publicKey.toBase58(),
         uniq_key: uniq,
         scoring_model_hash_hex: toHex(Uint8Array.from(SCORING_MODEL_HASH)),
@@ -0,0 +1,141 @@
+import { PublicKey } from '@solana/web3.js';
+import nacl from 'tweetnacl';
+
+import { SHIELD_PAYLOAD_V0_LEN } from './shield_score.ts';
+
+// Mirrors `serialize_shield_message_v0` in programs/unforgiven_v2: the oracle signs an
+// 86-byte domain followed by the 141-byte payload (docs/v2/PAYLOAD_V0_SPEC.md).
+export const SHIELD_DOMAIN_TAG = Buffer.from('UNFORGIVEN_SHIELD_V0', 'ascii');
+export const SHIELD_DOMAIN_PROGRAM_ID_OFFSET = SHIELD_DOMAIN_TAG.length;
+export const SHIELD_DOMAIN_CLUSTER_ID_OFFSET = SHIELD_DOMAIN_PROGRAM_ID_OFFSET + 32;
+export const SHIELD_DOMAIN_PURPOSE_OFFSET = SHIELD_DOMAIN_CLUSTER_ID_OFFSET + 32;
+export const SHIELD_DOMAIN_TICKET_CLASS_OFFSET = SHIELD_DOMAIN_PURPOSE_OFFSET + 1;
+export const SHIELD_DOMAIN_LEN = SHIELD_DOMAIN_TICKET_CLASS_OFFSET + 1;
+export const SHIELD_SIGNED_MESSAGE_V0_LEN = SHIELD_DOMAIN_LEN + SHIELD_PAYLOAD_V0_LEN;
+export const TICKET_CLASS_NONE = 0;
+
+export const SIGNING_PURPOSES = {
+  preview: 1,
+  execute: 2,
+  buy_resale: 3,
+  waitlist: 4,
+  sealed_bid: 5,
+  transfer: 6,
+  start_session: 7,
+  ending_badge: 8,
+  membership: 9,
+  shield_gate: 10,
+} as const;
+
+export type SigningPurpose = keyof typeof SIGNING_PURPOSES;
+
+export const DEFAULT_SIGNING_PURPOSES: SigningPurpose[] = ['preview', 'execute'];
+
+const DEFAULT_PROGRAM_ID = '5VqDVHqeCJW1cWZgydjJLG68ShDGVZ45k6cE7hUY9uMW';
+
+// Genesis hashes of the public clusters; localnet has no fixed one.
+const KNOWN_CLUSTER_IDS: Record<string, string> = {
+  devnet: 'EtWTRABZaYq6iMfeYKouRu166VU2xqa1wcaWoxPkrZBG',
+  testnet: '4uhcVJyU9pJkvQyS88uRDiswHXSCkY3zQawwpjk2NsNY',
+  'mainnet-beta': '5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d',
+};
+
+export type SigningDomain = {
+  programId: PublicKey;
+  clusterId: Uint8Array;
+  purpose: number;
+  ticketClass: number;
+};
+
+export function signingProgramId(): PublicKey {
+  return new PublicKey(
+    process.env.UNFORGIVEN_V2_PROGRAM_ID || process.env.NEXT_PUBLIC_PROGRAM_ID || DEFAULT_PROGRAM_ID,
+  );
+}
+
+// `SHIELD_CLUSTER_ID` is the base58 genesis hash (`solana genesis-hash`) stored in
+// `AdminConfig.cluster_id`. It falls back to the public cluster named by
+// `NEXT_PUBLIC_SOLANA_CLUSTER`.
+export function signingClusterIdBase58(): string {
+  const explicit = process.env.SHIELD_CLUSTER_ID;
+  if (explicit) return explicit;
+
+  const cluster = process.env.NEXT_PUBLIC_SOLANA_CLUSTER || 'devnet';
+  const known = KNOWN_CLUSTER_IDS[cluster];
+  if (!known) {
+    throw new Error(
+      `SHIELD_CLUSTER_ID is required for cluster "${cluster}" (set it to the cluster genesis hash).`,
+    );
+  }
+  return known;
+}
+
+export function signingClusterId(): Uint8Array {
+  return new PublicKey(signingClusterIdBase58()).toBytes();
+}
+
+export function parseSigningPurposes(raw: unknown): SigningPurpose[] | null {
+  if (raw == null) return DEFAULT_SIGNING_PURPOSES;
+  if (!Array.isArray(raw) || raw.length === 0) return null;
+  const purposes: SigningPurpose[] = [];
+  for (const item of raw) {
+    if (typeof item !== 'string' || !(item in SIGNING_PURPOSES)) return null;
+    if (!purposes.includes(item as SigningPurpose)) purposes.push(item as SigningPurpose);
+  }
+  return purposes;
+}
+
+export function serializeShieldMessageV0(payloadBytes: Uint8Array, domain: SigningDomain): Uint8Array {
+  if (payloadBytes.length !== SHIELD_PAYLOAD_V0_LEN) {
+    throw new Error(`payload must be ${SHIELD_PAYLOAD_V0_LEN} bytes, got ${payloadBytes.length}`);
+  }
+  if (domain.clusterId.length !== 32) throw new Error('cluster_id must be 32 bytes');
+
+  const out = new Uint8Array(SHIELD_SIGNED_MESSAGE_V0_LEN);
+  out.set(SHIELD_DOMAIN_TAG, 0);
+  out.set(domain.programId.toBytes(), SHIELD_DOMAIN_PROGRAM_ID_OFFSET);
+  out.set(domain.clusterId, SHIELD_DOMAIN_CLUSTER_ID_OFFSET);
+  out[SHIELD_DOMAIN_PURPOSE_OFFSET] = domain.purpose & 0xff;
+  out[SHIELD_DOMAIN_TICKET_CLASS_OFFSET] = domain.ticketClass & 0xff;
+  out.set(payloadBytes, SHIELD_DOMAIN_LEN);
+  return out;
+}
+
+export type SignedPurposes = {
+  signing_domain: {
+    program_id: string;
+    cluster_id: string;
+    ticket_class: number;
+  };
+  oracle_signatures: Partial<Record<SigningPurpose, string>>;
+};
+
+// One signature per purpose: a signature only verifies for the purpose it was made for.
+export function signShieldPayloadForPurposes(
+  payloadBytes: Uint8Array,
+  secretKey: Uint8Array,
+  purposes: SigningPurpose[],
+  ticketClass: number = TICKET_CLASS_NONE,
+): SignedPurposes {
+  const programId = signingProgramId();
+  const clusterId = signingClusterId();
+  const signatures: Partial<Record<SigningPurpose, string>> = {};
+  for (const purpose of purposes) {
+    const message = serializeShieldMessageV0(payloadBytes, {
+      programId,
+      clusterId,
+      purpose: SIGNING_PURPOSES[purpose],
+      ticketClass,
+    });
+    signatures[purpose] = Buffer.from(nacl.sign.detached(message, secretKey)).toString('hex');
+  }
+
+  return {
+    signing_domain: {
+      program_id: programId.toBase58(),
+      cluster_id: signingClusterIdBase58(),
+      ticket_class: ticketClass,
+    },
+    oracle_signatures: signatures,
+  };
+}
@@ -6,6 +6,14 @@ import {
   validateAttestationWalletOwnership,
 } from "../lib/shield_score.ts";
 import { computeDignityScore as computeDignityFromAttestations } from "../services/dignity-scoring/src/index.ts";
+import { PublicKey } from "@solana/web3.js";
+import nacl from "tweetnacl";
+import {
+  SHIELD_DOMAIN_LEN,
+  SIGNING_PURPOSES,
+  serializeShieldMessageV0,
+  signShieldPayloadForPurposes,
+} from "../services/shield-oracle/src/signing_domain.ts";
 
 describe("Shield Score API helpers", () => {
   it("computes adapter score for github+spotify+twitter", () => {
@@ -79,4 +87,47 @@ describe("Shield Score API helpers", () => {
     expect(bytes[57]).to.equal(90);
     expect(bytes[58]).to.equal(0b111);
   });
+
+  it("signs the 86-byte domain followed by the payload, once per purpose", () => {
+    const programId = new PublicKey("5VqDVHqeCJW1cWZgydjJLG68ShDGVZ45k6cE7hUY9uMW");
+    const clusterBase58 = "EtWTRABZaYq6iMfeYKouRu166VU2xqa1wcaWoxPkrZBG";
+    const clusterId = new PublicKey(clusterBase58).toBytes();
+    const payloadBytes = new Uint8Array(141).fill(5);
+
+    const message = serializeShieldMessageV0(payloadBytes, {
+      programId,
+      clusterId,
+      purpose: SIGNING_PURPOSES.execute,
+      ticketClass: 3,
+    });
+    expect(SHIELD_DOMAIN_LEN).to.equal(86);
+    expect(message.length).to.equal(227);
+    expect(Buffer.from(message.slice(0, 20)).toString("ascii")).to.equal("UNFORGIVEN_SHIELD_V0");
+    expect(Buffer.from(message.slice(20, 52)).equals(programId.toBuffer())).to.equal(true);
+    expect(Buffer.from(message.slice(52, 84)).equals(Buffer.from(clusterId))).to.equal(true);
+    expect(message[84]).to.equal(2);
+    expect(message[85]).to.equal(3);
+    expect(Buffer.from(message.slice(86)).equals(Buffer.from(payloadBytes))).to.equal(true);
+
+    const oracle = nacl.sign.keyPair();
+    const prevCluster = process.env.SHIELD_CLUSTER_ID;
+    process.env.SHIELD_CLUSTER_ID = clusterBase58;
+    try {
+      const signed = signShieldPayloadForPurposes(payloadBytes, oracle.secretKey, ["preview", "execute"], 3);
+      const executeSig = Buffer.from(signed.oracle_signatures.execute as string, "hex");
+      const signedMessage = serializeShieldMessageV0(payloadBytes, {
+        programId: new PublicKey(signed.signing_domain.program_id),
+        clusterId,
+        purpose: SIGNING_PURPOSES.execute,
+        ticketClass: 3,
+      });
+      expect(nacl.sign.detached.verify(signedMessage, executeSig, oracle.publicKey)).to.equal(true);
+      expect(nacl.sign.detached.verify(payloadBytes, executeSig, oracle.publicKey)).to.equal(false);
+      expect(signed.oracle_signatures.preview).to.not.equal(signed.oracle_signatures.execute);
+      expect(signed.signing_domain.cluster_id).to.equal(clusterBase58);
+    } finally {
+      if (prevCluster === undefined) delete process.env.SHIELD_CLUSTER_ID;
+      else process.env.SHIELD_CLUSTER_ID = prevCluster;
+    }
+  });
 });
@@ -6,6 +6,9 @@ import {
   normalizeSignedProofPayload,
   parseTicketListingAccount,
   parseTicketReceiptAccount,
+  serializeShieldMessageV0,
+  SHIELD_DOMAIN_LEN,
+  SIGNING_PURPOSE_EXECUTE,
 } from '../lib/unforgiven-v2-client';
 import { PublicKey } from '@solana/web3.js';
 
@@ -105,4 +108,24 @@ describe('unforgiven v2 client helpers', () => {
     expect(listing?.askPriceLamports.toString()).to.equal('2000000000');
     expect(listing?.bump).to.equal(12);
   });
+
+  it('prefixes the signing domain before the payload', () => {
+    const programId = new PublicKey('5VqDVHqeCJW1cWZgydjJLG68ShDGVZ45k6cE7hUY9uMW');
+    const clusterId = new Uint8Array(32).fill(42);
+    const payload = new Uint8Array(141).fill(7);
+
+    const message = serializeShieldMessageV0(payload, {
+      programId,
+      clusterId,
+      purpose: SIGNING_PURPOSE_EXECUTE,
+    });
+    expect(SHIELD_DOMAIN_LEN).to.equal(86);
+    expect(message.length).to.equal(227);
+    expect(Buffer.from(message.slice(0, 20)).toString('ascii')).to.equal('UNFORGIVEN_SHIELD_V0');
+    expect(new PublicKey(message.slice(20, 52)).toBase58()).to.equal(programId.toBase58());
+    expect(message[52]).to.equal(42);
+    expect(message[84]).to.equal(SIGNING_PURPOSE_EXECUTE);
+    expect(message[85]).to.equal(0);
+    expect(message[86]).to.equal(7);
+  });
 });
@@ -5,9 +5,14 @@ import {
   buildShieldPayloadV0,
   calculateShieldQuote,
   serializeShieldPayloadV0,
+  SIGNING_PURPOSE,
 } from "../packages/universal-shield-sdk/src/index.ts";
 
 const USER = "11111111111111111111111111111111";
+const SIGNING_DOMAIN = {
+  programId: "5VqDVHqeCJW1cWZgydjJLG68ShDGVZ45k6cE7hUY9uMW",
+  clusterId: "EtWTRABZaYq6iMfeYKouRu166VU2xqa1wcaWoxPkrZBG",
+};
 
 describe("Universal Shield SDK", () => {
   it("serializes payload deterministically with frozen 141-byte layout", () => {
@@ -122,17 +127,22 @@ describe("Universal Shield SDK", () => {
           { provider: "twitter", data: { account_age_days: 700, activity_score: 99 } },
         ],
       },
-      (payloadBytes) => {
+      (message) => {
         const sig = new Uint8Array(64);
-        sig.set(payloadBytes.slice(0, 32), 0);
-        sig.set(payloadBytes.slice(0, 32), 32);
+        sig.set(message.slice(0, 32), 0);
+        sig.set(message.slice(0, 32), 32);
         return sig;
       },
+      SIGNING_DOMAIN,
     );
 
     expect(req.quote.blocked).to.equal(false);
     expect(req.oracleSignature.length).to.equal(64);
     expect(req.oracleSignatureHex.startsWith("0x")).to.equal(true);
+    expect(req.signedMessage.length).to.equal(86 + 141);
+    expect(new TextDecoder().decode(req.signedMessage.slice(0, 20))).to.equal("UNFORGIVEN_SHIELD_V0");
+    expect(req.signedMessage[84]).to.equal(SIGNING_PURPOSE.execute);
+    expect(req.signedMessage.slice(86)).to.deep.equal(req.quote.payloadBytes);
   });
 
   it("rejects execute request when shield marks payload as blocked", async () => {
@@ -147,6 +157,7 @@ describe("Universal Shield SDK", () => {
           mode: "bot_suspected",
         },
         () => new Uint8Array(64),
+        SIGNING_DOMAIN,
       );
     } catch (e) {
       err = e as Error;